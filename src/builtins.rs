@@ -0,0 +1,296 @@
+//! Built-in tasks.
+//!
+//! Hooks can reference checks that ship with `huk` itself with a `huk:`
+//! prefix, as in `"commit-msg": "huk:conventional"`, so that common policies
+//! need no extra tooling. A built-in receives the arguments Git passes to the
+//! hook (and any written after ` -- ` in the reference), reads its settings
+//! from the top-level `huk` object, and either passes or fails with a report
+//! explaining what is wrong.
+
+pub mod conventional;
+pub mod staged;
+pub mod template;
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use regex::Regex;
+use thiserror::Error;
+
+use crate::config::HookConfig;
+use crate::git::git_output;
+use staged::Index;
+use staged::Syntax;
+
+/// Names of the available built-in tasks, without the `huk:` prefix.
+pub const BUILTINS: [&str; 11] = [
+  "conventional",
+  "commit-template",
+  "no-large-files",
+  "no-merge-conflicts",
+  "trailing-whitespace",
+  "end-of-file-fixer",
+  "check-json",
+  "check-yaml",
+  "check-toml",
+  "no-private-keys",
+  "forbid-branch",
+];
+
+/// Whether `name` (without the `huk:` prefix) is a built-in task.
+pub fn is_builtin(name: &str) -> bool {
+  BUILTINS.contains(&name)
+}
+
+/// The result of running a built-in task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+  /// Whether the check passed.
+  pub success: bool,
+  /// Explanation printed after the task runs; empty when there is nothing
+  /// to report.
+  pub report:  String,
+}
+
+/// Errors that prevent a built-in task from running at all.
+#[derive(Error, Debug)]
+pub enum BuiltinError {
+  /// The referenced built-in does not exist.
+  #[error("unknown built-in task 'huk:{0}' (available: {list})", list = BUILTINS.join(", "))]
+  Unknown(String),
+  /// A required argument was not passed.
+  #[error("huk:{name} expects {expected} as its first argument")]
+  MissingArgument {
+    name:     &'static str,
+    expected: &'static str,
+  },
+  /// A file the task needs could not be read.
+  #[error("failed to read {0}: {1}")]
+  Io(PathBuf, #[source] io::Error),
+  /// A configured regular expression does not compile.
+  #[error("invalid pattern '{pattern}' for huk:{name}: {source}")]
+  InvalidPattern {
+    name:    &'static str,
+    pattern: String,
+    #[source]
+    source:  regex::Error,
+  },
+  /// A Git command needed to inspect the repository failed.
+  #[error("`git {command}` failed: {detail}")]
+  Git { command: String, detail: String },
+}
+
+/// Run the built-in task `name` with `args`, resolving relative paths
+/// against `cwd`. `color` enables ANSI colors in the report.
+pub fn run(
+  name: &str,
+  args: &[String],
+  cfg: &HookConfig,
+  cwd: &Path,
+  color: bool,
+) -> Result<Outcome, BuiltinError> {
+  match name {
+    "conventional" => {
+      let path = args.first().ok_or(BuiltinError::MissingArgument {
+        name:     "conventional",
+        expected: "the commit message file",
+      })?;
+      let path = cwd.join(path);
+      let message = std::fs::read_to_string(&path)
+        .map_err(|e| BuiltinError::Io(path.clone(), e))?;
+      let rules = &cfg.settings.conventional;
+      let problems = conventional::lint(&message, rules, comment_char(cwd));
+      Ok(Outcome {
+        success: problems.is_empty(),
+        report:  conventional::format_problems(&message, &problems, color),
+      })
+    }
+    "commit-template" => {
+      let path = args.first().ok_or(BuiltinError::MissingArgument {
+        name:     "commit-template",
+        expected: "the commit message file",
+      })?;
+      let path = cwd.join(path);
+      let message = std::fs::read_to_string(&path)
+        .map_err(|e| BuiltinError::Io(path.clone(), e))?;
+      let rules = &cfg.settings.commit_template;
+      let pattern = Regex::new(&rules.issue_pattern).map_err(|source| {
+        BuiltinError::InvalidPattern {
+          name: "commit-template",
+          pattern: rules.issue_pattern.clone(),
+          source,
+        }
+      })?;
+      let branch = git_output(cwd, &["symbolic-ref", "--short", "-q", "HEAD"]);
+      let issue = branch
+        .as_deref()
+        .and_then(|branch| template::issue_key(&pattern, branch));
+      let contents = match &rules.template {
+        Some(file) => {
+          let file = cfg.source.base_dir().join(file);
+          let text = std::fs::read_to_string(&file)
+            .map_err(|e| BuiltinError::Io(file, e))?;
+          Some(
+            text
+              .replace("{issue}", issue.as_deref().unwrap_or_default())
+              .replace("{branch}", branch.as_deref().unwrap_or_default()),
+          )
+        }
+        None => None,
+      };
+      let prepared = template::prepare(
+        &message,
+        args.get(1).map(String::as_str).filter(|s| !s.is_empty()),
+        issue.as_deref(),
+        contents.as_deref(),
+        rules,
+        comment_char(cwd),
+      );
+      if prepared != message {
+        std::fs::write(&path, prepared)
+          .map_err(|e| BuiltinError::Io(path.clone(), e))?;
+      }
+      Ok(Outcome {
+        success: true,
+        report:  String::new(),
+      })
+    }
+    "forbid-branch" => {
+      let branch = git_output(cwd, &["symbolic-ref", "--short", "-q", "HEAD"]);
+      let protected = &cfg.settings.protected_branches;
+      Ok(match branch.filter(|b| protected.contains(b)) {
+        Some(branch) => Outcome {
+          success: false,
+          report:  staged::format_findings(
+            &format!("committing directly to '{branch}' is not allowed"),
+            &[],
+            color,
+          ),
+        },
+        None => Outcome {
+          success: true,
+          report:  String::new(),
+        },
+      })
+    }
+    "no-large-files" => {
+      let index = Index::read(cwd)?;
+      let max = cfg.settings.max_file_size;
+      let findings = staged::large_files(&index, max)?;
+      Ok(outcome(
+        "staged files exceed the size limit",
+        findings,
+        color,
+      ))
+    }
+    "no-merge-conflicts" => {
+      let index = Index::read(cwd)?;
+      let findings = index
+        .contents(|_| true)?
+        .iter()
+        .flat_map(|(file, data)| staged::merge_conflicts(&file.path, data))
+        .collect();
+      Ok(outcome(
+        "staged files contain merge conflict markers",
+        findings,
+        color,
+      ))
+    }
+    "no-private-keys" => {
+      let index = Index::read(cwd)?;
+      let findings = index
+        .contents(|_| true)?
+        .iter()
+        .flat_map(|(file, data)| staged::private_keys(&file.path, data))
+        .collect();
+      Ok(outcome(
+        "staged files contain private keys",
+        findings,
+        color,
+      ))
+    }
+    "check-json" | "check-yaml" | "check-toml" => {
+      let (syntax, label) = match name {
+        "check-json" => (Syntax::Json, "JSON"),
+        "check-yaml" => (Syntax::Yaml, "YAML"),
+        _ => (Syntax::Toml, "TOML"),
+      };
+      let index = Index::read(cwd)?;
+      let findings = index
+        .contents(|file| syntax.matches(&file.path))?
+        .iter()
+        .filter_map(|(file, data)| syntax.check(&file.path, data))
+        .collect();
+      Ok(outcome(
+        &format!("staged files are not valid {label}"),
+        findings,
+        color,
+      ))
+    }
+    "trailing-whitespace" | "end-of-file-fixer" => {
+      let (fixer, summary): (staged::Fixer, _) = match name {
+        "trailing-whitespace" => (
+          staged::strip_trailing_whitespace,
+          "removed trailing whitespace from staged files",
+        ),
+        _ => (
+          staged::fix_end_of_file,
+          "fixed the final newline of staged files",
+        ),
+      };
+      let index = Index::read(cwd)?;
+      let mut findings = Vec::new();
+      for (file, data) in index.contents(|_| true)? {
+        if staged::is_binary(&data) {
+          continue;
+        }
+        let fixed = fixer(&data);
+        if fixed != data {
+          let in_sync = index.fix(file, &data, &fixed)?;
+          findings.push(staged::Finding {
+            path:    file.path.clone(),
+            line:    None,
+            message: if in_sync {
+              "fixed".to_string()
+            } else {
+              "fixed in the index only; the working tree has unstaged changes"
+                .to_string()
+            },
+          });
+        }
+      }
+      let summary = format!("{summary}; review the changes and commit again");
+      Ok(outcome(&summary, findings, color))
+    }
+    _ => Err(BuiltinError::Unknown(name.to_string())),
+  }
+}
+
+/// The outcome of a check over staged files, which passes when nothing was
+/// found.
+fn outcome(
+  summary: &str,
+  findings: Vec<staged::Finding>,
+  color: bool,
+) -> Outcome {
+  if findings.is_empty() {
+    return Outcome {
+      success: true,
+      report:  String::new(),
+    };
+  }
+  Outcome {
+    success: false,
+    report:  staged::format_findings(summary, &findings, color),
+  }
+}
+
+/// The character starting comment lines in commit messages, from Git's
+/// `core.commentChar` (`#` unless configured, or when set to `auto`).
+fn comment_char(cwd: &Path) -> char {
+  git_output(cwd, &["config", "--get", "core.commentChar"])
+    .filter(|value| value != "auto")
+    .and_then(|value| value.chars().next())
+    .unwrap_or('#')
+}
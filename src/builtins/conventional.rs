@@ -0,0 +1,300 @@
+//! `huk:conventional`: check commit messages against the Conventional
+//! Commits specification.
+//!
+//! The header must read `type(scope)!: description`, where the scope and `!`
+//! are optional. The allowed types and scopes, whether a scope is required
+//! and the maximum line lengths are configured in the `conventional` object
+//! of the `huk` settings. Comment lines and everything below the scissors
+//! line written by `git commit --verbose` are ignored, as Git strips them
+//! from the final message; so are messages Git generates for merges, reverts
+//! and `--fixup`/`--squash` commits.
+
+use serde::Deserialize;
+
+/// Rules for `huk:conventional`, read from `huk.conventional`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ConventionalRules {
+  /// Allowed commit types.
+  pub types:                Vec<String>,
+  /// Allowed scopes. Any scope is accepted when empty.
+  pub scopes:               Vec<String>,
+  /// Reject headers without a scope.
+  pub require_scope:        bool,
+  /// Maximum length of the subject (first) line, in characters.
+  pub subject_max_length:   usize,
+  /// Maximum length of each line of the body and footers, in characters;
+  /// `0` disables the check. Lines without spaces, such as long URLs, are
+  /// exempt since they cannot be wrapped.
+  pub body_max_line_length: usize,
+}
+
+impl Default for ConventionalRules {
+  fn default() -> Self {
+    Self {
+      types:                [
+        "build", "chore", "ci", "docs", "feat", "fix", "perf", "refactor",
+        "revert", "style", "test",
+      ]
+      .map(String::from)
+      .to_vec(),
+      scopes:               Vec::new(),
+      require_scope:        false,
+      subject_max_length:   72,
+      body_max_line_length: 100,
+    }
+  }
+}
+
+/// A rule violation, pointing at a span of one line of the message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+  /// 1-based line number in the message file.
+  pub line:    usize,
+  /// Character columns `start..end` of the offending text. An empty span
+  /// points between two characters.
+  pub start:   usize,
+  pub end:     usize,
+  pub message: String,
+}
+
+/// Prefixes of the messages Git writes itself, which are not checked.
+const GENERATED: [&str; 5] =
+  ["Merge ", "Revert \"", "fixup! ", "squash! ", "amend! "];
+
+/// Check a commit message as written to the message file. `comment_char`
+/// is Git's `core.commentChar`.
+pub fn lint(
+  message: &str,
+  rules: &ConventionalRules,
+  comment_char: char,
+) -> Vec<Problem> {
+  let scissors = format!(
+    "{comment_char} ------------------------ >8 ------------------------"
+  );
+  // Keep the original line numbers while dropping what Git strips.
+  let mut lines: Vec<(usize, &str)> = message
+    .lines()
+    .enumerate()
+    .take_while(|(_, line)| *line != scissors)
+    .filter(|(_, line)| !line.starts_with(comment_char))
+    .map(|(i, line)| (i + 1, line.trim_end()))
+    .skip_while(|(_, line)| line.is_empty())
+    .collect();
+  while lines.last().is_some_and(|(_, line)| line.is_empty()) {
+    lines.pop();
+  }
+  let Some(&(header_line, header)) = lines.first() else {
+    // Git aborts commits with an empty message by itself.
+    return Vec::new();
+  };
+  if GENERATED.iter().any(|prefix| header.starts_with(prefix)) {
+    return Vec::new();
+  }
+
+  let mut problems = Vec::new();
+  let mut problem = |line: usize, start: usize, end: usize, message: String| {
+    problems.push(Problem {
+      line,
+      start,
+      end,
+      message,
+    })
+  };
+  let header_len = header.chars().count();
+  if header_len > rules.subject_max_length {
+    problem(
+      header_line,
+      rules.subject_max_length,
+      header_len,
+      format!(
+        "subject line is {header_len} characters long (max {})",
+        rules.subject_max_length
+      ),
+    );
+  }
+  check_header(header, rules, &mut |start, end, message| {
+    problem(header_line, start, end, message)
+  });
+
+  if let Some(&(line, text)) = lines.get(1)
+    && !text.is_empty()
+  {
+    problem(
+      line,
+      0,
+      text.chars().count(),
+      "leave a blank line between the subject and the body".into(),
+    );
+  }
+  if rules.body_max_line_length > 0 {
+    for &(line, text) in &lines[1..] {
+      let len = text.chars().count();
+      if len > rules.body_max_line_length && text.trim().contains(' ') {
+        problem(
+          line,
+          rules.body_max_line_length,
+          len,
+          format!(
+            "line is {len} characters long (max {})",
+            rules.body_max_line_length
+          ),
+        );
+      }
+    }
+  }
+  problems.sort_by_key(|p| (p.line, p.start));
+  problems
+}
+
+/// Check `type(scope)!: description`, reporting problems as character spans
+/// of the header.
+fn check_header(
+  header: &str,
+  rules: &ConventionalRules,
+  problem: &mut dyn FnMut(usize, usize, String),
+) {
+  let col = |byte: usize| header[..byte].chars().count();
+  let Some(colon) = header.find(':') else {
+    problem(
+      0,
+      col(header.len()),
+      "expected 'type(scope): description'".into(),
+    );
+    return;
+  };
+  let prefix = &header[..colon];
+  let type_end = prefix.find(['(', '!']).unwrap_or(prefix.len());
+  let kind = &prefix[..type_end];
+  let mut rest = &prefix[type_end..];
+
+  if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_lowercase()) {
+    problem(
+      0,
+      col(type_end),
+      "type must be a lowercase word such as 'feat' or 'fix'".into(),
+    );
+  } else if !rules.types.iter().any(|t| t == kind) {
+    problem(
+      0,
+      col(type_end),
+      format!(
+        "type '{kind}' is not allowed (expected one of: {})",
+        rules.types.join(", ")
+      ),
+    );
+  }
+
+  let mut scope = None;
+  if let Some(inner) = rest.strip_prefix('(') {
+    let Some(close) = inner.find(')') else {
+      problem(type_end, colon, "scope is missing its closing ')'".into());
+      return;
+    };
+    let start = type_end + 1;
+    scope = Some((start, &inner[..close]));
+    rest = &inner[close + 1..];
+  }
+  let malformed = match rest {
+    "" | "!" => None,
+    other => Some(other),
+  };
+  if let Some(other) = malformed {
+    let start = colon - other.len();
+    problem(col(start), col(colon), "unexpected text before ':'".into());
+  }
+  match scope {
+    Some((start, "")) => problem(
+      col(start - 1),
+      col(start + 1),
+      "scope must not be empty".into(),
+    ),
+    Some((start, name))
+      if !rules.scopes.is_empty()
+        && !rules.scopes.iter().any(|s| s == name) =>
+    {
+      problem(
+        col(start),
+        col(start + name.len()),
+        format!(
+          "scope '{name}' is not allowed (expected one of: {})",
+          rules.scopes.join(", ")
+        ),
+      )
+    }
+    None if rules.require_scope => problem(
+      col(type_end),
+      col(type_end),
+      "a scope is required, as in 'type(scope): description'".into(),
+    ),
+    _ => {}
+  }
+
+  let description = &header[colon + 1..];
+  if !description.starts_with(' ') {
+    problem(
+      col(colon + 1),
+      col(colon + 1),
+      "expected a space after ':'".into(),
+    );
+  }
+  if description.trim().is_empty() {
+    problem(
+      col(colon),
+      col(header.len()),
+      "description must not be empty".into(),
+    );
+  } else if description.ends_with('.') {
+    let end = col(header.len());
+    problem(
+      end - 1,
+      end,
+      "description must not end with a period".into(),
+    );
+  }
+}
+
+/// Render problems with the lines they refer to, underlining the offending
+/// text. Returns an empty string when there are no problems.
+pub fn format_problems(
+  message: &str,
+  problems: &[Problem],
+  color: bool,
+) -> String {
+  if problems.is_empty() {
+    return String::new();
+  }
+  let paint = |code: &str, text: &str| {
+    if color {
+      format!("\x1b[{code}m{text}\x1b[0m")
+    } else {
+      text.to_string()
+    }
+  };
+  let lines: Vec<&str> = message.lines().collect();
+  let width = problems
+    .iter()
+    .map(|p| p.line)
+    .max()
+    .unwrap_or(1)
+    .to_string()
+    .len();
+  let gutter = paint("2", &format!("{:width$} |", ""));
+  let mut out = format!(
+    "{} commit message does not follow Conventional Commits\n",
+    paint("1;31", "error:")
+  );
+  for problem in problems {
+    let text = lines.get(problem.line - 1).copied().unwrap_or_default();
+    let number = paint("2", &format!("{:>width$} |", problem.line));
+    out.push_str(&format!("{number} {}\n", text.trim_end()));
+    let marker = "^".repeat((problem.end - problem.start).max(1));
+    out.push_str(&format!(
+      "{gutter} {}{} {}\n",
+      " ".repeat(problem.start),
+      paint("31", &marker),
+      problem.message
+    ));
+  }
+  out
+}
@@ -0,0 +1,418 @@
+//! Checks over the staged content of a commit, modelled on the hooks of
+//! [pre-commit-hooks](https://github.com/pre-commit/pre-commit-hooks).
+//!
+//! Files are read from the index rather than the working tree, so that what
+//! is checked is exactly what is about to be committed, even when only part
+//! of a file is staged. The fixers (`trailing-whitespace` and
+//! `end-of-file-fixer`) rewrite the staged blob, and the working tree copy
+//! when it has no unstaged changes, then fail so that the result can be
+//! reviewed before committing again.
+
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Stdio;
+
+use super::BuiltinError;
+
+/// Markers that begin private keys, from pre-commit-hooks'
+/// `detect-private-key`.
+const PRIVATE_KEY_MARKERS: [&str; 10] = [
+  "BEGIN RSA PRIVATE KEY",
+  "BEGIN DSA PRIVATE KEY",
+  "BEGIN EC PRIVATE KEY",
+  "BEGIN OPENSSH PRIVATE KEY",
+  "BEGIN PRIVATE KEY",
+  "BEGIN ENCRYPTED PRIVATE KEY",
+  "BEGIN SSH2 ENCRYPTED PRIVATE KEY",
+  "BEGIN PGP PRIVATE KEY BLOCK",
+  "BEGIN OpenVPN Static key V1",
+  "PuTTY-User-Key-File-2",
+];
+
+/// Lines Git writes around the sides of a conflict.
+const CONFLICT_MARKERS: [&str; 3] = ["<<<<<<< ", "||||||| ", ">>>>>>> "];
+
+/// A file added, copied, modified or renamed in the index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StagedFile {
+  /// Path relative to the top of the working tree.
+  pub path: String,
+  /// Octal file mode, such as `100644`.
+  pub mode: String,
+  /// Object id of the staged blob.
+  pub oid:  String,
+}
+
+impl StagedFile {
+  /// Whether this is a regular file, as opposed to a symlink or submodule.
+  fn is_regular(&self) -> bool {
+    self.mode.starts_with("100")
+  }
+}
+
+/// A problem found in a staged file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+  pub path:    String,
+  /// 1-based line number, when the problem is on a particular line.
+  pub line:    Option<usize>,
+  pub message: String,
+}
+
+/// The staged files of the repository containing `cwd`, together with the
+/// top of its working tree.
+pub struct Index {
+  pub top:   PathBuf,
+  pub files: Vec<StagedFile>,
+}
+
+impl Index {
+  /// List the staged files, ignoring deletions.
+  pub fn read(cwd: &Path) -> Result<Self, BuiltinError> {
+    let top = git(cwd, &["rev-parse", "--show-toplevel"], None)?;
+    let top = PathBuf::from(String::from_utf8_lossy(&top).trim());
+    let raw = git(
+      &top,
+      &[
+        "diff",
+        "--cached",
+        "--raw",
+        "-z",
+        "--no-renames",
+        "--no-abbrev",
+        "--diff-filter=ACMR",
+      ],
+      None,
+    )?;
+    // Each entry is `:<old mode> <new mode> <old oid> <new oid> <status>`
+    // followed by the path, both NUL-terminated.
+    let raw = String::from_utf8_lossy(&raw);
+    let mut fields = raw.split('\0');
+    let mut files = Vec::new();
+    while let (Some(meta), Some(path)) = (fields.next(), fields.next()) {
+      let meta: Vec<&str> = meta.trim_start_matches(':').split(' ').collect();
+      if let [_, mode, _, oid, _] = meta[..] {
+        files.push(StagedFile {
+          path: path.to_string(),
+          mode: mode.to_string(),
+          oid:  oid.to_string(),
+        });
+      }
+    }
+    Ok(Self { top, files })
+  }
+
+  /// Sizes of the staged blobs, in the order of [`Index::files`].
+  pub fn sizes(&self) -> Result<Vec<u64>, BuiltinError> {
+    let input: String =
+      self.files.iter().map(|f| format!("{}\n", f.oid)).collect();
+    let out = git(
+      &self.top,
+      &["cat-file", "--batch-check=%(objectsize)"],
+      Some(input.as_bytes()),
+    )?;
+    Ok(
+      String::from_utf8_lossy(&out)
+        .lines()
+        .map(|line| line.trim().parse().unwrap_or(0))
+        .collect(),
+    )
+  }
+
+  /// Staged regular files accepted by `filter`, with their contents.
+  pub fn contents(
+    &self,
+    filter: impl Fn(&StagedFile) -> bool,
+  ) -> Result<Vec<(&StagedFile, Vec<u8>)>, BuiltinError> {
+    let files: Vec<&StagedFile> = self
+      .files
+      .iter()
+      .filter(|f| f.is_regular() && filter(f))
+      .collect();
+    if files.is_empty() {
+      return Ok(Vec::new());
+    }
+    let input: String = files.iter().map(|f| format!("{}\n", f.oid)).collect();
+    let out = git(&self.top, &["cat-file", "--batch"], Some(input.as_bytes()))?;
+    // Each object is written as `<oid> <type> <size>\n<contents>\n`.
+    let mut rest = &out[..];
+    let mut contents = Vec::with_capacity(files.len());
+    for file in files {
+      let header_end = rest.iter().position(|&b| b == b'\n').unwrap_or(0);
+      let header = String::from_utf8_lossy(&rest[..header_end]);
+      let size: usize = header
+        .rsplit(' ')
+        .next()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+      let start = header_end + 1;
+      let end = (start + size).min(rest.len());
+      contents.push((file, rest[start..end].to_vec()));
+      rest = &rest[(end + 1).min(rest.len())..];
+    }
+    Ok(contents)
+  }
+
+  /// Replace the staged contents of `file` with `fixed`, and the working
+  /// tree copy too when it still matches the staged `original`. Returns
+  /// whether the working tree was updated.
+  pub fn fix(
+    &self,
+    file: &StagedFile,
+    original: &[u8],
+    fixed: &[u8],
+  ) -> Result<bool, BuiltinError> {
+    let oid = git(
+      &self.top,
+      &["hash-object", "-w", "--no-filters", "--stdin"],
+      Some(fixed),
+    )?;
+    let oid = String::from_utf8_lossy(&oid).trim().to_string();
+    let info = format!("{},{oid},{}", file.mode, file.path);
+    git(&self.top, &["update-index", "--cacheinfo", &info], None)?;
+    let path = self.top.join(&file.path);
+    let in_sync = std::fs::read(&path).is_ok_and(|data| data == original);
+    if in_sync {
+      std::fs::write(&path, fixed).map_err(|e| BuiltinError::Io(path, e))?;
+    }
+    Ok(in_sync)
+  }
+}
+
+/// Run `git` in `dir`, feeding it `stdin`, and return its stdout.
+fn git(
+  dir: &Path,
+  args: &[&str],
+  stdin: Option<&[u8]>,
+) -> Result<Vec<u8>, BuiltinError> {
+  let failed = |detail: String| BuiltinError::Git {
+    command: args.join(" "),
+    detail,
+  };
+  let mut child = Command::new("git")
+    .args(args)
+    .current_dir(dir)
+    .stdin(if stdin.is_some() {
+      Stdio::piped()
+    } else {
+      Stdio::null()
+    })
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+    .map_err(|e| failed(e.to_string()))?;
+  if let Some(input) = stdin {
+    let mut pipe = child.stdin.take().expect("stdin is piped");
+    // Write from another thread so that a full stdout pipe cannot deadlock
+    // `git cat-file --batch` on large inputs.
+    let input = input.to_vec();
+    std::thread::spawn(move || pipe.write_all(&input));
+  }
+  let out = child
+    .wait_with_output()
+    .map_err(|e| failed(e.to_string()))?;
+  if out.status.success() {
+    Ok(out.stdout)
+  } else {
+    Err(failed(
+      String::from_utf8_lossy(&out.stderr).trim().to_string(),
+    ))
+  }
+}
+
+/// Whether `data` looks binary, using Git's heuristic of a NUL byte within
+/// the first 8000 bytes.
+pub fn is_binary(data: &[u8]) -> bool {
+  data.iter().take(8000).any(|&b| b == 0)
+}
+
+/// Files larger than `max` bytes.
+pub fn large_files(
+  index: &Index,
+  max: u64,
+) -> Result<Vec<Finding>, BuiltinError> {
+  let sizes = index.sizes()?;
+  Ok(
+    index
+      .files
+      .iter()
+      .zip(sizes)
+      .filter(|(file, size)| file.is_regular() && *size > max)
+      .map(|(file, size)| Finding {
+        path:    file.path.clone(),
+        line:    None,
+        message: format!("{} (max {})", format_size(size), format_size(max)),
+      })
+      .collect(),
+  )
+}
+
+/// Lines holding conflict markers left over from a merge.
+pub fn merge_conflicts(path: &str, data: &[u8]) -> Vec<Finding> {
+  if is_binary(data) {
+    return Vec::new();
+  }
+  String::from_utf8_lossy(data)
+    .lines()
+    .enumerate()
+    .filter(|(_, line)| {
+      CONFLICT_MARKERS.iter().any(|m| line.starts_with(m))
+        || *line == "<<<<<<<"
+        || *line == ">>>>>>>"
+    })
+    .map(|(i, line)| Finding {
+      path:    path.to_string(),
+      line:    Some(i + 1),
+      message: format!("conflict marker '{}'", &line[..7]),
+    })
+    .collect()
+}
+
+/// The first private key found in a file.
+pub fn private_keys(path: &str, data: &[u8]) -> Vec<Finding> {
+  let text = String::from_utf8_lossy(data);
+  PRIVATE_KEY_MARKERS
+    .iter()
+    .filter_map(|marker| text.find(marker))
+    .min()
+    .map(|at| Finding {
+      path:    path.to_string(),
+      line:    Some(text[..at].matches('\n').count() + 1),
+      message: "private key".to_string(),
+    })
+    .into_iter()
+    .collect()
+}
+
+/// `data` with spaces and tabs removed from the end of every line, keeping
+/// `\r\n` line endings intact.
+pub fn strip_trailing_whitespace(data: &[u8]) -> Vec<u8> {
+  let mut out = Vec::with_capacity(data.len());
+  for line in data.split_inclusive(|&b| b == b'\n') {
+    let (body, eol): (&[u8], &[u8]) = match line {
+      [body @ .., b'\r', b'\n'] => (body, b"\r\n"),
+      [body @ .., b'\n'] => (body, b"\n"),
+      body => (body, b""),
+    };
+    let end = body
+      .iter()
+      .rposition(|&b| b != b' ' && b != b'\t')
+      .map_or(0, |i| i + 1);
+    out.extend_from_slice(&body[..end]);
+    out.extend_from_slice(eol);
+  }
+  out
+}
+
+/// Rewrites the contents of a staged file, as done by the fixers.
+pub type Fixer = fn(&[u8]) -> Vec<u8>;
+
+/// `data` ending in exactly one newline, or empty if it holds nothing but
+/// line breaks.
+pub fn fix_end_of_file(data: &[u8]) -> Vec<u8> {
+  let end = data
+    .iter()
+    .rposition(|&b| b != b'\n' && b != b'\r')
+    .map_or(0, |i| i + 1);
+  if end == 0 {
+    return Vec::new();
+  }
+  let eol: &[u8] = if data[end..].starts_with(b"\r\n") {
+    b"\r\n"
+  } else {
+    b"\n"
+  };
+  [&data[..end], eol].concat()
+}
+
+/// The syntax checked by `huk:check-<syntax>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Syntax {
+  Json,
+  Yaml,
+  Toml,
+}
+
+impl Syntax {
+  /// Whether `path` has an extension of this syntax.
+  pub fn matches(self, path: &str) -> bool {
+    let ext = Path::new(path)
+      .extension()
+      .map(|e| e.to_string_lossy().to_ascii_lowercase());
+    matches!(
+      (self, ext.as_deref()),
+      (Self::Json, Some("json"))
+        | (Self::Yaml, Some("yaml" | "yml"))
+        | (Self::Toml, Some("toml"))
+    )
+  }
+
+  /// Parse `data`, returning the error message if it is invalid.
+  pub fn check(self, path: &str, data: &[u8]) -> Option<Finding> {
+    let finding = |line: Option<usize>, message: String| Finding {
+      path: path.to_string(),
+      line,
+      message,
+    };
+    let text = match std::str::from_utf8(data) {
+      Ok(text) => text,
+      Err(e) => return Some(finding(None, e.to_string())),
+    };
+    match self {
+      Self::Json => serde_json::from_str::<serde::de::IgnoredAny>(text)
+        .err()
+        .map(|e| finding(Some(e.line()), e.to_string())),
+      Self::Yaml => {
+        use serde::Deserialize;
+        serde_yaml::Deserializer::from_str(text).find_map(|document| {
+          serde::de::IgnoredAny::deserialize(document)
+            .err()
+            .map(|e| finding(e.location().map(|l| l.line()), e.to_string()))
+        })
+      }
+      Self::Toml => text.parse::<toml::Table>().err().map(|e| {
+        let line = e.span().map(|s| text[..s.start].matches('\n').count() + 1);
+        finding(line, e.message().to_string())
+      }),
+    }
+  }
+}
+
+/// Format a byte count with a binary unit.
+pub fn format_size(bytes: u64) -> String {
+  match bytes {
+    b if b >= 1 << 20 => format!("{:.1} MiB", b as f64 / (1 << 20) as f64),
+    b if b >= 1 << 10 => format!("{:.1} KiB", b as f64 / (1 << 10) as f64),
+    b => format!("{b} B"),
+  }
+}
+
+/// Render findings under a one-line summary.
+pub fn format_findings(
+  summary: &str,
+  findings: &[Finding],
+  color: bool,
+) -> String {
+  let paint = |code: &str, text: &str| {
+    if color {
+      format!("\x1b[{code}m{text}\x1b[0m")
+    } else {
+      text.to_string()
+    }
+  };
+  let mut out = format!("{} {summary}\n", paint("1;31", "error:"));
+  for finding in findings {
+    let location = match finding.line {
+      Some(line) => format!("{}:{line}", finding.path),
+      None => finding.path.clone(),
+    };
+    out.push_str(&format!(
+      "  {}: {}\n",
+      paint("1", &location),
+      finding.message
+    ));
+  }
+  out
+}
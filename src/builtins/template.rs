@@ -0,0 +1,155 @@
+//! `huk:commit-template`: fill in commit messages from `prepare-commit-msg`.
+//!
+//! Depending on the `commit_template` settings, the message is started from a
+//! template file, its subject is prefixed with an issue key found in the
+//! branch name (`feature/ABC-123-login` gives `ABC-123: `), and
+//! `Co-authored-by` trailers are appended. Git passes the source of the
+//! message as the second hook argument; messages Git or the user already
+//! wrote (`merge`, `squash` and `commit`, as with `--amend` or `-c`) are left
+//! untouched, and the template is only applied when Git provides no message
+//! at all.
+
+use regex::Regex;
+use serde::Deserialize;
+
+/// Settings for `huk:commit-template`, read from `huk.commit_template`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct CommitTemplate {
+  /// Regular expression finding the issue key in the branch name. The first
+  /// capture group is used when there is one, and the whole match otherwise.
+  pub issue_pattern: String,
+  /// Text put in front of the subject, where `{issue}` is replaced by the
+  /// issue key. Empty to disable the prefix.
+  pub issue_prefix:  String,
+  /// `Co-authored-by` trailers to add, as `Name <email>`.
+  pub co_authors:    Vec<String>,
+  /// Template file, relative to the configuration file, to start new
+  /// messages from. `{issue}` and `{branch}` are replaced in it.
+  pub template:      Option<String>,
+}
+
+impl Default for CommitTemplate {
+  fn default() -> Self {
+    Self {
+      issue_pattern: r"(?:^|/)([A-Z][A-Z0-9]+-[0-9]+)".to_string(),
+      issue_prefix:  "{issue}: ".to_string(),
+      co_authors:    Vec::new(),
+      template:      None,
+    }
+  }
+}
+
+/// Sources of messages that are passed through unchanged.
+const KEEP_SOURCES: [&str; 3] = ["merge", "squash", "commit"];
+
+/// The issue key matched by `pattern` in `branch`.
+pub fn issue_key(pattern: &Regex, branch: &str) -> Option<String> {
+  let captures = pattern.captures(branch)?;
+  captures
+    .get(1)
+    .or_else(|| captures.get(0))
+    .map(|m| m.as_str().to_string())
+}
+
+/// Fill in `message`, the contents of the message file, for a commit whose
+/// message comes from `source`. `issue` is the key found in the branch name
+/// and `template` the contents of the template file with its placeholders
+/// replaced.
+pub fn prepare(
+  message: &str,
+  source: Option<&str>,
+  issue: Option<&str>,
+  template: Option<&str>,
+  rules: &CommitTemplate,
+  comment_char: char,
+) -> String {
+  if source.is_some_and(|s| KEEP_SOURCES.contains(&s)) {
+    return message.to_string();
+  }
+  // What the user edits comes first; Git's comments (and the scissors line
+  // with the diff below it) follow.
+  let lines: Vec<&str> = message.lines().collect();
+  let split = lines
+    .iter()
+    .position(|line| line.starts_with(comment_char))
+    .unwrap_or(lines.len());
+  let (content, comments) = lines.split_at(split);
+  let mut content: Vec<String> = content
+    .iter()
+    .map(|line| line.to_string())
+    .skip_while(|line| line.trim().is_empty())
+    .collect();
+  while content.last().is_some_and(|line| line.trim().is_empty()) {
+    content.pop();
+  }
+  let original = content.clone();
+
+  if content.is_empty()
+    && source.is_none()
+    && let Some(template) = template
+  {
+    content = template.trim_end().lines().map(String::from).collect();
+  }
+
+  if let Some(issue) = issue
+    && !rules.issue_prefix.is_empty()
+    && !content
+      .first()
+      .is_some_and(|subject| subject.contains(issue))
+  {
+    let prefix = rules.issue_prefix.replace("{issue}", issue);
+    match content.first_mut() {
+      Some(subject) => subject.insert_str(0, &prefix),
+      None => content.push(prefix),
+    }
+  }
+
+  let trailers: Vec<String> = rules
+    .co_authors
+    .iter()
+    .map(|author| format!("Co-authored-by: {}", author.trim()))
+    .filter(|trailer| {
+      !content
+        .iter()
+        .any(|line| line.trim().eq_ignore_ascii_case(trailer))
+    })
+    .collect();
+  if !trailers.is_empty() {
+    // Join an existing trailer block rather than starting a second one.
+    let last_paragraph = content
+      .iter()
+      .rev()
+      .take_while(|line| !line.trim().is_empty())
+      .count();
+    let in_trailers = content.len() > 1
+      && last_paragraph < content.len()
+      && content[content.len() - last_paragraph..]
+        .iter()
+        .all(|line| is_trailer(line));
+    if !in_trailers {
+      content.push(String::new());
+    }
+    content.extend(trailers);
+  }
+
+  if content == original {
+    return message.to_string();
+  }
+  let mut out = content.join("\n");
+  out.push('\n');
+  if !comments.is_empty() {
+    out.push('\n');
+    out.push_str(&comments.join("\n"));
+    out.push('\n');
+  }
+  out
+}
+
+/// Whether `line` looks like a `Token: value` trailer.
+fn is_trailer(line: &str) -> bool {
+  line.split_once(": ").is_some_and(|(token, _)| {
+    !token.is_empty()
+      && token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+  })
+}
@@ -0,0 +1,303 @@
+//! Run-result cache for tasks with declared inputs.
+//!
+//! A task object may list `inputs`: glob patterns (relative to the directory
+//! containing the configuration file) naming the files the task depends on.
+//! Before running such a task, `huk` hashes its command (or the body of the
+//! script or task it references), working directory, environment, forwarded
+//! arguments and the contents of every matched file. If a previous
+//! successful run produced the same hash, the task is skipped and reported as
+//! cached. Entries are stored as small JSON files under `.git/huk/cache`, one
+//! per hash, so the cache never appears in the working tree. Hits mark an
+//! entry as recently used, and recording a run removes the least recently
+//! used entries beyond [`MAX_ENTRIES`].
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use clap::ValueEnum;
+use serde_json::json;
+use sha2::Digest;
+use sha2::Sha256;
+use thiserror::Error;
+
+use crate::cli::CacheOpts;
+use crate::git;
+use crate::runner::RunnerError;
+use crate::runner::TaskKind;
+
+/// Version tag mixed into every key so that changes to the hashing scheme
+/// invalidate old entries instead of producing false hits.
+const KEY_VERSION: &str = "huk-cache-v2";
+
+/// Number of entries a cache keeps by default. Every change to a task's
+/// inputs adds an entry, so without a limit the cache would grow forever.
+pub const MAX_ENTRIES: usize = 512;
+
+/// Errors that may occur while computing a cache key.
+#[derive(Error, Debug)]
+pub enum CacheError {
+  /// An `inputs` entry is not a valid glob pattern.
+  #[error("invalid input pattern '{0}': {1}")]
+  Pattern(String, #[source] glob::PatternError),
+  /// A matched input file could not be read.
+  #[error("failed to read input file {0}: {1}")]
+  Io(PathBuf, #[source] io::Error),
+}
+
+/// Operations supported by the `cache` subcommand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum CacheAction {
+  /// Delete every cached run result.
+  Clear,
+  /// Print the number and total size of cached run results.
+  Stats,
+}
+
+/// Everything that determines whether a previous run of a task can be reused.
+#[derive(Debug, Clone, Copy)]
+pub struct CacheInputs<'a> {
+  /// The command string or task reference as written in the configuration.
+  pub command:  &'a str,
+  /// What the command or reference resolves to.
+  pub kind:     TaskKind,
+  /// The definition that actually runs: the command itself, or the body of
+  /// the referenced script or task, so that editing it invalidates the entry.
+  pub body:     &'a str,
+  /// The directory the command runs in.
+  pub cwd:      &'a Path,
+  /// Whether the command runs through a shell.
+  pub shell:    bool,
+  /// The effective environment overrides applied to the command.
+  pub env:      &'a BTreeMap<String, String>,
+  /// Arguments forwarded from Git to the command.
+  pub args:     &'a [String],
+  /// Glob patterns naming the task's input files.
+  pub patterns: &'a [String],
+}
+
+/// Compute the hex-encoded cache key for a task. Glob patterns are resolved
+/// relative to `base`; directories matched by a pattern are ignored, and each
+/// file is hashed at most once, in sorted order, so the key does not depend on
+/// pattern order or overlap.
+pub fn compute_key(
+  base: &Path,
+  inputs: &CacheInputs<'_>,
+) -> Result<String, CacheError> {
+  let mut hasher = Sha256::new();
+  let mut field = |bytes: &[u8]| {
+    hasher.update((bytes.len() as u64).to_le_bytes());
+    hasher.update(bytes);
+  };
+  field(KEY_VERSION.as_bytes());
+  field(inputs.command.as_bytes());
+  field(inputs.kind.to_string().as_bytes());
+  field(inputs.body.as_bytes());
+  let cwd = inputs.cwd.strip_prefix(base).unwrap_or(inputs.cwd);
+  field(cwd.to_string_lossy().as_bytes());
+  field(if inputs.shell { b"sh" } else { b"exec" });
+  for (key, value) in inputs.env {
+    field(key.as_bytes());
+    field(value.as_bytes());
+  }
+  field(b"args");
+  for arg in inputs.args {
+    field(arg.as_bytes());
+  }
+
+  let mut files = BTreeSet::new();
+  for pattern in inputs.patterns {
+    let full = base.join(pattern);
+    let paths = glob::glob(&full.to_string_lossy())
+      .map_err(|e| CacheError::Pattern(pattern.clone(), e))?;
+    for path in paths.flatten() {
+      if path.is_file() {
+        files.insert(path);
+      }
+    }
+  }
+  field(b"files");
+  for path in files {
+    let content =
+      fs::read(&path).map_err(|e| CacheError::Io(path.clone(), e))?;
+    let rel = path.strip_prefix(base).unwrap_or(&path);
+    field(rel.to_string_lossy().as_bytes());
+    field(&content);
+  }
+
+  Ok(
+    hasher
+      .finalize()
+      .iter()
+      .map(|b| format!("{b:02x}"))
+      .collect(),
+  )
+}
+
+/// Summary of the cache contents, as printed by `huk cache stats`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheStats {
+  /// Number of cached run results.
+  pub entries: usize,
+  /// Total size of the cache directory in bytes.
+  pub bytes:   u64,
+}
+
+/// On-disk store of successful run results, keyed by [`compute_key`].
+#[derive(Debug, Clone)]
+pub struct CacheStore {
+  dir:         PathBuf,
+  max_entries: usize,
+}
+
+impl CacheStore {
+  /// Create a store rooted at `dir`. The directory is created lazily on the
+  /// first call to [`CacheStore::record`].
+  pub fn new(dir: impl Into<PathBuf>) -> Self {
+    Self {
+      dir:         dir.into(),
+      max_entries: MAX_ENTRIES,
+    }
+  }
+
+  /// Keep at most `max_entries` entries instead of [`MAX_ENTRIES`].
+  pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+    self.max_entries = max_entries;
+    self
+  }
+
+  /// Open the store for the repository containing `cwd`, located at
+  /// `<git-dir>/huk/cache`.
+  pub fn discover(cwd: &Path) -> io::Result<Self> {
+    Ok(Self::new(git::huk_dir(cwd)?.join("cache")))
+  }
+
+  /// The directory holding the cache entries.
+  pub fn dir(&self) -> &Path {
+    &self.dir
+  }
+
+  fn entry_path(&self, key: &str) -> PathBuf {
+    self.dir.join(format!("{key}.json"))
+  }
+
+  /// Returns `true` if a successful run with this key has been recorded,
+  /// marking the entry as recently used.
+  pub fn contains(&self, key: &str) -> bool {
+    let path = self.entry_path(key);
+    if !path.is_file() {
+      return false;
+    }
+    // Failing to update the time only makes the entry a candidate for
+    // pruning sooner.
+    let _ = fs::File::options()
+      .append(true)
+      .open(&path)
+      .and_then(|file| file.set_modified(SystemTime::now()));
+    true
+  }
+
+  /// Record a successful run. `label` is stored for human inspection only.
+  pub fn record(&self, key: &str, label: &str) -> io::Result<()> {
+    fs::create_dir_all(&self.dir)?;
+    let recorded = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|d| d.as_secs())
+      .unwrap_or_default();
+    let entry = json!({ "task": label, "recorded": recorded });
+    fs::write(self.entry_path(key), entry.to_string())?;
+    self.prune()?;
+    Ok(())
+  }
+
+  /// Remove the least recently used entries beyond the store's limit,
+  /// returning how many were deleted.
+  pub fn prune(&self) -> io::Result<usize> {
+    let mut entries: Vec<(SystemTime, PathBuf)> = self
+      .entries()?
+      .into_iter()
+      .map(|path| {
+        let used = fs::metadata(&path)
+          .and_then(|meta| meta.modified())
+          .unwrap_or(UNIX_EPOCH);
+        (used, path)
+      })
+      .collect();
+    if entries.len() <= self.max_entries {
+      return Ok(0);
+    }
+    entries.sort();
+    let excess = entries.len() - self.max_entries;
+    for (_, path) in &entries[..excess] {
+      fs::remove_file(path)?;
+    }
+    Ok(excess)
+  }
+
+  /// Remove every entry, returning how many were deleted.
+  pub fn clear(&self) -> io::Result<usize> {
+    let mut removed = 0;
+    for path in self.entries()? {
+      fs::remove_file(path)?;
+      removed += 1;
+    }
+    Ok(removed)
+  }
+
+  /// Count the entries and their total size.
+  pub fn stats(&self) -> io::Result<CacheStats> {
+    let mut stats = CacheStats::default();
+    for path in self.entries()? {
+      stats.entries += 1;
+      stats.bytes += fs::metadata(path)?.len();
+    }
+    Ok(stats)
+  }
+
+  fn entries(&self) -> io::Result<Vec<PathBuf>> {
+    let read = match fs::read_dir(&self.dir) {
+      Ok(read) => read,
+      Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+      Err(e) => return Err(e),
+    };
+    let mut paths = Vec::new();
+    for entry in read {
+      let path = entry?.path();
+      if path.extension().is_some_and(|ext| ext == "json") {
+        paths.push(path);
+      }
+    }
+    Ok(paths)
+  }
+}
+
+/// Handler for the `cache` subcommand.
+pub fn handle_cache(opts: &CacheOpts) -> Result<(), RunnerError> {
+  let store = CacheStore::discover(&std::env::current_dir()?)?;
+  match opts.action {
+    CacheAction::Clear => {
+      let n = store.clear()?;
+      let s = if n == 1 { "" } else { "s" };
+      eprintln!(
+        "Removed {n} cached result{s} from '{}'.",
+        store.dir().display()
+      );
+    }
+    CacheAction::Stats => {
+      let stats = store.stats()?;
+      let s = if stats.entries == 1 { "" } else { "s" };
+      println!(
+        "{} cached result{s} ({} bytes) in '{}'",
+        stats.entries,
+        stats.bytes,
+        store.dir().display()
+      );
+    }
+  }
+  Ok(())
+}
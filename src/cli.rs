@@ -10,10 +10,15 @@ use clap::Subcommand;
 use derive_more::with_trait::IsVariant;
 use derive_more::with_trait::TryInto;
 use paste::paste;
+use std::path::PathBuf;
 use thiserror::Error;
 
+use crate::cache;
 use crate::config;
+use crate::doctor;
+use crate::history;
 use crate::install;
+use crate::report;
 use crate::runner;
 use crate::task;
 
@@ -35,6 +40,10 @@ pub enum HukError {
   /// Wrapper around [configuration errors][config::ConfigError]
   #[error(transparent)]
   Config(#[from] config::ConfigError),
+
+  /// Wrapper around [diagnostics errors][doctor::DoctorError].
+  #[error(transparent)]
+  Doctor(#[from] doctor::DoctorError),
 }
 
 /// Top-level options for the `huk` binary.
@@ -194,6 +203,40 @@ cli! {
     ): Vec<String>,
     /// Enable verbose output during task execution.
     verbose(long, short = 'v'): bool,
+    /// Ignore cached results and run every task.
+    no_cache(
+      long,
+      long_help = "Ignore cached results and run every task.\n\n\
+        Tasks that declare `inputs` are normally skipped when their command,\n\
+        environment, arguments and input files are unchanged since their last\n\
+        successful run. With this flag they run regardless, and their results\n\
+        are not recorded in the cache."
+    ): bool,
+    /// Print what the hook would run without executing anything.
+    dry_run(
+      long,
+      visible_alias = "explain",
+      long_help = "Print what the hook would run without executing anything.\n\n\
+        Every entry of the hook is resolved exactly as it would be for a real\n\
+        run, and the resulting plan lists each command with its resolution\n\
+        kind (Deno task, Node script, hook or command), working directory and\n\
+        environment overrides. Names that match no task, script or hook fall\n\
+        through to being run as shell commands; these are flagged with a\n\
+        warning, since they are often typos."
+    ): bool,
+    /// Write a timing report in the given format after the hook finishes.
+    report(
+      long,
+      value_enum,
+      value_name = "FORMAT",
+      long_help = "Write a timing report in the given format after the hook \
+        finishes.\n\n\
+        The report lists each task with its kind, wall time and status, for\n\
+        consumption by CI dashboards. It is written to `huk-report.json` or\n\
+        `huk-report.xml` unless --report-path is given."
+    ): Option<report::ReportFormat>,
+    /// Path of the file written by --report.
+    report_path(long, value_name = "PATH", requires = "report"): Option<PathBuf>,
   },
   /// List tasks available in the configuration and optionally run them.
   #[command(aliases = ["t", "tasks"])]
@@ -221,6 +264,15 @@ cli! {
       color output. Can be combined with --json, --yaml, or --toml for \
       compact machine-readable output."
     ): bool,
+    /// Show each task with the tree of tasks it depends on.
+    tree(
+      long,
+      conflicts_with_all = ["json", "yaml", "toml", "run"],
+      long_help = "Show each task with the tree of tasks it depends on.\n\n\
+        Tasks that no other task depends on are listed at the top level.\n\
+        Dependencies that are not defined, or that would form a cycle, are\n\
+        marked as such."
+    ): bool,
     /// Enable verbose output during task execution.
     verbose(long, short = 'v'): bool,
   },
@@ -292,6 +344,21 @@ cli! {
     /// Replace the existing hook definition instead of appending to it.
     replace(long, short = 'r'): bool,
   },
+  /// Prepare the configuration file for huk.
+  #[command(long_about = "Prepare the configuration file for huk.\n\n\
+    Adds an empty `hooks` object to deno.json, deno.jsonc or package.json if\n\
+    it has none. Running it again changes nothing.")]
+  Init {
+    /// Install the hooks whenever dependencies are installed.
+    #[arg(long_help = "\
+      Install the hooks whenever dependencies are installed.\n\n\
+      Adds `huk install --auto --quiet` to the `prepare` script of\n\
+      package.json, which npm, pnpm, yarn and bun run after installing\n\
+      dependencies, or a `prepare` task to deno.json, to run with\n\
+      `deno task prepare`. An existing `prepare` command is kept and runs\n\
+      first.")]
+    auto_install(long): bool,
+  },
   /// Install wrapper scripts into the Git hooks directory.
   #[command(
     aliases = ["link", "i"]
@@ -305,8 +372,92 @@ cli! {
       IMPORTANT: if this value does not match the `core.hooksPath` value in your\n\
       git config, Git will likely fail to find and execute the installed hooks!")]
     hooks_dir: Option<String>,
-    /// Overwrite existing hook scripts if they already exist.
+    /// Overwrite existing hook scripts instead of keeping them as backups.
+    #[arg(long_help = "\
+      Overwrite existing hook scripts instead of keeping them as backups.\n\n\
+      Without this flag, a hook script that huk did not generate (such as one\n\
+      installed by Git LFS) is moved to `<hook>.bak` and run before or after\n\
+      huk, as set by `huk.existing_hooks`.")]
     force(long, short = 'f', alias = "y"): bool,
+    /// Install into a committed `.huk` directory and point `core.hooksPath`
+    /// at it.
+    #[arg(long_help = "\
+      Install into a committed `.huk` directory and point `core.hooksPath`\n\
+      at it.\n\n\
+      The wrapper scripts are written to `.huk` at the top of the working tree\n\
+      (or to the directory given by `--hooks-dir`, relative to it) so that they\n\
+      can be committed, and `git config core.hooksPath` is set to that\n\
+      directory. After a fresh clone, running `huk install --managed` once\n\
+      enables the hooks. `huk uninstall` restores the previous\n\
+      `core.hooksPath` only if huk set it.")]
+    managed(long, short = 'm'): bool,
+    /// Do nothing in CI or outside a Git repository.
+    #[arg(long_help = "\
+      Do nothing in CI or outside a Git repository.\n\n\
+      Meant for package manager lifecycle scripts such as the `prepare` script\n\
+      added by `huk init --auto-install`: installing is skipped when the `CI`\n\
+      environment variable is set (to anything but an empty value, `false` or\n\
+      `0`) or when the package is not inside a Git repository, for example\n\
+      when installed from the registry.")]
+    auto(long): bool,
+    /// Only print warnings.
+    quiet(long, short = 'q'): bool,
+    /// Also install hooks in submodules that have their own configuration.
+    #[arg(long_help = "\
+      Also install hooks in submodules that have their own configuration.\n\n\
+      Every checked-out submodule, nested ones included, that contains a\n\
+      deno.json, deno.jsonc or package.json gets its hooks installed into its\n\
+      own hooks directory (usually `.git/modules/<name>/hooks`). `--hooks-dir`\n\
+      only applies to the top-level repository.")]
+    recursive(long, short = 'r'): bool,
+  },
+  /// Check why hooks might not run, and optionally repair the installation.
+  #[command(
+    aliases = ["status"],
+    long_about = "Check why hooks might not run, and optionally repair the \
+      installation.\n\n\
+      Prints a pass, warn or fail line for the Git version, the configuration,\n\
+      the hooks directory (core.hooksPath), every configured hook's script\n\
+      (installed, executable, up to date), whether the hooks can find huk,\n\
+      hooks that are no longer configured, the package manager, and\n\
+      conflicting hook managers such as husky or lefthook. Exits with an error\n\
+      if any check fails."
+  )]
+  Doctor {
+    /// Make scripts executable and reinstall hooks where needed, then check
+    /// again.
+    fix(long): bool,
+  },
+  /// Inspect or clear the cache of successful task runs.
+  #[command(long_about = "Inspect or clear the cache of successful task runs.\n\n\
+    Tasks that declare `inputs` are skipped when an identical successful run\n\
+    is recorded under `.git/huk/cache`. Use `stats` to see how many results\n\
+    are cached and `clear` to force every task to run again.")]
+  Cache {
+    /// Cache operation to perform.
+    action(value_enum): cache::CacheAction,
+  },
+  /// Show previous hook runs recorded in the run history.
+  #[command(
+    aliases = ["log"],
+    long_about = "Show previous hook runs recorded in the run history.\n\n\
+      Every `huk run` invocation is recorded under `.git/huk/history.jsonl`,\n\
+      including the commit and branch, and each command's duration, exit code\n\
+      and the tail of its output (unless `huk.history_output` is off). Use\n\
+      `huk history show <ID>` to print a single run in full."
+  )]
+  History {
+    /// Only show runs of the specified hook.
+    hook(long, value_name = "HOOK"): Option<String>,
+    /// Only show runs that failed.
+    failed(long, short = 'f'): bool,
+    /// Format the results as JSON.
+    json(long, short = 'j'): bool,
+    /// Maximum number of runs to list, newest first.
+    limit(long, short = 'n', default_value_t = 20): usize,
+    /// Inspect a single run.
+    #[command(subcommand)]
+    command: Option<history::HistoryCommand>,
   },
   /// Uninstall wrapper scripts from the Git hooks directory.
   #[command(alias = "un", alias = "u", alias = "unlink")]
@@ -314,12 +465,13 @@ cli! {
     /// Override the default `core.hooksPath` (usually `.git/hooks`) uninstall path.
     #[arg(long, short = 'd', value_name = "PATH")]
     hooks_dir: Option<String>,
-    /// Suppress errors if hook scripts do not exist.
+    /// Remove hook scripts even if huk did not write them, and suppress
+    /// errors if they do not exist.
     force(long, short = 'f', alias = "y"): bool,
-    /// Specific hook names to uninstall. If not provided, `huk` will attempt to
-    /// uninstall all hooks it previously installed. If you are using additional
-    /// hook integrations in your repo, such as Git LFS, it is recommended to
-    /// specify explicit names here to avoid removing those hooks.
+    /// Specific hook names to uninstall. If not provided, `huk` will uninstall
+    /// every hook it previously installed or that is configured. Scripts that
+    /// huk did not write, such as those of Git LFS, are left in place unless
+    /// `--force` is given.
     hooks(last = true): Vec<String>,
   },
 }
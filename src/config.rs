@@ -6,19 +6,27 @@
 //! `package.json` file. The chosen file is inspected for a top-level
 //! `hooks` object mapping Git hook names to task specifications. In
 //! addition, the Node `scripts` field and Deno `tasks` field are captured
-//! so that tasks can reference them.
+//! so that tasks can reference them, along with tasks defined for `huk`
+//! alone in the `tasks` map of the top-level `huk` object.
 
+use crate::builtins::conventional::ConventionalRules;
+use crate::builtins::template::CommitTemplate;
 use crate::constants::GIT_HOOKS;
 use crate::handlers::RunnerError;
+use crate::install::ExistingHooks;
 use crate::task::TaskSpec;
 use crate::task::TaskSpecParseError;
 use derive_more::IsVariant;
+use serde::Deserialize;
+use serde::Serialize;
 use serde_json::Value;
 use serde_json::{self};
 use std::collections::HashMap;
+use std::fmt;
 use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
 use thiserror::Error;
 
 /// A resolved configuration containing hook definitions and tasks.
@@ -33,11 +41,524 @@ pub struct HookConfig {
   /// Mapping of task names to raw commands coming from the Node `scripts`
   /// field.
   pub node_scripts:    HashMap<String, String>,
-  /// Mapping of task names to raw commands coming from the Deno `tasks` field.
-  pub deno_tasks:      HashMap<String, String>,
+  /// Tasks from the Deno `tasks` field, including those of workspace
+  /// members. Member tasks are keyed by the member directory and the task
+  /// name, as in `api/serve`, so that each stays scoped to its own
+  /// configuration file.
+  pub deno_tasks:      HashMap<String, DenoTask>,
+  /// Descriptions and locations of Node scripts, keyed by script name.
+  pub script_meta:     HashMap<String, ScriptMeta>,
+  /// Tasks defined in the `huk.tasks` map.
+  pub huk_tasks:       HashMap<String, HukTask>,
   /// The preferred package manager to use when executing Node scripts (npm,
   /// pnpm, yarn, etc.).
   pub package_manager: Option<String>,
+  /// Behavioral settings read from the top-level `huk` object.
+  pub settings:        Settings,
+}
+
+/// A task from the `tasks` field of a Deno configuration file. Tasks are
+/// always run with `deno task`, which executes the dependencies (in
+/// parallel, each at most once) before the command.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DenoTask {
+  /// The command, run by Deno's built-in task shell. `None` for a task that
+  /// only groups its dependencies.
+  pub command:      Option<String>,
+  /// Optional description, shown by `huk tasks`.
+  pub description:  Option<String>,
+  /// Names of tasks run before this one, all defined in the same
+  /// configuration file.
+  pub dependencies: Vec<String>,
+  /// The workspace member defining the task, as written in the `workspace`
+  /// field without a leading `./`; `None` for tasks of the root
+  /// configuration.
+  pub member:       Option<String>,
+  /// Directory of the workspace member defining the task; `None` for tasks
+  /// of the root configuration.
+  pub cwd:          Option<PathBuf>,
+  /// Where the task is defined.
+  pub location:     Option<TaskLocation>,
+}
+
+/// Metadata for a Node script, which `package.json` itself has no room for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScriptMeta {
+  /// Description from the `scriptsMeta` field or the `huk.tasks` map.
+  pub description: Option<String>,
+  /// Where the script is defined.
+  pub location:    Option<TaskLocation>,
+}
+
+/// The file and (1-based) line a task is defined on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TaskLocation {
+  #[serde(rename = "file")]
+  pub path: PathBuf,
+  pub line: usize,
+}
+
+impl fmt::Display for TaskLocation {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}:{}", self.path.display(), self.line)
+  }
+}
+
+/// Find the line on which `key` is defined inside the object at `sections`
+/// (a path of keys such as `["huk", "tasks"]`) of a JSON document, with
+/// comments already blanked out so that lines are preserved. Only direct
+/// children of each object are considered, so a key of the same name nested
+/// deeper, or in an earlier sibling, never matches.
+fn locate_key(content: &str, sections: &[&str], key: &str) -> Option<usize> {
+  /// Find `key` among the direct children of the object whose `{` is the
+  /// first token at or after `from`, returning the offset just past its
+  /// colon.
+  fn find_key(content: &str, from: usize, key: &str) -> Option<usize> {
+    let bytes = content.as_bytes();
+    let mut depth = 0usize;
+    let mut i = from;
+    while i < bytes.len() {
+      match bytes[i] {
+        b'"' => {
+          let start = i;
+          i += 1;
+          while i < bytes.len() && bytes[i] != b'"' {
+            i += if bytes[i] == b'\\' { 2 } else { 1 };
+          }
+          let end = (i + 1).min(bytes.len());
+          let rest = content[end..].trim_start();
+          if depth == 1
+            && rest.starts_with(':')
+            && serde_json::from_str::<String>(&content[start..end])
+              .is_ok_and(|name| name == key)
+          {
+            return Some(content.len() - rest.len() + 1);
+          }
+          if depth == 0 {
+            return None;
+          }
+          i = end;
+          continue;
+        }
+        b'{' | b'[' => depth += 1,
+        b'}' | b']' => {
+          depth = depth.checked_sub(1)?;
+          if depth == 0 {
+            return None;
+          }
+        }
+        c if depth == 0 && !c.is_ascii_whitespace() => return None,
+        _ => {}
+      }
+      i += 1;
+    }
+    None
+  }
+  let start = sections
+    .iter()
+    .try_fold(0, |from, section| find_key(content, from, section))?;
+  let end = find_key(content, start, key)?;
+  Some(content[..end].matches('\n').count() + 1)
+}
+
+/// Read task descriptions from the `scriptsMeta` field, a map of script
+/// names to either a description string or an object with a `description`.
+fn script_descriptions(value: &Value) -> Vec<(String, String)> {
+  let Some(Value::Object(map)) = value.get("scriptsMeta") else {
+    return Vec::new();
+  };
+  map
+    .iter()
+    .filter_map(|(name, v)| {
+      let description = match v {
+        Value::String(s) => s,
+        Value::Object(obj) => obj.get("description")?.as_str()?,
+        _ => return None,
+      };
+      Some((name.clone(), description.to_string()))
+    })
+    .collect()
+}
+
+/// A task defined in the `huk.tasks` map, with the same grammar as a hook
+/// entry. These tasks need neither Deno nor a package manager.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HukTask {
+  pub spec:     TaskSpec,
+  /// Where the task is defined.
+  pub location: Option<TaskLocation>,
+}
+
+impl HukTask {
+  /// What the task runs, for listings: the command or reference of a string
+  /// entry or task object, or the entries of a sequence separated by `;`.
+  /// `None` for a task object that only groups its dependencies.
+  pub fn command(&self) -> Option<String> {
+    match &self.spec {
+      TaskSpec::Detailed(object) => {
+        object.command.clone().or(object.task.clone())
+      }
+      spec => Some(Self::describe(spec)),
+    }
+  }
+
+  /// A one-line summary of the task for listings: what it runs, or the
+  /// dependencies it groups.
+  pub fn summary(&self) -> String {
+    Self::describe(&self.spec).replace('\n', " ")
+  }
+
+  fn describe(spec: &TaskSpec) -> String {
+    match spec {
+      TaskSpec::Single(reference) => reference.clone(),
+      TaskSpec::Detailed(object) => object
+        .command
+        .clone()
+        .or(object.task.clone())
+        .unwrap_or_else(|| {
+          format!("(runs {})", object.dependencies.join(", "))
+        }),
+      TaskSpec::Sequence(list) => list
+        .iter()
+        .map(Self::describe)
+        .collect::<Vec<_>>()
+        .join("; "),
+    }
+  }
+
+  /// The description of a task object, if it has one.
+  pub fn description(&self) -> Option<&str> {
+    match &self.spec {
+      TaskSpec::Detailed(object) => object.description.as_deref(),
+      _ => None,
+    }
+  }
+}
+
+/// Tasks and descriptions from the `huk.tasks` map, in a configuration
+/// loaded from `path` whose comment-free source text is `content`.
+#[derive(Default)]
+struct HukTasks {
+  tasks:        HashMap<String, HukTask>,
+  /// Entries holding nothing but a `description`, which annotate the Deno
+  /// task or Node script of the same name instead of defining a task.
+  descriptions: Vec<(String, String)>,
+}
+
+impl HukTasks {
+  fn from_config(
+    path: &Path,
+    content: &str,
+    value: &Value,
+  ) -> Result<Self, ConfigError> {
+    let mut out = Self::default();
+    let Some(Value::Object(map)) =
+      value.get("huk").and_then(|huk| huk.get("tasks"))
+    else {
+      return Ok(out);
+    };
+    for (name, v) in map {
+      if let Value::Object(obj) = v
+        && obj.len() == 1
+        && let Some(Value::String(description)) = obj.get("description")
+      {
+        out.descriptions.push((name.clone(), description.clone()));
+        continue;
+      }
+      let spec = TaskSpec::from_json(v)
+        .map_err(|e| ConfigError::InvalidTask(name.clone(), e))?;
+      let location =
+        locate_key(content, &["huk", "tasks"], name).map(|line| TaskLocation {
+          path: path.to_path_buf(),
+          line,
+        });
+      out.tasks.insert(name.clone(), HukTask { spec, location });
+    }
+    Ok(out)
+  }
+}
+
+impl DenoTask {
+  /// Parse a task definition: either a command string or an object with
+  /// `command`, `description` and `dependencies` fields. Returns `None` for
+  /// values Deno would reject.
+  fn from_json(value: &Value) -> Option<Self> {
+    match value {
+      Value::String(cmd) => Some(Self {
+        command: Some(cmd.clone()),
+        ..Self::default()
+      }),
+      Value::Object(obj) => {
+        let string =
+          |key: &str| obj.get(key).and_then(Value::as_str).map(str::to_string);
+        let dependencies = match obj.get("dependencies") {
+          None | Some(Value::Null) => Vec::new(),
+          Some(Value::Array(deps)) => deps
+            .iter()
+            .map(|dep| dep.as_str().map(str::to_string))
+            .collect::<Option<_>>()?,
+          Some(_) => return None,
+        };
+        Some(Self {
+          command: string("command"),
+          description: string("description"),
+          dependencies,
+          member: None,
+          cwd: None,
+          location: None,
+        })
+      }
+      _ => None,
+    }
+  }
+
+  /// A one-line summary of the task for listings: its command, or the
+  /// dependencies it groups.
+  pub fn summary(&self) -> String {
+    match &self.command {
+      Some(cmd) => cmd.replace('\n', " "),
+      None => format!("(runs {})", self.dependencies.join(", ")),
+    }
+  }
+
+  /// The key under which the task `name` of this task's configuration file
+  /// is stored in [`HookConfig::deno_tasks`].
+  pub fn key(&self, name: &str) -> String {
+    match &self.member {
+      Some(member) => format!("{member}/{name}"),
+      None => name.to_string(),
+    }
+  }
+
+  /// The name `deno task` knows the task stored under `key` by, within its
+  /// own configuration file.
+  pub fn name<'a>(&self, key: &'a str) -> &'a str {
+    self
+      .member
+      .as_ref()
+      .and_then(|member| key.strip_prefix(member.as_str())?.strip_prefix('/'))
+      .unwrap_or(key)
+  }
+}
+
+/// Read the `tasks` field of a parsed Deno configuration, whose
+/// comment-free source text is `content`, loaded from `path`.
+fn deno_tasks_from(
+  path: &Path,
+  content: &str,
+  value: &Value,
+) -> Vec<(String, DenoTask)> {
+  match value.get("tasks") {
+    Some(Value::Object(tasks)) => tasks
+      .iter()
+      .filter_map(|(name, v)| {
+        let location =
+          locate_key(content, &["tasks"], name).map(|line| TaskLocation {
+            path: path.to_path_buf(),
+            line,
+          });
+        Some((
+          name.clone(),
+          DenoTask {
+            location,
+            ..DenoTask::from_json(v)?
+          },
+        ))
+      })
+      .collect(),
+    _ => Vec::new(),
+  }
+}
+
+/// Names and directories of the members of a Deno workspace, from either
+/// the array form of the `workspace` field or the older `{ "members": [...]
+/// }` form.
+fn deno_workspace_members(
+  base: &Path,
+  value: &Value,
+) -> Vec<(String, PathBuf)> {
+  let members = match value.get("workspace") {
+    Some(Value::Array(members)) => members,
+    Some(Value::Object(obj)) => match obj.get("members") {
+      Some(Value::Array(members)) => members,
+      _ => return Vec::new(),
+    },
+    _ => return Vec::new(),
+  };
+  members
+    .iter()
+    .filter_map(Value::as_str)
+    .map(|member| {
+      let name = member.trim_start_matches("./").trim_end_matches('/');
+      let dir = base
+        .join(member)
+        .components()
+        .filter(|c| !matches!(c, std::path::Component::CurDir))
+        .collect();
+      (name.to_string(), dir)
+    })
+    .collect()
+}
+
+/// Settings controlling how `huk` itself behaves, read from an optional
+/// top-level `huk` object in the configuration file. Unknown keys are ignored
+/// and every field falls back to its default when omitted.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+  /// Record every `huk run` invocation in the run history.
+  pub history:            bool,
+  /// Capture (and echo) the tail of each command's output so that it can be
+  /// stored in the run history. Commands then write to a pipe rather than the
+  /// terminal, which causes some tools to disable colored output or progress
+  /// bars; turn this off to keep them attached to the terminal.
+  pub history_output:     bool,
+  /// Maximum size of the history file in bytes. The oldest records are
+  /// discarded whenever a new record would push the file past this limit.
+  pub history_max_bytes:  u64,
+  /// Print a table of every task's kind, wall time and status after a hook
+  /// finishes.
+  pub summary:            bool,
+  /// Tasks taking longer than this are highlighted as slow in the summary.
+  /// Accepts a number of seconds or a string such as `"500ms"`, `"2.5s"` or
+  /// `"1m"`; `null` disables the warning.
+  #[serde(deserialize_with = "deserialize_threshold")]
+  pub warn_after:         Option<Duration>,
+  /// Reject hook entries and dependencies that name no Deno task, Node
+  /// script or hook, instead of running them as shell commands. Commands
+  /// must then be written as task objects or with an `sh:` prefix.
+  pub strict:             bool,
+  /// Run Node scripts directly with `sh -c` (with `node_modules/.bin` on
+  /// `PATH` and npm's environment variables) instead of through the package
+  /// manager, skipping its startup time. Scripts that depend on the package
+  /// manager still go through it.
+  pub direct_scripts:     bool,
+  /// Rules for the `huk:conventional` commit message check.
+  pub conventional:       ConventionalRules,
+  /// How `huk:commit-template` fills in commit messages.
+  pub commit_template:    CommitTemplate,
+  /// Largest staged file `huk:no-large-files` accepts, in bytes. Accepts a
+  /// number of bytes or a string such as `"500KB"` or `"2MB"`.
+  #[serde(deserialize_with = "deserialize_size")]
+  pub max_file_size:      u64,
+  /// Branches `huk:forbid-branch` refuses commits on.
+  pub protected_branches: Vec<String>,
+  /// Whether hook scripts that `huk install` finds in place, and keeps as
+  /// `<hook>.bak`, run `"before"` or `"after"` huk.
+  pub existing_hooks:     ExistingHooks,
+  /// Let hooks pass with a warning when the `huk` binary cannot be found,
+  /// instead of failing. `HUK_SKIP_MISSING=0` or `1` overrides this for a
+  /// single run.
+  pub skip_when_missing:  bool,
+  /// Install `post-checkout`, `post-merge` and `post-rewrite` scripts that
+  /// reinstall the hooks whenever a checkout, merge or rebase changes which
+  /// hooks are configured.
+  pub sync:               bool,
+  /// Number of entries the task result cache keeps before removing the
+  /// least recently used ones.
+  pub cache_max_entries:  usize,
+}
+
+impl Default for Settings {
+  fn default() -> Self {
+    Self {
+      history:            true,
+      history_output:     true,
+      history_max_bytes:  1024 * 1024,
+      summary:            true,
+      warn_after:         Some(Duration::from_secs(5)),
+      strict:             false,
+      direct_scripts:     false,
+      conventional:       ConventionalRules::default(),
+      commit_template:    CommitTemplate::default(),
+      max_file_size:      500 * 1024,
+      protected_branches: vec!["main".into(), "master".into()],
+      existing_hooks:     ExistingHooks::default(),
+      skip_when_missing:  false,
+      sync:               false,
+      cache_max_entries:  crate::cache::MAX_ENTRIES,
+    }
+  }
+}
+
+/// Parse a human-friendly duration: a bare number of seconds, or a number
+/// followed by `ms`, `s` or `m`.
+pub fn parse_duration(input: &str) -> Option<Duration> {
+  let input = input.trim();
+  let (number, scale) = if let Some(n) = input.strip_suffix("ms") {
+    (n, 0.001)
+  } else if let Some(n) = input.strip_suffix('s') {
+    (n, 1.0)
+  } else if let Some(n) = input.strip_suffix('m') {
+    (n, 60.0)
+  } else {
+    (input, 1.0)
+  };
+  let value: f64 = number.trim().parse().ok()?;
+  Duration::try_from_secs_f64(value * scale).ok()
+}
+
+/// Parse a size in bytes: a bare number, or a number followed by `B`, `KB`,
+/// `MB` or `GB` (powers of 1024, case-insensitive).
+pub fn parse_size(input: &str) -> Option<u64> {
+  let input = input.trim().to_ascii_uppercase();
+  let number = input.trim_end_matches("IB").trim_end_matches('B');
+  let (number, scale) = match number.chars().last() {
+    Some('K') => (&number[..number.len() - 1], 1u64 << 10),
+    Some('M') => (&number[..number.len() - 1], 1 << 20),
+    Some('G') => (&number[..number.len() - 1], 1 << 30),
+    _ => (number, 1),
+  };
+  let value: f64 = number.trim().parse().ok()?;
+  (value >= 0.0).then_some((value * scale as f64) as u64)
+}
+
+fn deserialize_size<'de, D>(de: D) -> Result<u64, D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  use serde::de::Error;
+  match Value::deserialize(de)? {
+    Value::Number(n) => n
+      .as_u64()
+      .ok_or_else(|| D::Error::custom(format!("invalid size '{n}'"))),
+    Value::String(s) => parse_size(&s)
+      .ok_or_else(|| D::Error::custom(format!("invalid size '{s}'"))),
+    other => Err(D::Error::custom(format!("invalid size '{other}'"))),
+  }
+}
+
+fn deserialize_threshold<'de, D>(de: D) -> Result<Option<Duration>, D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  use serde::de::Error;
+  match Value::deserialize(de)? {
+    Value::Null => Ok(None),
+    Value::Number(n) => n
+      .as_f64()
+      .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+      .map(Some)
+      .ok_or_else(|| D::Error::custom(format!("invalid duration '{n}'"))),
+    Value::String(s) => parse_duration(&s)
+      .map(Some)
+      .ok_or_else(|| D::Error::custom(format!("invalid duration '{s}'"))),
+    other => Err(D::Error::custom(format!("invalid duration '{other}'"))),
+  }
+}
+
+impl Settings {
+  /// Parse the settings from the `huk` key of a configuration document.
+  fn from_config_value(
+    path: &Path,
+    value: &Value,
+  ) -> Result<Self, ConfigError> {
+    match value.get("huk") {
+      None | Some(Value::Null) => Ok(Self::default()),
+      Some(v) => serde_json::from_value(v.clone())
+        .map_err(|e| ConfigError::InvalidSettings(path.to_path_buf(), e)),
+    }
+  }
 }
 
 /// Enum describing where the configuration was loaded from.
@@ -76,6 +597,12 @@ impl ConfigSource {
     }
   }
 
+  /// Get the directory containing the configuration file. Relative paths in
+  /// the configuration (such as task `inputs`) are resolved against it.
+  pub fn base_dir(&self) -> &Path {
+    self.as_path().parent().unwrap_or(Path::new("."))
+  }
+
   /// Get the default file name for the configuration file based on its type.
   fn default_file_name(&self) -> &str {
     match self {
@@ -115,6 +642,12 @@ pub enum ConfigError {
     "no supported configuration file (deno.json, deno.jsonc, package.json) found in {0}"
   )]
   NotFound(PathBuf),
+  /// A configuration file was named explicitly but is not of a supported
+  /// kind.
+  #[error(
+    "unsupported configuration file {0} (expected deno.json, deno.jsonc or package.json)"
+  )]
+  Unsupported(PathBuf),
   /// Failed to read the configuration file.
   #[error("failed to read config file {0}: {1}")]
   Io(PathBuf, #[source] std::io::Error),
@@ -124,6 +657,13 @@ pub enum ConfigError {
   /// The hooks field exists but could not be parsed into a task specification.
   #[error("invalid hook definition for '{0}': {1}")]
   InvalidHook(String, #[source] TaskSpecParseError),
+  /// An entry of the `huk.tasks` map could not be parsed into a task
+  /// specification.
+  #[error("invalid definition for task '{0}' in huk.tasks: {1}")]
+  InvalidTask(String, #[source] TaskSpecParseError),
+  /// The top-level `huk` settings object has an unexpected shape.
+  #[error("invalid 'huk' settings in {0}: {1}")]
+  InvalidSettings(PathBuf, #[source] serde_json::Error),
   /// An unknown or unsupported Git hook name was specified.
   #[error("unknown Git hook name '{0}'. Supported hooks are: {supported_hooks}", supported_hooks = GIT_HOOKS.join(", "))]
   UnknownHook(String),
@@ -149,6 +689,16 @@ impl HookConfig {
     }
   }
 
+  /// Load configuration from the given file, which must be named
+  /// `deno.json`, `deno.jsonc` or `package.json`.
+  pub fn load(path: &Path) -> Result<Self, ConfigError> {
+    match path.file_name().and_then(|name| name.to_str()) {
+      Some("deno.json" | "deno.jsonc") => Self::load_deno_json(path),
+      Some("package.json") => Self::load_package_json(path),
+      _ => Err(ConfigError::Unsupported(path.to_path_buf())),
+    }
+  }
+
   /// Load configuration from a Deno JSON or JSONC file.
   fn load_deno_json(path: &Path) -> Result<Self, ConfigError> {
     let content = fs::read_to_string(path)
@@ -175,33 +725,42 @@ impl HookConfig {
         }
       }
     }
-    // Extract deno tasks (these are simple command strings in Deno).
-    let mut deno_tasks = HashMap::new();
-    if let Some(Value::Object(tasks)) = value.get("tasks") {
-      for (name, val) in tasks {
-        match val {
-          Value::String(cmd) => {
-            deno_tasks.insert(name.clone(), cmd.clone());
-          }
-          // Deno tasks may also be objects with command/description etc.
-          Value::Object(obj) => {
-            let mut cmd_parts = Vec::new();
-            if let Some(Value::Array(deps)) = obj.get("dependencies") {
-              // If only dependencies are defined, we can join them with "&&".
-              for dep in deps {
-                if let Value::String(task) = dep {
-                  cmd_parts.push(format!("deno task {task}"));
-                }
-              }
-            }
-            if let Some(Value::String(cmd)) = obj.get("command") {
-              cmd_parts.push(cmd.clone());
-            }
-            let joined = cmd_parts.join(" && ");
-            deno_tasks.insert(name.clone(), joined);
-          }
-          _ => {}
-        }
+    // Extract Deno tasks, then those of workspace members under their
+    // qualified names, so that neither dependencies nor names cross from one
+    // configuration file into another.
+    let mut deno_tasks: HashMap<String, DenoTask> =
+      deno_tasks_from(path, &clean, &value).into_iter().collect();
+    let base = path.parent().unwrap_or(Path::new("."));
+    for (member_name, member) in deno_workspace_members(base, &value) {
+      let Some((member_path, member_clean, member_value)) =
+        ["deno.json", "deno.jsonc"]
+          .iter()
+          .map(|file| member.join(file))
+          .find(|file| file.is_file())
+          .and_then(|file| {
+            let clean = strip_json_comments(&fs::read_to_string(&file).ok()?);
+            let value = serde_json::from_str::<Value>(&clean).ok()?;
+            Some((file, clean, value))
+          })
+      else {
+        continue;
+      };
+      for (name, task) in
+        deno_tasks_from(&member_path, &member_clean, &member_value)
+      {
+        let task = DenoTask {
+          member: Some(member_name.clone()),
+          cwd: Some(member.clone()),
+          ..task
+        };
+        deno_tasks.insert(task.key(&name), task);
+      }
+    }
+    // Descriptions from `huk.tasks` fill in tasks that have none.
+    let huk_tasks = HukTasks::from_config(path, &clean, &value)?;
+    for (name, description) in huk_tasks.descriptions {
+      if let Some(task) = deno_tasks.get_mut(&name) {
+        task.description.get_or_insert(description);
       }
     }
     Ok(HookConfig {
@@ -209,7 +768,10 @@ impl HookConfig {
       hooks,
       node_scripts: HashMap::new(),
       deno_tasks,
+      script_meta: HashMap::new(),
+      huk_tasks: huk_tasks.tasks,
       package_manager: None,
+      settings: Settings::from_config_value(path, &value)?,
     })
   }
 
@@ -237,15 +799,38 @@ impl HookConfig {
         }
       }
     }
-    // Extract Node scripts.
+    // Extract Node scripts, with their descriptions from `scriptsMeta` or,
+    // failing that, the `huk.tasks` map.
     let mut node_scripts = HashMap::new();
+    let mut script_meta = HashMap::new();
     if let Some(Value::Object(scripts)) = value.get("scripts") {
       for (name, val) in scripts {
         if let Value::String(cmd) = val {
           node_scripts.insert(name.clone(), cmd.clone());
+          let location =
+            locate_key(&content, &["scripts"], name).map(|line| TaskLocation {
+              path: path.to_path_buf(),
+              line,
+            });
+          script_meta.insert(
+            name.clone(),
+            ScriptMeta {
+              description: None,
+              location,
+            },
+          );
         }
       }
     }
+    let huk_tasks = HukTasks::from_config(path, &content, &value)?;
+    for (name, description) in script_descriptions(&value)
+      .into_iter()
+      .chain(huk_tasks.descriptions)
+    {
+      if let Some(meta) = script_meta.get_mut(&name) {
+        meta.description.get_or_insert(description);
+      }
+    }
 
     // Determine preferred package manager.
     let package_manager = value
@@ -258,7 +843,10 @@ impl HookConfig {
       hooks,
       node_scripts,
       deno_tasks: HashMap::new(),
+      script_meta,
+      huk_tasks: huk_tasks.tasks,
       package_manager,
+      settings: Settings::from_config_value(path, &value)?,
     })
   }
 }
@@ -32,7 +32,7 @@ pub const VERSION_PRE: &str = env!("CARGO_PKG_VERSION_PRE");
 /// in the git hooks directory will actually be recognized by Git.
 ///
 /// [Git documentation]: https://git-scm.com/docs/githooks
-pub const GIT_HOOKS: [&str; 22] = [
+pub const GIT_HOOKS: [&str; 23] = [
   "pre-applypatch",
   "pre-auto-gc",
   "pre-checkout",
@@ -52,6 +52,7 @@ pub const GIT_HOOKS: [&str; 22] = [
   "post-rewrite",
   "post-update",
   "push-to-checkout",
+  "reference-transaction",
   "fsmonitor-watchman",
   "sendemail-validate",
   "update",
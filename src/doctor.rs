@@ -0,0 +1,500 @@
+//! `huk doctor`: find out why hooks do not run.
+//!
+//! Each check reports a pass, a warning or a failure, with a suggestion for
+//! anything that is not a pass. The checks cover Git itself, the
+//! configuration, where Git looks for hooks, every configured hook's script
+//! (installed, executable, and up to date with the manifest), whether the
+//! scripts can find the `huk` binary, scripts left behind by hooks that are no
+//! longer configured, the tools tasks need, and other hook managers that
+//! compete for the same scripts. `--fix` makes scripts executable and
+//! reinstalls hooks, then checks again.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::io::IsTerminal;
+#[cfg(target_family = "unix")]
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+use crate::VERSION;
+use crate::cli::DoctorOpts;
+use crate::cli::InstallOpts;
+use crate::config::HookConfig;
+use crate::git;
+use crate::git::git_output;
+use crate::install;
+use crate::install::InstallError;
+use crate::manifest::Manifest;
+use crate::manifest::ManifestStore;
+use crate::package_manager::PackageManager;
+use crate::runner;
+
+/// Oldest Git release supporting `core.hooksPath`.
+const MIN_GIT_VERSION: (u32, u32) = (2, 9);
+
+/// Files configuring lefthook, which installs its own hook scripts.
+const LEFTHOOK_FILES: [&str; 6] = [
+  "lefthook.yml",
+  "lefthook.yaml",
+  ".lefthook.yml",
+  ".lefthook.yaml",
+  "lefthook.json",
+  "lefthook.toml",
+];
+
+/// Errors that may occur while running `huk doctor`.
+#[derive(Error, Debug)]
+pub enum DoctorError {
+  /// At least one check failed.
+  #[error("{0} check(s) failed")]
+  Failed(usize),
+  /// `--fix` could not reinstall the hooks.
+  #[error(transparent)]
+  Install(#[from] InstallError),
+  /// An underlying I/O error occurred.
+  #[error(transparent)]
+  Io(#[from] io::Error),
+}
+
+/// The outcome of a check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Status {
+  Pass,
+  Warn,
+  Fail,
+}
+
+/// A repair `--fix` can make.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Fix {
+  /// Run `huk install`.
+  Install,
+  /// Set the executable bits of a script.
+  MakeExecutable(PathBuf),
+}
+
+/// The result of a single check.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Check {
+  pub status:  Status,
+  pub message: String,
+  /// How to resolve a warning or failure.
+  pub hint:    Option<String>,
+  /// What `--fix` does about it, if anything.
+  pub fix:     Option<Fix>,
+}
+
+impl Check {
+  fn pass(message: impl Into<String>) -> Self {
+    Self {
+      status:  Status::Pass,
+      message: message.into(),
+      hint:    None,
+      fix:     None,
+    }
+  }
+
+  fn warn(message: impl Into<String>, hint: impl Into<String>) -> Self {
+    Self {
+      status: Status::Warn,
+      hint: Some(hint.into()),
+      ..Self::pass(message)
+    }
+  }
+
+  fn fail(message: impl Into<String>, hint: impl Into<String>) -> Self {
+    Self {
+      status: Status::Fail,
+      ..Self::warn(message, hint)
+    }
+  }
+
+  fn with_fix(self, fix: Fix) -> Self {
+    Self {
+      fix: Some(fix),
+      ..self
+    }
+  }
+}
+
+/// Run every check for the repository containing `cwd`.
+pub fn diagnose(cwd: &Path) -> Vec<Check> {
+  let mut checks = Vec::new();
+  let Some(version) = git_output(cwd, &["--version"]) else {
+    checks.push(Check::fail(
+      "git was not found on PATH",
+      "install Git and make sure `git` is on PATH",
+    ));
+    return checks;
+  };
+  let version = version.trim_start_matches("git version ").to_string();
+  checks.push(match parse_version(&version) {
+    Some(v) if v < MIN_GIT_VERSION => Check::warn(
+      format!("git {version} is older than 2.9"),
+      "upgrade Git; older releases ignore core.hooksPath",
+    ),
+    _ => Check::pass(format!("git {version}")),
+  });
+  if git::git_dir(cwd).is_err() {
+    checks.push(Check::fail(
+      "not inside a Git repository",
+      "run huk from a Git repository, or create one with `git init`",
+    ));
+    return checks;
+  }
+
+  let cfg = match install::load_config(cwd) {
+    Ok(cfg) => match runner::validate(&cfg) {
+      Ok(()) => {
+        let path = cfg.source.as_path_buf().display().to_string();
+        checks.push(if cfg.hooks.is_empty() {
+          Check::warn(
+            format!("configuration {path} defines no hooks"),
+            "add a `hooks` object, or `huk add <hook> <task>`",
+          )
+        } else {
+          Check::pass(format!("configuration {path}"))
+        });
+        Some(cfg)
+      }
+      Err(err) => {
+        checks.push(Check::fail(
+          format!("configuration is invalid: {err}"),
+          "fix the configuration file; hooks fail until then",
+        ));
+        None
+      }
+    },
+    Err(err) => {
+      checks.push(Check::fail(
+        format!("configuration could not be loaded: {err}"),
+        "add a `hooks` object to deno.json or package.json",
+      ));
+      None
+    }
+  };
+
+  let manifest = ManifestStore::discover(cwd)
+    .and_then(|store| store.load())
+    .unwrap_or_default();
+  match install::find_git_hooks_dir(cwd, None) {
+    Ok(hooks_dir) => {
+      checks.extend(check_hooks_dir(cwd, &hooks_dir, &manifest));
+      if let Some(cfg) = &cfg {
+        checks.extend(check_scripts(cfg, &hooks_dir, &manifest));
+      }
+    }
+    Err(err) => checks.push(Check::fail(
+      err.to_string(),
+      "check `git config core.hooksPath`",
+    )),
+  }
+  // Git runs hooks from the top of the working tree.
+  let top = git_output(cwd, &["rev-parse", "--show-toplevel"])
+    .map(PathBuf::from)
+    .unwrap_or_else(|| cwd.to_path_buf());
+  if let Some(cfg) = &cfg {
+    if !cfg.hooks.is_empty() {
+      checks.push(match install::locate_huk(&top) {
+        Some(command) => Check::pass(format!("hooks run huk as {command}")),
+        None => Check::fail(
+          "hooks cannot find huk",
+          "put huk on PATH, add it to node_modules, or set HUK_BIN",
+        ),
+      });
+    }
+    checks.extend(check_tools(cfg));
+  }
+  checks.extend(check_conflicts(&top));
+  checks
+}
+
+/// The major and minor version of a version string such as `2.43.0`.
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+  let mut parts = version.split(|c: char| !c.is_ascii_digit());
+  Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+}
+
+/// Where Git looks for hooks, and whether that is where huk installed them.
+fn check_hooks_dir(
+  cwd: &Path,
+  hooks_dir: &Path,
+  manifest: &Manifest,
+) -> Vec<Check> {
+  let mut checks = Vec::new();
+  let hooks_path = git_output(cwd, &["config", "--get", "core.hooksPath"])
+    .filter(|path| !path.is_empty());
+  checks.push(match &hooks_path {
+    Some(path) if path.contains(".husky") => Check::warn(
+      format!("core.hooksPath is {path}, which husky set up"),
+      "run `git config --unset core.hooksPath` and `huk install` to use \
+       huk instead of husky",
+    ),
+    Some(path)
+      if manifest
+        .hooks_path
+        .as_ref()
+        .is_some_and(|set| set.value == *path) =>
+    {
+      Check::pass(format!(
+        "hooks directory {path} (core.hooksPath, set by huk install --managed)"
+      ))
+    }
+    Some(path) => {
+      Check::pass(format!("hooks directory {path} (core.hooksPath)"))
+    }
+    None => Check::pass(format!("hooks directory {}", hooks_dir.display())),
+  });
+  let hooks_dir =
+    fs::canonicalize(hooks_dir).unwrap_or(hooks_dir.to_path_buf());
+  let elsewhere = manifest
+    .hooks
+    .values()
+    .find(|entry| entry.path.parent() != Some(hooks_dir.as_path()));
+  if let Some(entry) = elsewhere {
+    let dir = entry.path.parent().unwrap_or(&entry.path);
+    checks.push(
+      Check::fail(
+        format!(
+          "hooks were installed in {}, but Git runs them from {}",
+          dir.display(),
+          hooks_dir.display()
+        ),
+        "run `huk install` to move them",
+      )
+      .with_fix(Fix::Install),
+    );
+  }
+  checks
+}
+
+/// The script of every configured hook, and the scripts of hooks that are
+/// no longer configured.
+fn check_scripts(
+  cfg: &HookConfig,
+  hooks_dir: &Path,
+  manifest: &Manifest,
+) -> Vec<Check> {
+  let hooks = install::installed_hooks(cfg);
+  let mut checks: Vec<Check> = hooks
+    .iter()
+    .map(|hook| check_script(hook, &hooks_dir.join(hook), manifest))
+    .collect();
+  for (hook, entry) in &manifest.hooks {
+    if !hooks.contains(hook) && entry.path.exists() {
+      checks.push(
+        Check::warn(
+          format!("{hook}: still installed but no longer configured"),
+          "run `huk install` to remove it",
+        )
+        .with_fix(Fix::Install),
+      );
+    }
+  }
+  checks
+}
+
+/// The script installed for `hook` at `path`.
+fn check_script(hook: &str, path: &Path, manifest: &Manifest) -> Check {
+  let reinstall = "run `huk install`";
+  let Ok(script) = fs::read_to_string(path) else {
+    return Check::fail(format!("{hook}: not installed"), reinstall)
+      .with_fix(Fix::Install);
+  };
+  if !install::owns(manifest, hook, path) {
+    return Check::warn(
+      format!("{hook}: {} was not written by huk", path.display()),
+      format!(
+        "run `huk install` to keep it as {hook}.bak and run it alongside huk"
+      ),
+    )
+    .with_fix(Fix::Install);
+  }
+  #[cfg(target_family = "unix")]
+  if fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 == 0) {
+    return Check::fail(
+      format!("{hook}: {} is not executable", path.display()),
+      format!("run `chmod +x {}`", path.display()),
+    )
+    .with_fix(Fix::MakeExecutable(path.to_path_buf()));
+  }
+  if let Some(binary) = install::wrapper_binary(&script) {
+    return if Path::new(&binary).exists() {
+      Check::warn(
+        format!("{hook}: runs huk from a fixed path, {binary}"),
+        "run `huk install` for a script that finds huk wherever it is \
+         installed",
+      )
+    } else {
+      Check::fail(
+        format!("{hook}: runs {binary}, which does not exist"),
+        reinstall,
+      )
+    }
+    .with_fix(Fix::Install);
+  }
+  match manifest.hooks.get(hook) {
+    Some(entry) if !manifest.matches(hook, path) => Check::warn(
+      format!("{hook}: modified since huk {} installed it", entry.version),
+      reinstall,
+    )
+    .with_fix(Fix::Install),
+    Some(entry) if entry.version != VERSION => Check::warn(
+      format!(
+        "{hook}: installed by huk {}, this is {VERSION}",
+        entry.version
+      ),
+      reinstall,
+    )
+    .with_fix(Fix::Install),
+    _ => Check::pass(format!("{hook}: installed")),
+  }
+}
+
+/// The tools needed to run Deno tasks and Node scripts.
+fn check_tools(cfg: &HookConfig) -> Vec<Check> {
+  let mut checks = Vec::new();
+  if !cfg.deno_tasks.is_empty() {
+    checks.push(match which::which("deno") {
+      Ok(_) => Check::pass("deno is available for Deno tasks"),
+      Err(_) => Check::fail(
+        "deno was not found on PATH, but Deno tasks are defined",
+        "install Deno from https://deno.com",
+      ),
+    });
+  }
+  if !cfg.node_scripts.is_empty() {
+    let base = cfg.source.base_dir();
+    let manager = PackageManager::detect(base, cfg.package_manager.as_deref())
+      .and_then(|manager| {
+        manager.invocation().map(|argv| (manager.kind, argv))
+      });
+    checks.push(match manager {
+      Ok((kind, argv)) => Check::pass(format!(
+        "package manager {kind} is available (runs `{}`)",
+        argv.join(" ")
+      )),
+      Err(err) => Check::fail(
+        format!("package manager: {err}"),
+        "install it, or set `\"direct_scripts\": true` in the huk object",
+      ),
+    });
+  }
+  checks
+}
+
+/// Other hook managers set up in the repository at `top`.
+fn check_conflicts(top: &Path) -> Vec<Check> {
+  let mut checks = Vec::new();
+  if top.join(".husky").is_dir() {
+    checks.push(Check::warn(
+      "husky is set up in this repository (.husky)",
+      "remove husky so that it does not take over the hooks directory",
+    ));
+  }
+  if let Some(file) = LEFTHOOK_FILES.iter().find(|f| top.join(f).is_file()) {
+    checks.push(Check::warn(
+      format!("lefthook is configured ({file})"),
+      "keep a single hook manager; `lefthook install` overwrites huk's \
+       scripts",
+    ));
+  }
+  if checks.is_empty() {
+    checks.push(Check::pass("no other hook managers found"));
+  }
+  checks
+}
+
+/// Render checks as `pass`, `warn` and `fail` lines, each followed by its
+/// suggestion. ANSI colors are only emitted when `color` is set.
+pub fn format_checks(checks: &[Check], color: bool) -> String {
+  let paint = |code: &str, text: &str| {
+    if color {
+      format!("\x1b[{code}m{text}\x1b[0m")
+    } else {
+      text.to_string()
+    }
+  };
+  let mut out = String::new();
+  for check in checks {
+    let label = match check.status {
+      Status::Pass => paint("32", "pass"),
+      Status::Warn => paint("33", "warn"),
+      Status::Fail => paint("31", "fail"),
+    };
+    out.push_str(&format!("{label}  {}\n", check.message));
+    if let Some(hint) = &check.hint {
+      out.push_str(&format!("      {}\n", paint("2", hint)));
+    }
+  }
+  let count = |status, noun: &str| {
+    let n = checks.iter().filter(|c| c.status == status).count();
+    format!("{n} {noun}{}", if n == 1 { "" } else { "s" })
+  };
+  out.push_str(&format!(
+    "\n{}, {}, {}\n",
+    count(Status::Pass, "passed check"),
+    count(Status::Warn, "warning"),
+    count(Status::Fail, "failure")
+  ));
+  out
+}
+
+/// Apply the fixes of `checks`, reinstalling at most once.
+fn apply_fixes(checks: &[Check]) -> Result<(), DoctorError> {
+  let mut install = false;
+  for fix in checks.iter().filter_map(|check| check.fix.as_ref()) {
+    match fix {
+      Fix::Install => install = true,
+      #[cfg(target_family = "unix")]
+      Fix::MakeExecutable(path) => {
+        let mut perms = fs::metadata(path)?.permissions();
+        perms.set_mode(perms.mode() | 0o111);
+        fs::set_permissions(path, perms)?;
+        eprintln!("Made {} executable", path.display());
+      }
+      #[cfg(not(target_family = "unix"))]
+      Fix::MakeExecutable(_) => {}
+    }
+  }
+  if install {
+    // Keep a managed installation in its directory.
+    let hooks_dir = ManifestStore::discover(&env::current_dir()?)?
+      .load()?
+      .hooks_path
+      .map(|set| set.value);
+    install::handle_install(&InstallOpts {
+      managed: hooks_dir.is_some(),
+      hooks_dir,
+      force: false,
+      auto: false,
+      quiet: false,
+      recursive: false,
+    })?;
+  }
+  Ok(())
+}
+
+/// Handle the `doctor` subcommand.
+pub fn handle_doctor(opts: &DoctorOpts) -> Result<(), DoctorError> {
+  let cwd = env::current_dir()?;
+  let color = io::stdout().is_terminal();
+  let mut checks = diagnose(&cwd);
+  if opts.fix && checks.iter().any(|check| check.fix.is_some()) {
+    apply_fixes(&checks)?;
+    eprintln!();
+    checks = diagnose(&cwd);
+  }
+  print!("{}", format_checks(&checks, color));
+  if !opts.fix && checks.iter().any(|check| check.fix.is_some()) {
+    eprintln!("Run `huk doctor --fix` to repair what can be repaired.");
+  }
+  match checks.iter().filter(|c| c.status == Status::Fail).count() {
+    0 => Ok(()),
+    failed => Err(DoctorError::Failed(failed)),
+  }
+}
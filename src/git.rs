@@ -0,0 +1,101 @@
+//! Helpers for querying the enclosing Git repository.
+//!
+//! `huk` keeps its own state (run caches, history, install records) in a
+//! `huk` directory inside the repository's Git directory, so that nothing it
+//! writes ever shows up as an untracked file in the working tree. Paths are
+//! resolved by Git itself, so linked worktrees, submodules and `GIT_DIR` are
+//! handled as Git handles them.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Run `git` with the given arguments in `cwd` and return its trimmed stdout,
+/// or `None` if git is unavailable or exits unsuccessfully.
+pub(crate) fn git_output(cwd: &Path, args: &[&str]) -> Option<String> {
+  let out = Command::new("git")
+    .args(args)
+    .current_dir(cwd)
+    .output()
+    .ok()?;
+  if !out.status.success() {
+    return None;
+  }
+  Some(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+/// Locate the Git directory for the repository containing `cwd`, honouring
+/// `GIT_DIR`. The result is always absolute, even though `git rev-parse
+/// --git-dir` may report a path relative to `cwd`.
+pub fn git_dir(cwd: &Path) -> io::Result<PathBuf> {
+  let dir = git_output(cwd, &["rev-parse", "--git-dir"])
+    .filter(|s| !s.is_empty())
+    .ok_or_else(|| {
+      io::Error::new(io::ErrorKind::NotFound, "not inside a git repository")
+    })?;
+  Ok(cwd.join(dir))
+}
+
+/// Run `git rev-parse` with `args` in `cwd` and return the path it prints,
+/// made absolute by joining it to `cwd`.
+fn rev_parse_path(cwd: &Path, args: &[&str]) -> Option<PathBuf> {
+  let mut command = vec!["rev-parse"];
+  command.extend(args);
+  git_output(cwd, &command)
+    .filter(|s| !s.is_empty())
+    .map(|path| cwd.join(path))
+}
+
+/// Locate the Git directory shared by every worktree of the repository
+/// containing `cwd`. This is the Git directory itself outside of linked
+/// worktrees.
+pub fn common_dir(cwd: &Path) -> io::Result<PathBuf> {
+  rev_parse_path(cwd, &["--git-common-dir"]).ok_or_else(|| {
+    io::Error::new(io::ErrorKind::NotFound, "not inside a git repository")
+  })
+}
+
+/// Resolve `name` inside the Git directory of the repository containing
+/// `cwd` the way Git does, as an absolute path. This accounts for linked
+/// worktrees, whose hooks live in the common directory, and for settings
+/// such as `core.hooksPath` that relocate `hooks`.
+pub fn git_path(cwd: &Path, name: &str) -> Option<PathBuf> {
+  rev_parse_path(cwd, &["--git-path", name])
+}
+
+/// The directory where `huk` stores its own state: `<git-dir>/huk`.
+pub fn huk_dir(cwd: &Path) -> io::Result<PathBuf> {
+  Ok(git_dir(cwd)?.join("huk"))
+}
+
+/// Set the Git configuration `key` of the repository containing `cwd` to
+/// `value`, or unset it when `value` is `None`.
+pub fn set_config(
+  cwd: &Path,
+  key: &str,
+  value: Option<&str>,
+) -> io::Result<()> {
+  let mut cmd = Command::new("git");
+  cmd.arg("config").current_dir(cwd);
+  match value {
+    Some(value) => cmd.args([key, value]),
+    None => cmd.args(["--unset", key]),
+  };
+  let out = cmd.output()?;
+  if !out.status.success() {
+    return Err(io::Error::other(format!(
+      "failed to update {key}: {}",
+      String::from_utf8_lossy(&out.stderr).trim()
+    )));
+  }
+  Ok(())
+}
+
+/// The top of the working tree containing `cwd`, or `None` in a bare
+/// repository or outside of one.
+pub fn top_level(cwd: &Path) -> Option<PathBuf> {
+  git_output(cwd, &["rev-parse", "--show-toplevel"])
+    .filter(|top| !top.is_empty())
+    .map(PathBuf::from)
+}
@@ -0,0 +1,372 @@
+//! Run history.
+//!
+//! Every `huk run` invocation appends a [`HistoryRecord`] to
+//! `.git/huk/history.jsonl`: the hook, its arguments, the commit and branch it
+//! ran against, and the duration, exit code and (truncated) output of each
+//! command. The file is a plain JSON-lines log so it can be inspected with
+//! standard tools; `huk history` lists and filters it, and `huk history show
+//! <id>` prints a single record in full. Once the file grows past the
+//! configured `history_max_bytes`, the oldest records are discarded.
+
+use std::fs;
+use std::io;
+use std::io::IsTerminal;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use clap::Subcommand;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::cli::HistoryOpts;
+use crate::config::HookConfig;
+use crate::config::Settings;
+use crate::git;
+use crate::runner::RunnerError;
+use crate::runner::TaskRun;
+
+/// A single recorded `huk run` invocation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryRecord {
+  /// Sequential identifier, unique within the history file.
+  pub id:          u64,
+  /// Name of the hook that was run.
+  pub hook:        String,
+  /// Arguments Git passed to the hook.
+  #[serde(default)]
+  pub args:        Vec<String>,
+  /// Start time, in seconds since the Unix epoch.
+  pub started:     u64,
+  /// Total wall-clock time, in milliseconds.
+  pub duration_ms: u64,
+  /// Whether every task succeeded.
+  pub success:     bool,
+  /// The error that stopped the run, if any.
+  #[serde(default)]
+  pub error:       Option<String>,
+  /// `HEAD` at the time of the run, if the repository has any commits.
+  #[serde(default)]
+  pub commit:      Option<String>,
+  /// The checked-out branch, if `HEAD` is not detached.
+  #[serde(default)]
+  pub branch:      Option<String>,
+  /// Each command executed (or skipped as cached), in order.
+  #[serde(default)]
+  pub tasks:       Vec<TaskRun>,
+}
+
+/// Subcommands of `huk history`.
+#[derive(Subcommand, Debug, Clone)]
+pub enum HistoryCommand {
+  /// Print a single run in full, including captured output.
+  Show {
+    /// Identifier of the run, as printed by `huk history`.
+    id: u64,
+  },
+}
+
+/// Append-only store of [`HistoryRecord`]s with a size-based retention policy.
+#[derive(Debug, Clone)]
+pub struct HistoryStore {
+  path:      PathBuf,
+  max_bytes: u64,
+}
+
+impl HistoryStore {
+  /// Create a store backed by the file at `path`, pruned to at most
+  /// `max_bytes`.
+  pub fn new(path: impl Into<PathBuf>, max_bytes: u64) -> Self {
+    Self {
+      path: path.into(),
+      max_bytes,
+    }
+  }
+
+  /// Open the store for the repository containing `cwd`, located at
+  /// `<git-dir>/huk/history.jsonl`.
+  pub fn discover(cwd: &Path, max_bytes: u64) -> io::Result<Self> {
+    Ok(Self::new(
+      git::huk_dir(cwd)?.join("history.jsonl"),
+      max_bytes,
+    ))
+  }
+
+  /// Load every record, oldest first. Lines that fail to parse (for example
+  /// a partial write) are skipped.
+  pub fn load(&self) -> io::Result<Vec<HistoryRecord>> {
+    let content = match fs::read_to_string(&self.path) {
+      Ok(content) => content,
+      Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+      Err(e) => return Err(e),
+    };
+    Ok(
+      content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect(),
+    )
+  }
+
+  /// Append a record, assigning it the next free identifier, then prune the
+  /// oldest records if the file exceeds its size limit. Returns the assigned
+  /// identifier.
+  ///
+  /// Hooks may run concurrently (for example from several worktrees), so the
+  /// whole read-append-prune sequence holds an exclusive lock on a sibling
+  /// `.lock` file. Locking the history file itself would not do, since
+  /// pruning replaces it.
+  pub fn append(&self, mut record: HistoryRecord) -> io::Result<u64> {
+    if let Some(parent) = self.path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    let lock = fs::OpenOptions::new()
+      .create(true)
+      .truncate(false)
+      .write(true)
+      .open(self.path.with_extension("jsonl.lock"))?;
+    lock.lock()?;
+    let records = self.load()?;
+    record.id = records.last().map_or(1, |r| r.id + 1);
+    let mut file = fs::OpenOptions::new()
+      .create(true)
+      .append(true)
+      .open(&self.path)?;
+    writeln!(file, "{}", serde_json::to_string(&record)?)?;
+    drop(file);
+    if fs::metadata(&self.path)?.len() > self.max_bytes {
+      self.prune()?;
+    }
+    Ok(record.id)
+  }
+
+  /// Rewrite the file keeping only the newest lines that fit within
+  /// `max_bytes`. The newest record is always kept, even if it alone exceeds
+  /// the limit.
+  fn prune(&self) -> io::Result<()> {
+    let content = fs::read_to_string(&self.path)?;
+    let mut kept: Vec<&str> = Vec::new();
+    let mut size = 0u64;
+    for line in content.lines().rev() {
+      let len = line.len() as u64 + 1;
+      if !kept.is_empty() && size + len > self.max_bytes {
+        break;
+      }
+      size += len;
+      kept.push(line);
+    }
+    kept.reverse();
+    let mut pruned = kept.join("\n");
+    pruned.push('\n');
+    let tmp = self.path.with_extension("jsonl.tmp");
+    fs::write(&tmp, pruned)?;
+    fs::rename(&tmp, &self.path)
+  }
+}
+
+/// Append a record describing a finished `huk run` invocation. Failures are
+/// reported as a warning only: a history that cannot be written must never
+/// block a commit.
+pub(crate) fn record_run(
+  cfg: &HookConfig,
+  hook: &str,
+  args: &[String],
+  started: SystemTime,
+  elapsed: Duration,
+  tasks: Vec<TaskRun>,
+  result: &Result<(), RunnerError>,
+) {
+  let base = cfg.source.base_dir();
+  let record = HistoryRecord {
+    id: 0,
+    hook: hook.to_string(),
+    args: args.to_vec(),
+    started: started
+      .duration_since(UNIX_EPOCH)
+      .map(|d| d.as_secs())
+      .unwrap_or_default(),
+    duration_ms: elapsed.as_millis() as u64,
+    success: result.is_ok(),
+    error: result.as_ref().err().map(|e| e.to_string()),
+    commit: git::git_output(base, &["rev-parse", "--verify", "-q", "HEAD"]),
+    branch: git::git_output(base, &["symbolic-ref", "--short", "-q", "HEAD"]),
+    tasks,
+  };
+  let stored = HistoryStore::discover(base, cfg.settings.history_max_bytes)
+    .and_then(|store| store.append(record));
+  if let Err(err) = stored {
+    eprintln!("warning: failed to record run history: {err}");
+  }
+}
+
+/// Format seconds since the Unix epoch as `YYYY-MM-DD HH:MM:SS` (UTC).
+pub fn format_timestamp(secs: u64) -> String {
+  // Civil-from-days conversion (Howard Hinnant's algorithm).
+  let days = (secs / 86_400) as i64;
+  let rem = secs % 86_400;
+  let z = days + 719_468;
+  let era = z.div_euclid(146_097);
+  let doe = z.rem_euclid(146_097);
+  let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let day = doy - (153 * mp + 2) / 5 + 1;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 };
+  let year = yoe + era * 400 + i64::from(month <= 2);
+  format!(
+    "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+    rem / 3_600,
+    rem % 3_600 / 60,
+    rem % 60
+  )
+}
+
+/// Format a duration in milliseconds for display, e.g. `850ms` or `3.2s`.
+pub fn format_duration(ms: u64) -> String {
+  if ms < 1_000 {
+    format!("{ms}ms")
+  } else if ms < 60_000 {
+    format!("{:.1}s", ms as f64 / 1_000.0)
+  } else {
+    format!("{}m{:02}s", ms / 60_000, ms % 60_000 / 1_000)
+  }
+}
+
+/// `ok`/`failed` label, padded to a fixed width before coloring so that
+/// columns line up.
+fn status_label(success: bool, color: bool) -> String {
+  let (label, code) = if success { ("ok", 32) } else { ("failed", 31) };
+  if color {
+    format!("\x1b[{code}m{label:<6}\x1b[0m")
+  } else {
+    format!("{label:<6}")
+  }
+}
+
+/// Handler for the `history` subcommand.
+pub fn handle_history(opts: &HistoryOpts) -> Result<(), RunnerError> {
+  let cwd = std::env::current_dir()?;
+  let max_bytes = HookConfig::discover(&cwd)
+    .map(|cfg| cfg.settings.history_max_bytes)
+    .unwrap_or_else(|_| Settings::default().history_max_bytes);
+  let store = HistoryStore::discover(&cwd, max_bytes)?;
+  let records = store.load()?;
+  let color = io::stdout().is_terminal();
+
+  if let Some(HistoryCommand::Show { id }) = &opts.command {
+    let record = records
+      .iter()
+      .find(|r| r.id == *id)
+      .ok_or(RunnerError::UnknownRun(*id))?;
+    if opts.json {
+      let out = serde_json::to_string_pretty(record)
+        .map_err(|e| RunnerError::Serialize(e.to_string()))?;
+      println!("{out}");
+      return Ok(());
+    }
+    println!(
+      "Run #{} — {} ({})",
+      record.id,
+      record.hook,
+      if record.success { "ok" } else { "failed" }
+    );
+    println!("  started:  {} UTC", format_timestamp(record.started));
+    println!("  duration: {}", format_duration(record.duration_ms));
+    if let Some(branch) = &record.branch {
+      println!("  branch:   {branch}");
+    }
+    if let Some(commit) = &record.commit {
+      println!("  commit:   {commit}");
+    }
+    if !record.args.is_empty() {
+      println!("  args:     {}", crate::shell::join(&record.args));
+    }
+    if let Some(error) = &record.error {
+      println!("  error:    {error}");
+    }
+    for task in &record.tasks {
+      let status = if task.cached {
+        "cached".to_string()
+      } else {
+        match task.exit_code {
+          Some(code) => format!("exit {code}"),
+          None if task.success => "ok".to_string(),
+          None => "killed".to_string(),
+        }
+      };
+      let details =
+        format!("({}, {status})", format_duration(task.duration_ms));
+      println!();
+      if color {
+        println!("\x1b[1m$ {}\x1b[0m  \x1b[2m{details}\x1b[0m", task.command);
+      } else {
+        println!("$ {}  {details}", task.command);
+      }
+      if !task.output.is_empty() {
+        print!("{}", task.output);
+        if !task.output.ends_with('\n') {
+          println!();
+        }
+      }
+    }
+    return Ok(());
+  }
+
+  let mut matching: Vec<&HistoryRecord> = records
+    .iter()
+    .rev()
+    .filter(|r| opts.hook.as_ref().is_none_or(|hook| &r.hook == hook))
+    .filter(|r| !opts.failed || !r.success)
+    .take(opts.limit)
+    .collect();
+
+  if opts.json {
+    // Summaries omit the captured output; use `history show` for that.
+    let summaries: Vec<HistoryRecord> = matching
+      .iter()
+      .map(|r| HistoryRecord {
+        tasks: r
+          .tasks
+          .iter()
+          .map(|t| TaskRun {
+            output: String::new(),
+            ..t.clone()
+          })
+          .collect(),
+        ..(*r).clone()
+      })
+      .collect();
+    let out = serde_json::to_string_pretty(&summaries)
+      .map_err(|e| RunnerError::Serialize(e.to_string()))?;
+    println!("{out}");
+    return Ok(());
+  }
+
+  if matching.is_empty() {
+    eprintln!("No matching runs recorded.");
+    return Ok(());
+  }
+  matching.reverse();
+  for r in matching {
+    let at = match (&r.branch, &r.commit) {
+      (Some(branch), Some(commit)) => {
+        format!("{branch}@{}", &commit[..commit.len().min(7)])
+      }
+      (Some(branch), None) => branch.clone(),
+      (None, Some(commit)) => commit[..commit.len().min(7)].to_string(),
+      (None, None) => String::new(),
+    };
+    println!(
+      "#{:<5} {}  {:<20} {} {:>8}  {at}",
+      r.id,
+      format_timestamp(r.started),
+      r.hook,
+      status_label(r.success, color),
+      format_duration(r.duration_ms),
+    );
+  }
+  Ok(())
+}
@@ -0,0 +1,118 @@
+//! Project setup for the `init` subcommand.
+//!
+//! `huk init` makes sure the configuration file has a `hooks` object, and with
+//! `--auto-install` hooks `huk install --auto --quiet` into the project's
+//! dependency installation: the `prepare` script of `package.json`, or a
+//! `prepare` task in `deno.json`. Both edits go through the same writer as
+//! `huk add`, leave the rest of the file untouched, and do nothing when
+//! already made.
+
+use std::env;
+
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::cli::InitOpts;
+use crate::config::ConfigSource;
+use crate::config::HookConfig;
+use crate::runner::RunnerError;
+use crate::runner::mutate_config;
+
+/// The command run after dependencies are installed.
+pub const INSTALL_COMMAND: &str = "huk install --auto --quiet";
+
+/// Handle the `init` subcommand.
+pub fn handle_init(opts: &InitOpts) -> Result<(), RunnerError> {
+  let cfg = HookConfig::discover(&env::current_dir()?)?;
+  let mut changes = Vec::new();
+  let changed = mutate_config(&cfg, |value| {
+    let obj = value.as_object_mut().ok_or_else(|| {
+      RunnerError::InvalidConfigShape(cfg.source.as_str().to_string())
+    })?;
+    if !obj.get("hooks").is_some_and(Value::is_object) {
+      obj.insert("hooks".into(), Value::Object(Map::new()));
+      changes.push("added an empty `hooks` object".to_string());
+    }
+    if opts.auto_install
+      && let Some(change) = add_auto_install(&cfg.source, obj)
+    {
+      changes.push(change);
+    }
+    Ok(())
+  })?;
+  if !changed {
+    eprintln!("{} is already set up for huk.", cfg.source.as_str());
+    return Ok(());
+  }
+  for change in changes {
+    eprintln!("Updated {}: {change}", cfg.source.as_str());
+  }
+  if opts.auto_install && matches!(cfg.source, ConfigSource::DenoJson(_)) {
+    eprintln!(
+      "Deno does not run tasks on install; have teammates run `deno task \
+       prepare` after cloning."
+    );
+  }
+  Ok(())
+}
+
+/// Add [`INSTALL_COMMAND`] to the `prepare` script or task of the
+/// configuration object `obj`, after any command already there. Returns a
+/// description of the change, or `None` if hooks are already installed
+/// there.
+pub fn add_auto_install(
+  source: &ConfigSource,
+  obj: &mut Map<String, Value>,
+) -> Option<String> {
+  let (section, command) = match source {
+    ConfigSource::PackageJson(_) => ("scripts", INSTALL_COMMAND.to_string()),
+    ConfigSource::DenoJson(_) => ("tasks", deno_install_command(obj)),
+  };
+  let entries = obj
+    .entry(section)
+    .or_insert_with(|| Value::Object(Map::new()));
+  if !entries.is_object() {
+    return None;
+  }
+  let prepare = entries
+    .as_object_mut()?
+    .entry("prepare")
+    .or_insert_with(|| Value::String(String::new()));
+  // Deno tasks may be objects with a `command` field.
+  let slot = match prepare {
+    Value::Object(task) => task
+      .entry("command")
+      .or_insert_with(|| Value::String(String::new())),
+    other => other,
+  };
+  let existing = slot.as_str()?.trim().to_string();
+  if existing.contains("huk install") {
+    return None;
+  }
+  *slot = Value::String(if existing.is_empty() {
+    command.clone()
+  } else {
+    format!("{existing} && {command}")
+  });
+  Some(format!("`{section}.prepare` runs `{command}`"))
+}
+
+/// How a Deno task runs `huk install`: through the `npm:huk` package when
+/// the configuration imports it, otherwise from `PATH`.
+fn deno_install_command(obj: &Map<String, Value>) -> String {
+  let imported =
+    obj
+      .get("imports")
+      .and_then(Value::as_object)
+      .is_some_and(|imports| {
+        imports
+          .values()
+          .filter_map(Value::as_str)
+          .any(|target| target.starts_with("npm:huk"))
+      });
+  if imported {
+    format!("deno run -A npm:{INSTALL_COMMAND}")
+  } else {
+    INSTALL_COMMAND.to_string()
+  }
+}
@@ -3,21 +3,48 @@
 //! This module provides functionality for generating executable wrapper scripts
 //! in the Git hooks directory. Each wrapper script invokes the `huk` binary
 //! with the appropriate `run` subcommand to execute configured tasks. The
-//! installer respects the `core.hooksPath` Git configuration if present; if
-//! unspecified, it falls back to the repository's `.git/hooks` directory.
+//! hooks directory is the one Git itself uses: `core.hooksPath` if present,
+//! otherwise `.git/hooks`, shared by linked worktrees, or
+//! `.git/modules/<name>/hooks` in a submodule.
+//!
+//! Hook scripts that were there before, such as those of Git LFS, are moved
+//! to `<hook>.bak` and run by the wrapper before or after `huk`, and
+//! uninstalling puts them back.
+//!
+//! With `--managed`, the scripts go into a `.huk` directory at the top of the
+//! working tree, meant to be committed, and `core.hooksPath` is pointed at it.
+//! The manifest records that setting so that uninstalling only undoes it if
+//! `huk` made it.
 
+use crate::VERSION;
 use crate::cli::InstallOpts;
 use crate::cli::UninstallOpts;
 use crate::config::ConfigError;
 use crate::config::HookConfig;
+use crate::constants::GIT_HOOKS;
+use crate::git;
+use crate::git::git_output;
+use crate::manifest::HooksPath;
+use crate::manifest::InstalledHook;
+use crate::manifest::Manifest;
+use crate::manifest::ManifestStore;
+use crate::manifest::hash_script;
+use crate::refs::STDIN_HOOKS;
+use crate::server;
+use crate::server::ServerError;
+use crate::shell;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::BTreeSet;
 use std::env;
 use std::fs;
 #[cfg(target_family = "unix")]
 use std::os::unix::fs::PermissionsExt;
 #[cfg(target_family = "windows")]
 use std::os::windows::fs::PermissionsExt;
+use std::path::Path;
 use std::path::PathBuf;
-use std::process::Command;
+use std::sync::LazyLock;
 use thiserror::Error;
 
 /// Errors that may occur during installation of hooks.
@@ -32,149 +59,641 @@ pub enum InstallError {
   /// I/O error while writing files.
   #[error(transparent)]
   Io(#[from] std::io::Error),
+  /// Failed to load the configuration of a bare repository.
+  #[error(transparent)]
+  Server(#[from] ServerError),
+}
+
+/// The directory, relative to the top of the working tree, that
+/// `huk install --managed` writes the hook scripts to.
+pub const MANAGED_HOOKS_DIR: &str = ".huk";
+
+/// Load the configuration for `cwd`, reading it from the
+/// repository itself when that is bare.
+pub fn load_config(cwd: &Path) -> Result<HookConfig, InstallError> {
+  if server::is_bare(cwd) {
+    Ok(server::load_config(cwd)?)
+  } else {
+    Ok(HookConfig::discover(cwd)?)
+  }
+}
+
+/// Comment line identifying the wrapper scripts generated by `huk`.
+pub const MARKER: &str = "# Auto-generated by huk.";
+
+/// Hooks installed with `huk.sync` so that huk can reinstall the hooks when
+/// a checkout, merge or rebase changes the configuration.
+pub const SYNC_HOOKS: [&str; 3] =
+  ["post-checkout", "post-merge", "post-rewrite"];
+
+/// When a hook script that existed before `huk install` runs, relative to
+/// `huk`. Set with `huk.existing_hooks`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExistingHooks {
+  /// Run the original script first, and `huk` only if it succeeds.
+  #[default]
+  Before,
+  /// Run `huk` first, and the original script only if it succeeds.
+  After,
+}
+
+/// The path an existing hook script is moved to when `huk` is installed.
+pub fn backup_path(script_path: &Path) -> PathBuf {
+  let mut name = script_path.file_name().unwrap_or_default().to_os_string();
+  name.push(".bak");
+  script_path.with_file_name(name)
+}
+
+/// Whether the script at `path` is a wrapper generated by `huk`.
+pub fn is_huk_script(path: &Path) -> bool {
+  fs::read_to_string(path).is_ok_and(|script| script.contains(MARKER))
+}
+
+/// Compose the wrapper script for `hook`. The script looks for `huk` each
+/// time it runs, in the order of [`locate_huk`], so that it keeps working
+/// when the binary is upgraded or moved, and when the hooks directory is
+/// shared between checkouts. If `huk` cannot be found the hook fails with an
+/// explanation, or passes with a warning when `HUK_SKIP_MISSING` is set to
+/// anything but `0` (the default with `skip_missing`).
+///
+/// With `chain`, the original script saved next to it as `<hook>.bak` runs
+/// as well, if it is still executable, and the hook fails as soon as either
+/// of them does. Whatever runs last is started with `exec`, unless the
+/// script has saved standard input to a file it must remove afterwards.
+pub fn wrapper_script(
+  hook: &str,
+  chain: Option<ExistingHooks>,
+  skip_missing: bool,
+) -> String {
+  let name = shell::quote(hook);
+  let run = format!("run {name} -- \"$@\"");
+  let mut script = format!(
+    "#!/bin/sh\n{MARKER}\n# huk is looked up on every run: $HUK_BIN, \
+     node_modules/.bin/huk, the huk\n# package through deno or npx, then \
+     PATH.\n"
+  );
+  if skip_missing {
+    script.push_str("HUK_SKIP_MISSING=${HUK_SKIP_MISSING-1}\n");
+  }
+  script.push_str("huk_exec=\n");
+  script.push_str(&format!(
+    "huk_run() {{\n  \
+       if [ -n \"${{HUK_BIN-}}\" ]; then\n    \
+         $huk_exec \"$HUK_BIN\" {run}\n  \
+       elif [ -x node_modules/.bin/huk ]; then\n    \
+         $huk_exec node_modules/.bin/huk {run}\n  \
+       elif command -v deno >/dev/null 2>&1 &&\n    \
+         grep -qs '\"npm:huk' deno.json deno.jsonc; then\n    \
+         $huk_exec deno run -A npm:huk {run}\n  \
+       elif command -v npx >/dev/null 2>&1 &&\n    \
+         grep -Eqs '\"huk\"[[:space:]]*:[[:space:]]*\"' package.json; then\n    \
+         $huk_exec npx --no-install huk {run}\n  \
+       elif command -v huk >/dev/null 2>&1; then\n    \
+         $huk_exec huk {run}\n  \
+       else\n    \
+         echo \"huk: cannot run the {hook} hook: huk was not found in \
+         \\$HUK_BIN, node_modules/.bin, deno, npx or PATH.\" >&2\n    \
+         if [ \"${{HUK_SKIP_MISSING-0}}\" != 0 ]; then\n      \
+           echo \"huk: skipping the hook because HUK_SKIP_MISSING is set.\" \
+         >&2\n      \
+           return 0\n    \
+         fi\n    \
+         echo \"huk: install huk, set HUK_BIN to its path, or set \
+         HUK_SKIP_MISSING=1 to skip hooks while it is missing.\" >&2\n    \
+         return 1\n  \
+       fi\n\
+     }}\n"
+  ));
+  // The last call replaces the shell with huk, unless the script still
+  // has to clean up after it.
+  let Some(order) = chain else {
+    script.push_str("huk_exec=exec\nhuk_run \"$@\"\n");
+    return script;
+  };
+  let backup = format!("{hook}.bak");
+  let backup = shell::quote(&backup);
+  let when = match order {
+    ExistingHooks::Before => "before",
+    ExistingHooks::After => "after",
+  };
+  script.push_str(&format!(
+    "# The original hook, saved as {backup}, runs {when} huk.\n\
+     original=\"$(dirname \"$0\")\"/{backup}\n"
+  ));
+  let original = "if [ -x \"$original\" ]; then \"$original\" \"$@\"";
+  // Standard input is saved so that it can be given to both the original
+  // script and huk.
+  let (input, exec) = if STDIN_HOOKS.contains(&hook) {
+    script.push_str(
+      "input=$(mktemp) || exit 1\ntrap 'rm -f \"$input\"' EXIT\ncat \
+       >\"$input\"\n",
+    );
+    (" <\"$input\"", "")
+  } else {
+    ("", "exec ")
+  };
+  match order {
+    ExistingHooks::Before => script.push_str(&format!(
+      "{original}{input} || exit; fi\n{exec_huk}huk_run \"$@\"{input}\n",
+      exec_huk = if exec.is_empty() {
+        ""
+      } else {
+        "huk_exec=exec\n"
+      }
+    )),
+    ExistingHooks::After => script.push_str(&format!(
+      "huk_run \"$@\"{input} || exit\nif [ -x \"$original\" ]; then \
+       {exec}\"$original\" \"$@\"{input}; fi\n"
+    )),
+  }
+  script
+}
+
+/// The `huk` binary a wrapper script runs, for wrappers written by versions
+/// of `huk` that recorded the path of the binary instead of looking it up.
+pub fn wrapper_binary(script: &str) -> Option<String> {
+  script.lines().find_map(|line| {
+    let line = line.strip_prefix("exec ").unwrap_or(line);
+    let words = shell::split(line).ok()?;
+    (words.get(1).map(String::as_str) == Some("run")
+      && Path::new(&words[0]).is_absolute())
+    .then(|| words[0].clone())
+  })
+}
+
+/// Find `huk` the way wrapper scripts do when run in `dir`: `HUK_BIN`, then
+/// `node_modules/.bin/huk`, then the `huk` package through `deno` (when
+/// `deno.json` imports `npm:huk`) or `npx` (when `package.json` depends on
+/// it), then `PATH`. Returns the command that would run.
+pub fn locate_huk(dir: &Path) -> Option<String> {
+  if let Some(bin) = env::var_os("HUK_BIN").filter(|bin| !bin.is_empty()) {
+    return Some(bin.to_string_lossy().into_owned());
+  }
+  let local = dir.join("node_modules/.bin/huk");
+  if local.is_file() {
+    return Some(local.display().to_string());
+  }
+  let read =
+    |file: &str| fs::read_to_string(dir.join(file)).unwrap_or_default();
+  if which::which("deno").is_ok()
+    && (read("deno.json").contains("\"npm:huk")
+      || read("deno.jsonc").contains("\"npm:huk"))
+  {
+    return Some("deno run -A npm:huk".to_string());
+  }
+  /// A `"huk": "<version>"` entry, as in the dependencies of `package.json`.
+  static DEPENDENCY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#""huk"\s*:\s*""#).unwrap());
+  if which::which("npx").is_ok() && DEPENDENCY.is_match(&read("package.json")) {
+    return Some("npx --no-install huk".to_string());
+  }
+  which::which("huk")
+    .ok()
+    .map(|path| path.display().to_string())
 }
 
 /// Handle the `install` subcommand.
 pub fn handle_install(opts: &InstallOpts) -> Result<(), InstallError> {
-  let cfg =
-    HookConfig::discover(&env::current_dir().map_err(InstallError::Io)?)?;
-  let hooks_dir = find_git_hooks_dir(opts.hooks_dir.clone())?;
+  let cwd = env::current_dir()?;
+  install(&cwd, opts)?;
+  if opts.recursive {
+    for dir in submodules(&cwd) {
+      // Only submodules that use huk themselves get hooks.
+      if HookConfig::discover(&dir).is_err() {
+        continue;
+      }
+      if !opts.quiet {
+        eprintln!("Installing hooks in submodule {}", dir.display());
+      }
+      install(
+        &dir,
+        &InstallOpts {
+          hooks_dir: None,
+          force:     opts.force,
+          managed:   opts.managed,
+          auto:      opts.auto,
+          quiet:     opts.quiet,
+          recursive: false,
+        },
+      )?;
+    }
+  }
+  Ok(())
+}
+
+/// The checked-out submodules of the repository containing `cwd`, nested
+/// ones included.
+fn submodules(cwd: &Path) -> Vec<PathBuf> {
+  git_output(
+    cwd,
+    &["submodule", "foreach", "--quiet", "--recursive", "pwd"],
+  )
+  .map(|out| out.lines().map(PathBuf::from).collect())
+  .unwrap_or_default()
+}
+
+/// Install the hooks of the repository containing `cwd`.
+pub fn install(cwd: &Path, opts: &InstallOpts) -> Result<(), InstallError> {
+  // Run from a package manager lifecycle script, installing is only wanted
+  // in a developer's clone.
+  if opts.auto && (in_ci() || git::git_dir(cwd).is_err()) {
+    return Ok(());
+  }
+  install_hooks(cwd, opts)
+}
+
+/// Write the hook scripts of the repository containing `cwd`, whatever the
+/// environment.
+fn install_hooks(cwd: &Path, opts: &InstallOpts) -> Result<(), InstallError> {
+  let cwd = cwd.to_path_buf();
+  let quiet = opts.quiet;
+  let cfg = load_config(&cwd)?;
+  let store = ManifestStore::discover(&cwd)?;
+  let mut manifest = store.load()?;
+  // Scripts left behind in the hooks directory Git used before --managed.
+  let mut unmanaged = None;
+  let hooks_dir = if opts.managed {
+    let top = git::top_level(&cwd).ok_or_else(|| {
+      InstallError::HooksDir(
+        "--managed needs a repository with a working tree".into(),
+      )
+    })?;
+    let value = opts
+      .hooks_dir
+      .clone()
+      .unwrap_or_else(|| MANAGED_HOOKS_DIR.to_string());
+    let previous = find_git_hooks_dir(&cwd, None)?;
+    set_hooks_path(&cwd, &value, &mut manifest, quiet)?;
+    unmanaged = Some(previous);
+    top.join(value)
+  } else {
+    find_git_hooks_dir(&cwd, opts.hooks_dir.clone())?
+  };
   // ensure the hooks directory exists.
   fs::create_dir_all(&hooks_dir)?;
-  // Determine the path to the currently running binary. If this fails,
-  // fallback to calling `huk` from PATH.
-  let exe_path = env::current_exe().unwrap_or_else(|_| PathBuf::from("huk"));
-
-  let mut hook_names: Vec<&String> = cfg.hooks.keys().collect();
-  hook_names.sort();
+  let hooks_dir = fs::canonicalize(&hooks_dir)?;
 
-  for hook_name in hook_names {
+  let hook_names = installed_hooks(&cfg);
+  for hook_name in &hook_names {
     let script_path = hooks_dir.join(hook_name);
-    if script_path.exists() && !opts.force {
-      eprintln!(
-        "Skipping existing hook script '{}'; use --force to overwrite",
-        script_path.display()
-      );
-      continue;
+    // A script installed elsewhere, before core.hooksPath changed, is no
+    // longer run by Git.
+    if let Some(entry) = manifest.hooks.get(hook_name)
+      && entry.path != script_path
+    {
+      let old = entry.path.clone();
+      if owns(&manifest, hook_name, &old) {
+        remove_script(&old, quiet)?;
+      }
+      manifest.hooks.remove(hook_name);
     }
-    // Compose the wrapper script. Use exec so that signals propagate.
-    let script = format!(
-      "#!/bin/sh\n# Auto-generated by huk.\nexec {} run {} \"$@\"\n",
-      escape_shell(&exe_path.display().to_string()),
-      hook_name
-    );
-    fs::write(&script_path, script)?;
+    let backup = backup_path(&script_path);
+    // Scripts installed by something else are kept as a backup and run
+    // alongside huk, unless --force replaces them outright.
+    if script_path.exists()
+      && !owns(&manifest, hook_name, &script_path)
+      && !opts.force
+    {
+      if backup.exists() {
+        eprintln!(
+          "Skipping existing hook script '{}' because '{}' already exists; \
+           use --force to overwrite",
+          script_path.display(),
+          backup.display()
+        );
+        continue;
+      }
+      fs::rename(&script_path, &backup)?;
+      if !quiet {
+        eprintln!(
+          "Moved existing hook script to {}; it will run {} huk",
+          backup.display(),
+          match cfg.settings.existing_hooks {
+            ExistingHooks::Before => "before",
+            ExistingHooks::After => "after",
+          }
+        );
+      }
+    }
+    let chain = backup.exists().then_some(cfg.settings.existing_hooks);
+    let script =
+      wrapper_script(hook_name, chain, cfg.settings.skip_when_missing);
+    fs::write(&script_path, &script)?;
     // Make the script executable.
     let mut perms = fs::metadata(&script_path)?.permissions();
     perms.set_mode(0o755);
     fs::set_permissions(&script_path, perms)?;
-    eprintln!("Installed hook script: {}", script_path.display());
+    manifest.hooks.insert(
+      hook_name.clone(),
+      InstalledHook {
+        path:    script_path.clone(),
+        hash:    hash_script(script.as_bytes()),
+        version: VERSION.to_string(),
+      },
+    );
+    if !quiet {
+      eprintln!("Installed hook script: {}", script_path.display());
+    }
+  }
+
+  // Remove the scripts of hooks that are no longer configured.
+  let removed: Vec<String> = manifest
+    .hooks
+    .keys()
+    .filter(|hook| !hook_names.contains(*hook))
+    .cloned()
+    .collect();
+  for hook_name in removed {
+    let path = manifest.hooks[&hook_name].path.clone();
+    if path.exists() {
+      if owns(&manifest, &hook_name, &path) {
+        remove_script(&path, quiet)?;
+      } else {
+        eprintln!(
+          "Leaving '{}' in place: '{hook_name}' is no longer configured, \
+           but the script was not written by huk",
+          path.display()
+        );
+      }
+    }
+    manifest.hooks.remove(&hook_name);
+  }
+  manifest.config_hash = cfg.settings.sync.then(|| config_hash(&cfg));
+  store.save(&manifest)?;
+
+  if let Some(previous) = unmanaged
+    && previous.canonicalize().ok().as_ref() != Some(&hooks_dir)
+  {
+    let ignored: Vec<&str> = GIT_HOOKS
+      .iter()
+      .copied()
+      .filter(|hook| {
+        let path = previous.join(hook);
+        path.is_file() && !is_huk_script(&path)
+      })
+      .collect();
+    if !ignored.is_empty() {
+      eprintln!(
+        "warning: Git no longer runs these scripts in {}: {}",
+        previous.display(),
+        ignored.join(", ")
+      );
+    }
+  }
+
+  // Git runs hooks from the top of the working tree.
+  let top = git_output(&cwd, &["rev-parse", "--show-toplevel"])
+    .map(PathBuf::from)
+    .unwrap_or(cwd);
+  if !hook_names.is_empty() && locate_huk(&top).is_none() {
+    eprintln!(
+      "warning: the hooks will not find huk: it is not on PATH, in \
+       node_modules/.bin or a dependency, and HUK_BIN is not set"
+    );
+  }
+  Ok(())
+}
+
+/// The hooks `huk install` writes scripts for: the configured hooks, and
+/// [`SYNC_HOOKS`] when `huk.sync` is enabled.
+pub fn installed_hooks(cfg: &HookConfig) -> BTreeSet<String> {
+  let sync = SYNC_HOOKS.iter().filter(|_| cfg.settings.sync);
+  cfg
+    .hooks
+    .keys()
+    .cloned()
+    .chain(sync.map(|hook| hook.to_string()))
+    .collect()
+}
+
+/// Hash of what the installed scripts depend on: which hooks are installed
+/// and the settings that shape the wrappers.
+pub fn config_hash(cfg: &HookConfig) -> String {
+  let hooks: Vec<String> = installed_hooks(cfg).into_iter().collect();
+  let settings = &cfg.settings;
+  hash_script(
+    format!(
+      "{}\n{:?}\n{}",
+      hooks.join(","),
+      settings.existing_hooks,
+      settings.skip_when_missing
+    )
+    .as_bytes(),
+  )
+}
+
+/// Reinstall the hooks if the configuration changed since they were
+/// installed with `huk.sync`, as recorded in the manifest. Called from the
+/// [`SYNC_HOOKS`]; returns whether anything was reinstalled.
+pub fn sync(cwd: &Path, cfg: &HookConfig) -> Result<bool, InstallError> {
+  let manifest = ManifestStore::discover(cwd)?.load()?;
+  let Some(installed) = &manifest.config_hash else {
+    return Ok(false);
+  };
+  // Turning `huk.sync` off also changes the hash, so the sync hooks remove
+  // themselves.
+  let current = cfg.settings.sync.then(|| config_hash(cfg));
+  if current.as_ref() == Some(installed) {
+    return Ok(false);
+  }
+  // The hooks were installed here before, so unlike `huk install --auto`
+  // this does not depend on whether `CI` is set.
+  let hooks_dir = manifest.hooks_path.map(|set| set.value);
+  install_hooks(
+    cwd,
+    &InstallOpts {
+      managed: hooks_dir.is_some(),
+      hooks_dir,
+      force: false,
+      auto: false,
+      quiet: true,
+      recursive: false,
+    },
+  )?;
+  Ok(true)
+}
+
+/// Point `core.hooksPath` at `value`, recording in `manifest` what it was
+/// before so that uninstalling can put it back. A setting that already has
+/// this value is left alone, and is only recorded if huk made it. The change
+/// is reported unless `quiet` is set.
+pub fn set_hooks_path(
+  cwd: &Path,
+  value: &str,
+  manifest: &mut Manifest,
+  quiet: bool,
+) -> Result<(), InstallError> {
+  let current = git_output(cwd, &["config", "--get", "core.hooksPath"])
+    .filter(|path| !path.is_empty());
+  if current.as_deref() == Some(value) {
+    return Ok(());
+  }
+  git::set_config(cwd, "core.hooksPath", Some(value))?;
+  if !quiet {
+    eprintln!(
+      "Set core.hooksPath to {value}{}",
+      current
+        .as_ref()
+        .map(|path| format!(" (was {path})"))
+        .unwrap_or_default()
+    );
+  }
+  // Reinstalling into another directory keeps the original setting.
+  let previous = match manifest.hooks_path.take() {
+    Some(set) => set.previous,
+    None => current,
+  };
+  manifest.hooks_path = Some(HooksPath {
+    value: value.to_string(),
+    previous,
+  });
+  Ok(())
+}
+
+/// Undo the `core.hooksPath` setting recorded in `manifest`, unless it has
+/// been changed since huk made it.
+pub fn restore_hooks_path(
+  cwd: &Path,
+  manifest: &mut Manifest,
+) -> Result<(), InstallError> {
+  let Some(set) = manifest.hooks_path.take() else {
+    return Ok(());
+  };
+  let current = git_output(cwd, &["config", "--get", "core.hooksPath"]);
+  if current.as_deref() != Some(set.value.as_str()) {
+    return Ok(());
+  }
+  git::set_config(cwd, "core.hooksPath", set.previous.as_deref())?;
+  match set.previous {
+    Some(previous) => eprintln!("Restored core.hooksPath to {previous}"),
+    None => eprintln!("Unset core.hooksPath"),
+  }
+  Ok(())
+}
+
+/// Whether the script at `path` belongs to huk: either it is exactly what
+/// the manifest records for `hook`, or it carries huk's [`MARKER`].
+pub fn owns(manifest: &Manifest, hook: &str, path: &Path) -> bool {
+  manifest.matches(hook, path) || is_huk_script(path)
+}
+
+/// Remove the script at `script_path` and put back the original script it
+/// replaced, if any, reporting what was done unless `quiet` is set.
+fn remove_script(script_path: &Path, quiet: bool) -> Result<(), InstallError> {
+  fs::remove_file(script_path)?;
+  if !quiet {
+    eprintln!("Removed hook script: {}", script_path.display());
+  }
+  let backup = backup_path(script_path);
+  if backup.exists() {
+    fs::rename(&backup, script_path)?;
+    if !quiet {
+      eprintln!(
+        "Restored backup of original hook script: {}",
+        script_path.display()
+      );
+    }
   }
   Ok(())
 }
 
+/// Whether the `CI` environment variable says this is a CI run.
+fn in_ci() -> bool {
+  env::var("CI").is_ok_and(|value| is_ci_value(&value))
+}
+
+/// Whether `value`, as the `CI` environment variable, marks a CI run: it
+/// does unless it is empty, `false` or `0`.
+pub fn is_ci_value(value: &str) -> bool {
+  !matches!(
+    value.trim().to_ascii_lowercase().as_str(),
+    "" | "false" | "0"
+  )
+}
+
+/// Handle the `uninstall` subcommand. Without hook names, every hook in the
+/// manifest or the configuration is uninstalled, including hooks that have
+/// since been removed from the configuration.
 pub fn handle_uninstall(opts: &UninstallOpts) -> Result<(), InstallError> {
-  let hooks_dir = find_git_hooks_dir(opts.hooks_dir.clone())?;
-  let cfg =
-    HookConfig::discover(&env::current_dir().map_err(InstallError::Io)?)?;
+  uninstall(&env::current_dir()?, opts)
+}
 
-  let mut hook_names: Vec<&String> = cfg.hooks.keys().collect();
-  hook_names.sort();
+/// Uninstall the hooks of the repository containing `cwd`.
+pub fn uninstall(cwd: &Path, opts: &UninstallOpts) -> Result<(), InstallError> {
+  let hooks_dir = find_git_hooks_dir(cwd, opts.hooks_dir.clone())?;
+  let store = ManifestStore::discover(cwd)?;
+  let mut manifest = store.load()?;
 
+  let hook_names: BTreeSet<String> = if opts.hooks.is_empty() {
+    // The configuration may be gone or broken; the manifest still knows
+    // what was installed.
+    let configured = load_config(cwd)
+      .map(|cfg| cfg.hooks.into_keys().collect())
+      .unwrap_or_else(|_| Vec::new());
+    manifest.hooks.keys().cloned().chain(configured).collect()
+  } else {
+    opts.hooks.iter().cloned().collect()
+  };
   if hook_names.is_empty() {
-    if opts.hooks.is_empty() {
-      eprintln!("No hooks configured; nothing to uninstall.");
-      return Ok(());
-    }
-    eprintln!("Uninstalling all configured hooks...");
-    for hook_name in &opts.hooks {
-      let script_path = hooks_dir.join(hook_name);
-      // look for a backup file in case we overwrote an existing hook on
-      // install.
-      let maybe_backup_path = hooks_dir.join(format!("{}.bak", hook_name));
-      if script_path.exists() {
-        fs::remove_file(&script_path)?;
-        if maybe_backup_path.exists() {
-          fs::rename(&maybe_backup_path, &script_path)?;
-          eprintln!(
-            "Restored backup of original hook script: {}",
-            script_path.display()
-          );
-        }
-        eprintln!("Removed hook script: {}", script_path.display());
-      } else {
+    eprintln!("No hooks installed or configured; nothing to uninstall.");
+    return Ok(());
+  }
+
+  for hook_name in &hook_names {
+    let script_path = manifest
+      .hooks
+      .get(hook_name)
+      .map(|entry| entry.path.clone())
+      .unwrap_or_else(|| hooks_dir.join(hook_name));
+    if !script_path.exists() {
+      if !opts.force {
         eprintln!(
           "Hook script '{}' does not exist; skipping",
           script_path.display()
         );
       }
+    } else if owns(&manifest, hook_name, &script_path) || opts.force {
+      remove_script(&script_path, false)?;
+    } else {
+      eprintln!(
+        "Skipping '{}', which was not installed by huk; use --force to \
+         remove it anyway",
+        script_path.display()
+      );
+      continue;
     }
-  } else {
-    for hook_name in hook_names {
-      if opts.hooks.is_empty() || opts.hooks.contains(hook_name) {
-        let script_path = hooks_dir.join(hook_name);
-        if script_path.exists() {
-          fs::remove_file(&script_path)?;
-          eprintln!("Removed hook script: {}", script_path.display());
-        } else {
-          eprintln!(
-            "Hook script '{}' does not exist; skipping",
-            script_path.display()
-          );
-        }
-      }
-    }
+    manifest.hooks.remove(hook_name);
+  }
+  if manifest.hooks.is_empty() && manifest.hooks_path.is_some() {
+    restore_hooks_path(cwd, &mut manifest)?;
+    // The managed directory is only removed once it is empty.
+    let _ = fs::remove_dir(&hooks_dir);
   }
+  store.save(&manifest)?;
   Ok(())
 }
 
-/// Determine the git hooks directory using `git config core.hooksPath` if set,
-/// otherwise defaulting to `.git/hooks` relative to the repository root.
-fn find_git_hooks_dir(
+/// Determine the git hooks directory of the repository containing `cwd` as
+/// an absolute path. A directory given by the user takes precedence;
+/// otherwise Git is asked with `git rev-parse --git-path hooks`, which
+/// honours `core.hooksPath` and `GIT_DIR`, and resolves to the common
+/// directory in linked worktrees and to `.git/modules/<name>` in submodules.
+pub fn find_git_hooks_dir(
+  cwd: &Path,
   user_hooks_dir: Option<String>,
 ) -> Result<PathBuf, InstallError> {
   // If the user provided a custom hooks directory, try to use that first.
   if let Some(dir) = user_hooks_dir {
-    return Ok(PathBuf::from(dir));
-  }
-  // Try `git config core.hooksPath`.
-  let output = Command::new("git")
-    .args(["config", "--get", "core.hooksPath"])
-    .output();
-  if let Ok(out) = output
-    && out.status.success()
-  {
-    let path_str = String::from_utf8_lossy(&out.stdout).trim().to_string();
-    if !path_str.is_empty() {
-      return Ok(PathBuf::from(path_str));
-    }
+    return Ok(cwd.join(dir));
   }
-  // Fallback: locate the .git directory using `git rev-parse --git-dir`.
-  let output = Command::new("git")
-    .args(["rev-parse", "--git-dir"])
-    .output();
-  if let Ok(out) = output
-    && out.status.success()
-  {
-    let git_dir = String::from_utf8_lossy(&out.stdout).trim().to_string();
-    let hooks = PathBuf::from(git_dir).join("hooks");
-    return Ok(hooks);
-  }
-  Err(InstallError::HooksDir(
-    "unable to determine git hooks directory".into(),
-  ))
-}
-
-/// Escape a string for safe use in shell scripts. This implementation wraps
-/// values containing whitespace in single quotes and escapes existing single
-/// quotes. If the string contains no spaces it is returned unchanged.
-fn escape_shell(s: &str) -> String {
-  if s.contains(' ') {
-    // Replace single quotes with '\''; this ends the single-quoted string,
-    // inserts a literal single quote, then reopens single quotes.
-    let escaped = s.replace("'", "'\\''");
-    format!("'{}'", escaped)
-  } else {
-    s.to_string()
+  if let Some(path) = git::git_path(cwd, "hooks") {
+    return Ok(path);
   }
+  // Fallback: the hooks directory of the common Git directory.
+  git::common_dir(cwd)
+    .map(|dir| dir.join("hooks"))
+    .map_err(|_| {
+      InstallError::HooksDir("unable to determine git hooks directory".into())
+    })
 }
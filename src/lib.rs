@@ -1,6 +1,3 @@
-#![feature(associated_type_defaults)]
-#![feature(trait_alias)]
-
 //! Public library API for the `huk` crate.
 //!
 //! Although `huk` is primarily intended to be used as a CLI application,
@@ -9,11 +6,23 @@
 //! mirror those used by the CLI: configuration parsing, task definitions and
 //! execution logic.
 
+pub mod builtins;
+pub mod cache;
 pub mod cli;
 pub mod config;
 pub mod constants;
+pub mod doctor;
+pub mod git;
+pub mod history;
+pub mod init;
 pub mod install;
+pub mod manifest;
+pub mod package_manager;
+pub mod refs;
+pub mod report;
 pub mod runner;
+pub mod server;
+pub mod shell;
 pub mod task;
 pub mod tui;
 
@@ -21,6 +30,10 @@ pub mod tui;
 pub(crate) mod macros;
 
 pub(crate) mod handlers {
+  pub use crate::cache::*;
+  pub use crate::doctor::*;
+  pub use crate::history::*;
+  pub use crate::init::*;
   pub use crate::install::*;
   pub use crate::runner::*;
   pub use crate::tui::*;
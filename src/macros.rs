@@ -13,10 +13,10 @@ macro_rules! file_name {
 #[doc(hidden)]
 #[macro_export]
 macro_rules! print_tasks {
-  ($cfg:expr) => {
-    $crate::print_tasks!($cfg, compact = false);
+  ($cfg:expr, $tasks:expr) => {
+    $crate::print_tasks!($cfg, $tasks, compact = false);
   };
-  ($cfg:expr,compact = $compact:expr) => {
+  ($cfg:expr, $tasks:expr,compact = $compact:expr) => {
     let (kind, source, path) = match $cfg.source {
       $crate::config::ConfigSource::DenoJson(ref path) => {
         ("task", $crate::file_name!(path), path)
@@ -25,17 +25,16 @@ macro_rules! print_tasks {
         ("script", $crate::file_name!(path), path)
       }
     };
-    let mut all_tasks: Vec<&String> = Vec::new();
-
-    let mut deno_tasks: Vec<&String> = $cfg.deno_tasks.keys().collect();
-    deno_tasks.sort();
-    all_tasks.extend(deno_tasks.clone());
-
-    let mut node_scripts: Vec<&String> = $cfg.node_scripts.keys().collect();
-    node_scripts.sort();
-    all_tasks.extend(node_scripts.clone());
+    // Tasks from `huk.tasks` can accompany either kind of configuration.
+    let kind = if $cfg.huk_tasks.is_empty() {
+      kind
+    } else {
+      "task"
+    };
+    let tasks: &[$crate::runner::TaskEntry] = $tasks;
+    let base = path.parent().unwrap_or(::std::path::Path::new(""));
 
-    let mut n = all_tasks.len();
+    let n = tasks.len();
     if n == 0 {
       eprintln!(
         "No {kind}s found in '{path}'.",
@@ -48,21 +47,31 @@ macro_rules! print_tasks {
         path = path.display().to_string()
       );
 
-      while n > 0 {
-        let name = &all_tasks[all_tasks.len() - n];
-        let cmd = if let Some(script) = $cfg.node_scripts.get(*name) {
-          script
-        } else if let Some(script) = $cfg.deno_tasks.get(*name) {
-          script
-        } else {
-          "<unknown>"
+      for task in tasks {
+        let named = task.name.clone();
+        let src = match task.location {
+          Some(ref loc) => format!(
+            "{}:{}",
+            loc.path.strip_prefix(base).unwrap_or(&loc.path).display(),
+            loc.line
+          ),
+          None => source.to_string(),
         };
-        let named = (*name).clone();
-        let cmd = cmd.replace('\n', " ");
+        let mut details: Vec<String> = Vec::new();
+        if let Some(ref cmd) = task.command {
+          details.push(cmd.replace('\n', " "));
+        }
+        if !task.dependencies.is_empty() {
+          details.push(format!("depends on: {}", task.dependencies.join(", ")));
+        }
+        if !task.hooks.is_empty() {
+          details.push(format!("used by: {}", task.hooks.join(", ")));
+        }
         if $compact {
-          let src = format!("({source})");
-          eprintln!(r#"- {named} {src}"#);
-          eprintln!(r#"  {cmd}"#);
+          eprintln!(r#"- {named} ({src})"#);
+          if let Some(ref description) = task.description {
+            eprintln!(r#"  # {description}"#);
+          }
         } else {
           let name = format!(
             r#"{cyan}{named}{reset}"#,
@@ -70,16 +79,23 @@ macro_rules! print_tasks {
             reset = "\x1b[0m"
           );
           let src = format!(
-            r#"{italic}{gray}({source}){reset}"#,
+            r#"{italic}{gray}({src}){reset}"#,
             italic = "\x1b[3m",
             gray = "\x1b[90m",
             reset = "\x1b[0m"
           );
           eprintln!(r#"- {name} {src}"#);
-          eprintln!(r#"  {cmd}"#);
+          if let Some(ref description) = task.description {
+            eprintln!(
+              r#"  {dim}{description}{reset}"#,
+              dim = "\x1b[2m",
+              reset = "\x1b[0m"
+            );
+          }
+        }
+        for detail in details {
+          eprintln!(r#"  {detail}"#);
         }
-
-        n -= 1;
       }
     }
   };
@@ -6,11 +6,23 @@
 //! functionality can be found in the crate documentation and in the
 //! README.md accompanying this package.
 
+mod builtins;
+mod cache;
 mod cli;
 mod config;
 mod constants;
+mod doctor;
+mod git;
+mod history;
+mod init;
 mod install;
+mod manifest;
+mod package_manager;
+mod refs;
+mod report;
 mod runner;
+mod server;
+mod shell;
 mod task;
 mod tui;
 #[macro_use]
@@ -24,6 +36,10 @@ fn main() {
 }
 
 pub(crate) mod handlers {
+  pub use crate::cache::*;
+  pub use crate::doctor::*;
+  pub use crate::history::*;
+  pub use crate::init::*;
   pub use crate::install::*;
   pub use crate::runner::*;
   pub use crate::tui::*;
@@ -0,0 +1,120 @@
+//! Record of the hook scripts written by `huk install`.
+//!
+//! The manifest lives at `<git-dir>/huk/install.json` and lists, for every
+//! hook, the path of its script, the SHA-256 hash of the contents written
+//! and the version of `huk` that wrote it. Uninstalling consults it so that
+//! only scripts `huk` owns are removed, and reinstalling uses it to remove
+//! the scripts of hooks that are no longer configured. It also records the
+//! `core.hooksPath` setting made by `huk install --managed`, which is only
+//! undone if `huk` made it, and with `huk.sync` a hash of the configuration
+//! the hooks were installed from.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+
+use crate::git;
+
+/// The hook scripts installed in a repository, keyed by hook name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+  pub hooks:       BTreeMap<String, InstalledHook>,
+  /// The `core.hooksPath` setting made by `huk install --managed`, if any.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub hooks_path:  Option<HooksPath>,
+  /// Hash of the configuration the hooks were installed from, recorded
+  /// when `huk.sync` is enabled so that hooks can tell when it changed.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub config_hash: Option<String>,
+}
+
+/// A `core.hooksPath` value set by `huk`, so that it can be put back.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HooksPath {
+  /// The value `huk` set.
+  pub value:    String,
+  /// The value before that, if the setting existed.
+  pub previous: Option<String>,
+}
+
+/// A single hook script written by `huk install`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstalledHook {
+  /// Absolute path of the script.
+  pub path:    PathBuf,
+  /// Hex-encoded SHA-256 hash of the script as written.
+  pub hash:    String,
+  /// Version of `huk` that wrote the script.
+  pub version: String,
+}
+
+impl Manifest {
+  /// Whether the script at `path` is exactly the one recorded for `hook`.
+  pub fn matches(&self, hook: &str, path: &Path) -> bool {
+    self.hooks.get(hook).is_some_and(|entry| {
+      fs::read(path).is_ok_and(|content| hash_script(&content) == entry.hash)
+    })
+  }
+}
+
+/// The hex-encoded SHA-256 hash of a script's contents.
+pub fn hash_script(content: &[u8]) -> String {
+  Sha256::digest(content)
+    .iter()
+    .map(|b| format!("{b:02x}"))
+    .collect()
+}
+
+/// The file the manifest is read from and written to.
+#[derive(Debug, Clone)]
+pub struct ManifestStore {
+  path: PathBuf,
+}
+
+impl ManifestStore {
+  /// Create a store backed by the file at `path`.
+  pub fn new(path: impl Into<PathBuf>) -> Self {
+    Self { path: path.into() }
+  }
+
+  /// Open the store for the repository containing `cwd`, located at
+  /// `<git-dir>/huk/install.json`. Linked worktrees share their hooks, and
+  /// so use the manifest in the common Git directory.
+  pub fn discover(cwd: &Path) -> io::Result<Self> {
+    Ok(Self::new(git::common_dir(cwd)?.join("huk/install.json")))
+  }
+
+  /// Load the manifest, which is empty if nothing has been installed yet.
+  pub fn load(&self) -> io::Result<Manifest> {
+    match fs::read_to_string(&self.path) {
+      Ok(content) => serde_json::from_str(&content)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+      Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Manifest::default()),
+      Err(e) => Err(e),
+    }
+  }
+
+  /// Write the manifest, or remove the file once there is nothing left to
+  /// record.
+  pub fn save(&self, manifest: &Manifest) -> io::Result<()> {
+    if manifest.hooks.is_empty() && manifest.hooks_path.is_none() {
+      return match fs::remove_file(&self.path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+      };
+    }
+    if let Some(parent) = self.path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    let json =
+      serde_json::to_string_pretty(manifest).map_err(io::Error::other)?;
+    fs::write(&self.path, json + "\n")
+  }
+}
@@ -0,0 +1,369 @@
+//! Package manager detection for running Node scripts.
+//!
+//! The package manager is taken from the `packageManager` field of
+//! `package.json` when present, and otherwise inferred from the lockfile next
+//! to it (`pnpm-lock.yaml`, `yarn.lock`, `bun.lockb`/`bun.lock`,
+//! `package-lock.json`, `deno.lock`), falling back to npm. A version pinned
+//! in `packageManager` is checked against the installed binary; when they
+//! differ the script is run through `corepack`, which fetches the pinned
+//! release, or an error explains how to fix the mismatch.
+//!
+//! With the `direct_scripts` setting, scripts skip the package manager
+//! entirely: [`can_run_directly`] decides whether a script body is safe to
+//! run with `sh -c`, and [`script_env`] provides the environment npm would
+//! have set up for it.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+use thiserror::Error;
+
+/// The package managers `huk` knows how to run scripts with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PackageManagerKind {
+  Npm,
+  Pnpm,
+  Yarn,
+  Bun,
+  /// Deno's npm compatibility mode, which runs `package.json` scripts with
+  /// `deno task`.
+  Deno,
+}
+
+impl PackageManagerKind {
+  /// Parse a package manager name, as used in the `packageManager` field.
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name.trim().to_ascii_lowercase().as_str() {
+      "npm" => Some(Self::Npm),
+      "pnpm" => Some(Self::Pnpm),
+      "yarn" => Some(Self::Yarn),
+      "bun" => Some(Self::Bun),
+      "deno" => Some(Self::Deno),
+      _ => None,
+    }
+  }
+
+  /// Name of the executable.
+  pub const fn binary(self) -> &'static str {
+    match self {
+      Self::Npm => "npm",
+      Self::Pnpm => "pnpm",
+      Self::Yarn => "yarn",
+      Self::Bun => "bun",
+      Self::Deno => "deno",
+    }
+  }
+
+  /// Whether `corepack` can provide a pinned version of this manager.
+  const fn corepack_supported(self) -> bool {
+    matches!(self, Self::Npm | Self::Pnpm | Self::Yarn)
+  }
+}
+
+impl fmt::Display for PackageManagerKind {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.binary())
+  }
+}
+
+/// Lockfiles that identify a package manager, in order of precedence.
+const LOCKFILES: [(&str, PackageManagerKind); 7] = [
+  ("pnpm-lock.yaml", PackageManagerKind::Pnpm),
+  ("yarn.lock", PackageManagerKind::Yarn),
+  ("bun.lockb", PackageManagerKind::Bun),
+  ("bun.lock", PackageManagerKind::Bun),
+  ("package-lock.json", PackageManagerKind::Npm),
+  ("npm-shrinkwrap.json", PackageManagerKind::Npm),
+  ("deno.lock", PackageManagerKind::Deno),
+];
+
+/// How a [`PackageManager`] was chosen.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Detection {
+  /// The `packageManager` field of `package.json`.
+  Field,
+  /// A lockfile in the project directory.
+  Lockfile(PathBuf),
+  /// Nothing identified a manager, so npm is used.
+  Default,
+}
+
+/// The package manager selected for a project.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PackageManager {
+  pub kind:      PackageManagerKind,
+  /// Version pinned by the `packageManager` field, without any `+sha...`
+  /// integrity suffix.
+  pub version:   Option<String>,
+  pub detection: Detection,
+}
+
+/// Errors raised while locating a package manager.
+#[derive(Error, Debug)]
+pub enum PackageManagerError {
+  /// The `packageManager` field names a manager `huk` does not support.
+  #[error(
+    "unsupported package manager '{0}' in the packageManager field (expected \
+     npm, pnpm, yarn, bun or deno)"
+  )]
+  Unsupported(String),
+  /// The selected manager is not installed.
+  #[error(
+    "{kind} is required to run scripts in this project ({reason}) but was \
+     not found on PATH{hint}"
+  )]
+  NotFound {
+    kind:   PackageManagerKind,
+    reason: String,
+    hint:   String,
+  },
+  /// The installed manager does not match the pinned version and corepack
+  /// is unavailable.
+  #[error(
+    "packageManager pins {kind}@{pinned} but {kind} {installed} is \
+     installed; install the pinned version or enable corepack \
+     (`corepack enable`)"
+  )]
+  VersionMismatch {
+    kind:      PackageManagerKind,
+    pinned:    String,
+    installed: String,
+  },
+}
+
+impl PackageManager {
+  /// Select the package manager for the project in `dir`, preferring the
+  /// `packageManager` field over lockfiles.
+  pub fn detect(
+    dir: &Path,
+    field: Option<&str>,
+  ) -> Result<Self, PackageManagerError> {
+    if let Some(field) = field.map(str::trim).filter(|f| !f.is_empty()) {
+      let (name, version) = match field.split_once('@') {
+        Some((name, version)) => (name, Some(version)),
+        None => (field, None),
+      };
+      let kind = PackageManagerKind::from_name(name)
+        .ok_or_else(|| PackageManagerError::Unsupported(name.to_string()))?;
+      let version = version
+        .map(|v| v.split('+').next().unwrap_or(v).trim().to_string())
+        .filter(|v| !v.is_empty());
+      return Ok(Self {
+        kind,
+        version,
+        detection: Detection::Field,
+      });
+    }
+    for (file, kind) in LOCKFILES {
+      let path = dir.join(file);
+      if path.is_file() {
+        return Ok(Self {
+          kind,
+          version: None,
+          detection: Detection::Lockfile(path),
+        });
+      }
+    }
+    Ok(Self {
+      kind:      PackageManagerKind::Npm,
+      version:   None,
+      detection: Detection::Default,
+    })
+  }
+
+  /// Arguments after the executable that run `script` with `args`, following
+  /// each manager's conventions. npm needs `--` to stop it from consuming
+  /// flags meant for the script; pnpm, Yarn (both Classic and Berry) and Bun
+  /// forward everything after the script name, and would pass a `--` through
+  /// to the script literally. Deno runs `package.json` scripts with `deno
+  /// task`.
+  pub fn run_args(&self, script: &str, args: &[String]) -> Vec<String> {
+    let mut argv = match self.kind {
+      PackageManagerKind::Deno => vec!["task".to_string(), script.to_string()],
+      _ => vec!["run".to_string(), script.to_string()],
+    };
+    if !args.is_empty() {
+      if self.kind == PackageManagerKind::Npm {
+        argv.push("--".to_string());
+      }
+      argv.extend(args.iter().cloned());
+    }
+    argv
+  }
+
+  /// Whether this manager runs the `pre<name>` and `post<name>` scripts of
+  /// the project in `dir` around `<name>`. npm, Yarn Classic and Bun do.
+  /// pnpm only does with `enable-pre-post-scripts=true` in `.npmrc`. Yarn
+  /// Berry, recognised by a pinned version of 2 or later, a `.yarnrc.yml` or
+  /// the `__metadata` header of its lockfile, and Deno never do.
+  pub fn runs_pre_post_scripts(&self, dir: &Path) -> bool {
+    match self.kind {
+      PackageManagerKind::Npm | PackageManagerKind::Bun => true,
+      PackageManagerKind::Deno => false,
+      PackageManagerKind::Pnpm => std::fs::read_to_string(dir.join(".npmrc"))
+        .is_ok_and(|npmrc| {
+          npmrc.lines().any(|line| {
+            line.split_once('=').is_some_and(|(key, value)| {
+              key.trim() == "enable-pre-post-scripts" && value.trim() == "true"
+            })
+          })
+        }),
+      PackageManagerKind::Yarn => {
+        let berry = match &self.version {
+          Some(version) => !version.starts_with("1."),
+          None => {
+            dir.join(".yarnrc.yml").is_file()
+              || std::fs::read_to_string(dir.join("yarn.lock"))
+                .is_ok_and(|lock| lock.contains("__metadata:"))
+          }
+        };
+        !berry
+      }
+    }
+  }
+
+  /// A description of why this manager was chosen, for error messages.
+  fn reason(&self) -> String {
+    match &self.detection {
+      Detection::Field => match &self.version {
+        Some(version) => format!("packageManager is {}@{version}", self.kind),
+        None => format!("packageManager is {}", self.kind),
+      },
+      Detection::Lockfile(path) => format!(
+        "found {}",
+        path
+          .file_name()
+          .unwrap_or(path.as_os_str())
+          .to_string_lossy()
+      ),
+      Detection::Default => "default".to_string(),
+    }
+  }
+
+  /// Work out the command prefix used to invoke this manager: the binary
+  /// itself, or `corepack <binary>` when a pinned version differs from the
+  /// installed one (or the binary is missing) and corepack is available.
+  pub fn invocation(&self) -> Result<Vec<String>, PackageManagerError> {
+    let binary = self.kind.binary();
+    let corepack = || {
+      (self.version.is_some() && self.kind.corepack_supported())
+        .then(|| which::which("corepack").ok())
+        .flatten()
+        .map(|_| vec!["corepack".to_string(), binary.to_string()])
+    };
+    if which::which(binary).is_err() {
+      return corepack().ok_or_else(|| PackageManagerError::NotFound {
+        kind:   self.kind,
+        reason: self.reason(),
+        hint:   if self.kind.corepack_supported() {
+          "; install it or run `corepack enable`".to_string()
+        } else {
+          String::new()
+        },
+      });
+    }
+    let Some(pinned) = &self.version else {
+      return Ok(vec![binary.to_string()]);
+    };
+    match installed_version(binary) {
+      Some(installed) if installed != *pinned => {
+        corepack().ok_or(PackageManagerError::VersionMismatch {
+          kind: self.kind,
+          pinned: pinned.clone(),
+          installed,
+        })
+      }
+      _ => Ok(vec![binary.to_string()]),
+    }
+  }
+}
+
+/// Ask `binary` for its version. `deno --version` prints several lines such
+/// as `deno 2.1.4 (stable, ...)`; the other managers print a bare version.
+fn installed_version(binary: &str) -> Option<String> {
+  let out = Command::new(binary).arg("--version").output().ok()?;
+  if !out.status.success() {
+    return None;
+  }
+  let stdout = String::from_utf8_lossy(&out.stdout);
+  let first = stdout.lines().next()?.trim();
+  let version = first.strip_prefix(binary).unwrap_or(first).trim_start();
+  let version = version.split_whitespace().next()?;
+  Some(version.trim_start_matches('v').to_string())
+}
+
+/// Environment variables set by npm that [`script_env`] provides. Scripts
+/// referring to any other `npm_*` variable need the real package manager.
+const PROVIDED_NPM_VARS: [&str; 5] = [
+  "npm_package_name",
+  "npm_package_version",
+  "npm_package_json",
+  "npm_lifecycle_event",
+  "npm_lifecycle_script",
+];
+
+/// Whether a script body from the project in `dir` can be run directly with
+/// `sh -c` instead of through the package manager. This is not the case
+/// under Yarn Plug'n'Play, which has no `node_modules/.bin`, or when the
+/// script reads `npm_*` variables (such as `npm_config_*`) that only the
+/// package manager sets.
+pub fn can_run_directly(dir: &Path, body: &str) -> bool {
+  if dir.join(".pnp.cjs").exists() || dir.join(".pnp.js").exists() {
+    return false;
+  }
+  body.match_indices("npm_").all(|(i, _)| {
+    let rest = &body[i..];
+    PROVIDED_NPM_VARS.iter().any(|var| {
+      rest.strip_prefix(var).is_some_and(|after| {
+        !after.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_')
+      })
+    })
+  })
+}
+
+/// The environment npm sets up for a lifecycle script: package metadata,
+/// the lifecycle event and script body, and `PATH` with the
+/// `node_modules/.bin` directory of the package and each of its ancestors
+/// prepended, nearest first.
+pub fn script_env(
+  package_json: &Path,
+  event: &str,
+  body: &str,
+) -> BTreeMap<String, String> {
+  let mut env = BTreeMap::new();
+  let manifest: Option<serde_json::Value> =
+    std::fs::read_to_string(package_json)
+      .ok()
+      .and_then(|content| serde_json::from_str(&content).ok());
+  for key in ["name", "version"] {
+    if let Some(value) = manifest
+      .as_ref()
+      .and_then(|m| m.get(key))
+      .and_then(|v| v.as_str())
+    {
+      env.insert(format!("npm_package_{key}"), value.to_string());
+    }
+  }
+  env.insert(
+    "npm_package_json".into(),
+    package_json.to_string_lossy().into_owned(),
+  );
+  env.insert("npm_lifecycle_event".into(), event.to_string());
+  env.insert("npm_lifecycle_script".into(), body.to_string());
+
+  let bins = package_json
+    .parent()
+    .into_iter()
+    .flat_map(Path::ancestors)
+    .map(|dir| dir.join("node_modules").join(".bin"))
+    .filter(|dir| dir.is_dir());
+  let path = env::var_os("PATH").unwrap_or_default();
+  if let Ok(path) = env::join_paths(bins.chain(env::split_paths(&path))) {
+    env.insert("PATH".into(), path.to_string_lossy().into_owned());
+  }
+  env
+}
@@ -0,0 +1,319 @@
+//! Ref updates passed to hooks on standard input.
+//!
+//! `pre-push` receives one `<local ref> <local oid> <remote ref> <remote oid>`
+//! line per ref being pushed, while `pre-receive`, `post-receive` and
+//! `reference-transaction` receive `<old oid> <new oid> <ref>` lines. `huk
+//! run` reads these lines once, works out the commits and files each update
+//! introduces, and hands the original input on to every command it spawns.
+//! Tasks see the updates through `HUK_*` environment variables and the
+//! `{push_range}` and `{changed_files}` placeholders, and `when` conditions
+//! on task objects are checked against them.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::git::git_output;
+use crate::shell;
+use crate::task::Condition;
+
+/// Hooks Git writes to the standard input of. All but `post-rewrite`, whose
+/// input lists rewritten commits, receive a list of ref updates.
+pub const STDIN_HOOKS: [&str; 5] = [
+  "pre-push",
+  "pre-receive",
+  "post-receive",
+  "reference-transaction",
+  "post-rewrite",
+];
+
+/// A single ref update read from a hook's standard input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefUpdate {
+  /// The ref being updated: the remote ref for `pre-push`, and the ref in
+  /// this repository for the receiving hooks.
+  pub name:          String,
+  /// The local ref being pushed (`pre-push` only).
+  pub local_ref:     Option<String>,
+  /// Object id before the update, all zeros when the ref is created.
+  pub old:           String,
+  /// Object id after the update, all zeros when the ref is deleted.
+  pub new:           String,
+  /// Revision range of the commits the update introduces, such as
+  /// `a1b2..c3d4`, when known. Empty for deletions.
+  pub range:         Option<String>,
+  /// Files added or modified by those commits. Renames are not detected, so
+  /// a renamed file is listed under its new name only.
+  pub changed_files: Vec<String>,
+}
+
+impl RefUpdate {
+  /// Whether the update deletes the ref.
+  pub fn is_deletion(&self) -> bool {
+    is_null(&self.new)
+  }
+
+  /// Whether the update creates the ref.
+  pub fn is_creation(&self) -> bool {
+    is_null(&self.old)
+  }
+
+  /// The branch name, for refs under `refs/heads/`.
+  pub fn branch(&self) -> Option<&str> {
+    self.name.strip_prefix("refs/heads/")
+  }
+}
+
+/// Whether `oid` is Git's all-zeros null object id.
+fn is_null(oid: &str) -> bool {
+  !oid.is_empty() && oid.bytes().all(|b| b == b'0')
+}
+
+/// The ref updates of one hook invocation, along with the raw input they
+/// were parsed from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RefInput {
+  /// Whether the hook is one of the [`STDIN_HOOKS`] that list ref updates.
+  /// Conditions of other hooks are checked against the current branch.
+  pub listed:  bool,
+  /// Standard input exactly as Git wrote it.
+  pub raw:     Vec<u8>,
+  pub updates: Vec<RefUpdate>,
+}
+
+impl RefInput {
+  /// Parse the standard input of `hook`. Malformed lines are skipped.
+  pub fn parse(hook: &str, raw: Vec<u8>) -> Self {
+    let text = String::from_utf8_lossy(&raw);
+    let updates = text
+      .lines()
+      .filter_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match (hook, fields.as_slice()) {
+          ("pre-push", &[local_ref, new, name, old]) => Some(RefUpdate {
+            name:          name.to_string(),
+            local_ref:     Some(local_ref.to_string()),
+            old:           old.to_string(),
+            new:           new.to_string(),
+            range:         None,
+            changed_files: Vec::new(),
+          }),
+          ("pre-push" | "post-rewrite", _) => None,
+          (_, &[old, new, name]) => Some(RefUpdate {
+            name:          name.to_string(),
+            local_ref:     None,
+            old:           old.to_string(),
+            new:           new.to_string(),
+            range:         None,
+            changed_files: Vec::new(),
+          }),
+          _ => None,
+        }
+      })
+      .collect();
+    Self {
+      listed: STDIN_HOOKS.contains(&hook) && hook != "post-rewrite",
+      raw,
+      updates,
+    }
+  }
+
+  /// The update of the `update` hook, which Git passes as the arguments
+  /// `<ref> <old oid> <new oid>` instead of on standard input.
+  pub fn from_update_args(args: &[String]) -> Self {
+    let line = match args {
+      [name, old, new] => format!("{old} {new} {name}\n"),
+      _ => String::new(),
+    };
+    Self {
+      listed: true,
+      raw: Vec::new(),
+      ..Self::parse("update", line.into_bytes())
+    }
+  }
+
+  /// Fill in the range and changed files of every update from the
+  /// repository in `cwd`.
+  pub fn resolve(&mut self, hook: &str, cwd: &Path) {
+    for update in &mut self.updates {
+      update.range = commit_range(hook, cwd, update);
+      update.changed_files = update
+        .range
+        .as_deref()
+        .map(|range| changed_files(cwd, range))
+        .unwrap_or_default();
+    }
+  }
+
+  /// The updates satisfying `condition`, or `None` if there are none. For
+  /// hooks that are not given ref updates, `current_branch` is checked
+  /// instead.
+  pub fn filter(
+    &self,
+    condition: &Condition,
+    current_branch: Option<&str>,
+  ) -> Option<Self> {
+    if !self.listed {
+      let branch_ok = condition.branches.is_empty()
+        || current_branch.is_some_and(|b| condition.matches_branch(b));
+      return (branch_ok && condition.deleted != Some(true))
+        .then(|| self.clone());
+    }
+    let updates: Vec<RefUpdate> = self
+      .updates
+      .iter()
+      .filter(|u| condition.deleted.is_none_or(|d| d == u.is_deletion()))
+      .filter(|u| {
+        condition.branches.is_empty()
+          || u.branch().is_some_and(|b| condition.matches_branch(b))
+      })
+      .cloned()
+      .collect();
+    (!updates.is_empty()).then(|| Self {
+      updates,
+      ..self.clone()
+    })
+  }
+
+  /// The ranges of all updates, separated by spaces.
+  pub fn push_range(&self) -> String {
+    let ranges: Vec<&str> = self
+      .updates
+      .iter()
+      .filter_map(|u| u.range.as_deref())
+      .collect();
+    ranges.join(" ")
+  }
+
+  /// The files changed by any of the updates, sorted and without duplicates.
+  pub fn changed_files(&self) -> Vec<&str> {
+    let files: BTreeSet<&str> = self
+      .updates
+      .iter()
+      .flat_map(|u| u.changed_files.iter().map(String::as_str))
+      .collect();
+    files.into_iter().collect()
+  }
+
+  /// Environment variables describing the updates:
+  ///
+  /// - `HUK_REFS`: one `<ref> <old oid> <new oid>` line per update.
+  /// - `HUK_PUSH_RANGE`: the revision ranges, separated by spaces.
+  /// - `HUK_CHANGED_FILES`: the changed files, one per line.
+  pub fn env(&self) -> BTreeMap<String, String> {
+    let refs: Vec<String> = self
+      .updates
+      .iter()
+      .map(|u| format!("{} {} {}", u.name, u.old, u.new))
+      .collect();
+    BTreeMap::from([
+      ("HUK_REFS".to_string(), refs.join("\n")),
+      ("HUK_PUSH_RANGE".to_string(), self.push_range()),
+      (
+        "HUK_CHANGED_FILES".to_string(),
+        self.changed_files().join("\n"),
+      ),
+    ])
+  }
+
+  /// Replace the `{push_range}` and `{changed_files}` placeholders in
+  /// `command` with the shell-quoted ranges and file names. Returns `None`
+  /// when `command` uses a placeholder there is nothing to substitute for,
+  /// as outside the hooks that receive refs or when no update adds commits,
+  /// since running the command without its operands would act on something
+  /// else entirely.
+  pub fn expand(&self, command: &str) -> Option<String> {
+    let mut command = command.to_string();
+    if command.contains("{push_range}") {
+      let ranges = self.push_range();
+      if ranges.is_empty() {
+        return None;
+      }
+      let ranges = shell::join(ranges.split_whitespace());
+      command = command.replace("{push_range}", &ranges);
+    }
+    if command.contains("{changed_files}") {
+      let files = self.changed_files();
+      if files.is_empty() {
+        return None;
+      }
+      command = command.replace("{changed_files}", &shell::join(files));
+    }
+    Some(command)
+  }
+}
+
+/// The revision range of the commits `update` introduces. Updates of an
+/// existing ref give `<old>..<new>`. For new refs (or an old commit that is
+/// not available locally) the range starts at the parent of the oldest
+/// commit not yet reachable from another ref: a remote-tracking ref for
+/// `pre-push`, or any ref but the one being updated for the receiving hooks.
+fn commit_range(hook: &str, cwd: &Path, update: &RefUpdate) -> Option<String> {
+  if update.is_deletion() {
+    return None;
+  }
+  let new = &update.new;
+  if !update.is_creation()
+    && git_output(
+      cwd,
+      &["cat-file", "-e", &format!("{}^{{commit}}", update.old)],
+    )
+    .is_some()
+  {
+    return Some(format!("{}..{new}", update.old));
+  }
+  let exclude = format!("--exclude={}", update.name);
+  let others: &[&str] = if hook == "pre-push" {
+    &["--remotes"]
+  } else {
+    &[&exclude, "--all"]
+  };
+  let mut args = vec!["rev-list", "--reverse", new.as_str(), "--not"];
+  args.extend(others);
+  let commits = git_output(cwd, &args)?;
+  let oldest = commits.lines().next()?;
+  let parent = format!("{oldest}^");
+  Some(
+    match git_output(cwd, &["rev-parse", "--verify", "-q", &parent]) {
+      Some(base) => format!("{base}..{new}"),
+      None => new.clone(),
+    },
+  )
+}
+
+/// Files added or modified in `range`, which is either `<base>..<tip>` or a
+/// single root commit whose whole tree counts. Renames are not detected, so
+/// a renamed file counts as added under its new name.
+fn changed_files(cwd: &Path, range: &str) -> Vec<String> {
+  let listing = match range.split_once("..") {
+    Some((base, tip)) => git_output(
+      cwd,
+      &[
+        "-c",
+        "core.quotePath=false",
+        "diff",
+        "--name-only",
+        "--no-renames",
+        "--diff-filter=ACMR",
+        base,
+        tip,
+      ],
+    ),
+    None => git_output(
+      cwd,
+      &[
+        "-c",
+        "core.quotePath=false",
+        "ls-tree",
+        "-r",
+        "--name-only",
+        range,
+      ],
+    ),
+  };
+  listing
+    .unwrap_or_default()
+    .lines()
+    .map(String::from)
+    .collect()
+}
@@ -0,0 +1,413 @@
+//! Timing summaries and machine-readable run reports.
+//!
+//! After a hook finishes, [`format_summary`] renders the table printed by
+//! `huk run`: each task, how it was resolved, its wall time and its status,
+//! with tasks slower than the configured `warn_after` threshold highlighted.
+//! The same data can be written to a file for CI dashboards with `huk run
+//! --report json|junit`. [`format_plan`] renders the output of `huk run
+//! --dry-run`.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use clap::ValueEnum;
+use serde_json::Value;
+use serde_json::json;
+
+use crate::history::format_duration;
+use crate::runner::PlanStep;
+use crate::runner::TaskEntry;
+use crate::runner::TaskKind;
+use crate::runner::TaskRun;
+use crate::shell;
+
+/// File formats supported by `huk run --report`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+  /// A JSON document describing the hook and each task.
+  Json,
+  /// A JUnit XML test suite with one test case per task.
+  Junit,
+}
+
+impl ReportFormat {
+  /// File name used when `--report-path` is not given.
+  pub const fn default_path(self) -> &'static str {
+    match self {
+      ReportFormat::Json => "huk-report.json",
+      ReportFormat::Junit => "huk-report.xml",
+    }
+  }
+}
+
+/// Aggregate timing data for one hook run.
+#[derive(Debug, Clone, Copy)]
+pub struct RunSummary<'a> {
+  /// Name of the hook that was run.
+  pub hook:       &'a str,
+  /// Every task executed or skipped, in order.
+  pub runs:       &'a [TaskRun],
+  /// Total wall-clock time of the hook.
+  pub total:      Duration,
+  /// Threshold above which a task is considered slow.
+  pub warn_after: Option<Duration>,
+}
+
+impl RunSummary<'_> {
+  fn is_slow(&self, run: &TaskRun) -> bool {
+    self
+      .warn_after
+      .is_some_and(|limit| run.duration_ms > limit.as_millis() as u64)
+  }
+
+  fn success(&self) -> bool {
+    self.runs.iter().all(|r| r.success)
+  }
+}
+
+/// Render the summary table. ANSI colors are only emitted when `color` is
+/// set; slow tasks are additionally marked `(slow)` so they stand out in
+/// plain output too.
+pub fn format_summary(summary: &RunSummary<'_>, color: bool) -> String {
+  let paint = |code: &str, text: &str| {
+    if color {
+      format!("\x1b[{code}m{text}\x1b[0m")
+    } else {
+      text.to_string()
+    }
+  };
+  const MAX_NAME: usize = 40;
+  let label = |run: &TaskRun| {
+    let name = run.name.replace('\n', " ");
+    let name = if name.chars().count() > MAX_NAME {
+      let cut: String = name.chars().take(MAX_NAME - 1).collect();
+      format!("{cut}…")
+    } else {
+      name
+    };
+    format!("{}{name}", "  ".repeat(run.depth))
+  };
+  let width = summary
+    .runs
+    .iter()
+    .map(|r| label(r).chars().count())
+    .chain(["TASK".len()])
+    .max()
+    .unwrap_or(0);
+
+  let mut out = String::new();
+  out.push_str(&paint("1", &format!("Summary for '{}':", summary.hook)));
+  out.push('\n');
+  out.push_str(&paint(
+    "2",
+    &format!(
+      "  {:<width$}  {:<11}  {:>8}  STATUS",
+      "TASK", "KIND", "TIME"
+    ),
+  ));
+  out.push('\n');
+  for run in summary.runs {
+    let slow = summary.is_slow(run);
+    let name = format!("{:<width$}", label(run));
+    let time = format!("{:>8}", format_duration(run.duration_ms));
+    let status = match (run.cached, run.success) {
+      (true, _) => paint("2", "cached"),
+      (false, true) => paint("32", "ok"),
+      (false, false) => paint("31", "failed"),
+    };
+    let (name, time) = if slow {
+      (paint("1;33", &name), paint("1;33", &time))
+    } else {
+      (name, time)
+    };
+    let marker = if slow {
+      format!(" {}", paint("33", "(slow)"))
+    } else {
+      String::new()
+    };
+    out.push_str(&format!(
+      "  {name}  {:<11}  {time}  {status}{marker}\n",
+      run.kind.to_string()
+    ));
+  }
+  out.push_str(&format!(
+    "  {:<width$}  {:<11}  {:>8}\n",
+    "total",
+    "",
+    format_duration(summary.total.as_millis() as u64)
+  ));
+  out
+}
+
+fn status_str(run: &TaskRun) -> &'static str {
+  match (run.cached, run.success) {
+    (true, _) => "cached",
+    (false, true) => "ok",
+    (false, false) => "failed",
+  }
+}
+
+/// Build the JSON report document.
+pub fn to_json(summary: &RunSummary<'_>) -> Value {
+  let tasks: Vec<Value> = summary
+    .runs
+    .iter()
+    .map(|run| {
+      json!({
+        "name": run.name,
+        "kind": run.kind,
+        "command": run.command,
+        "depth": run.depth,
+        "duration_ms": run.duration_ms,
+        "status": status_str(run),
+        "exit_code": run.exit_code,
+        "slow": summary.is_slow(run),
+      })
+    })
+    .collect();
+  json!({
+    "hook": summary.hook,
+    "success": summary.success(),
+    "duration_ms": summary.total.as_millis() as u64,
+    "warn_after_ms": summary.warn_after.map(|d| d.as_millis() as u64),
+    "tasks": tasks,
+  })
+}
+
+/// Escape text for use in XML attribute values and character data.
+fn xml_escape(s: &str) -> String {
+  let mut out = String::with_capacity(s.len());
+  for c in s.chars() {
+    match c {
+      '&' => out.push_str("&amp;"),
+      '<' => out.push_str("&lt;"),
+      '>' => out.push_str("&gt;"),
+      '"' => out.push_str("&quot;"),
+      '\'' => out.push_str("&apos;"),
+      // Control characters other than tab/newline are not allowed in XML 1.0.
+      c if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r') => {}
+      c => out.push(c),
+    }
+  }
+  out
+}
+
+fn secs(ms: u64) -> String {
+  format!("{:.3}", ms as f64 / 1_000.0)
+}
+
+/// Build the JUnit XML report: one `<testsuite>` for the hook and one
+/// `<testcase>` per task, with failed tasks carrying a `<failure>` element
+/// that includes any captured output.
+pub fn to_junit(summary: &RunSummary<'_>) -> String {
+  // A nested hook only groups the runs after it, which are reported on their
+  // own; counting it too would report one failure twice.
+  let runs: Vec<&TaskRun> = summary
+    .runs
+    .iter()
+    .filter(|r| r.kind != TaskKind::Hook)
+    .collect();
+  let failures = runs.iter().filter(|r| !r.success).count();
+  let total_ms = summary.total.as_millis() as u64;
+  let hook = xml_escape(summary.hook);
+  let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+  out.push_str(&format!(
+    "<testsuites name=\"huk\" tests=\"{n}\" failures=\"{failures}\" \
+     time=\"{t}\">\n  <testsuite name=\"{hook}\" tests=\"{n}\" \
+     failures=\"{failures}\" time=\"{t}\">\n",
+    n = runs.len(),
+    t = secs(total_ms),
+  ));
+  for run in runs {
+    out.push_str(&format!(
+      "    <testcase name=\"{}\" classname=\"{hook}.{}\" time=\"{}\"",
+      xml_escape(&run.name),
+      run.kind.to_string().replace(' ', "-"),
+      secs(run.duration_ms),
+    ));
+    if run.success && !run.cached && run.output.is_empty() {
+      out.push_str("/>\n");
+      continue;
+    }
+    out.push_str(">\n");
+    if !run.success {
+      let message = match run.exit_code {
+        Some(code) => format!("exited with status {code}"),
+        None => "terminated by signal".to_string(),
+      };
+      out.push_str(&format!(
+        "      <failure message=\"{}\">{}</failure>\n",
+        xml_escape(&message),
+        xml_escape(&run.command),
+      ));
+    }
+    if run.cached {
+      out.push_str("      <system-out>cached</system-out>\n");
+    } else if !run.output.is_empty() {
+      out.push_str(&format!(
+        "      <system-out>{}</system-out>\n",
+        xml_escape(&run.output)
+      ));
+    }
+    out.push_str("    </testcase>\n");
+  }
+  out.push_str("  </testsuite>\n</testsuites>\n");
+  out
+}
+
+/// Write a report in the requested format to `path`.
+pub fn write_report(
+  format: ReportFormat,
+  path: &Path,
+  summary: &RunSummary<'_>,
+) -> io::Result<()> {
+  let content = match format {
+    ReportFormat::Json => {
+      let mut s = serde_json::to_string_pretty(&to_json(summary))?;
+      s.push('\n');
+      s
+    }
+    ReportFormat::Junit => to_junit(summary),
+  };
+  if let Some(parent) = path.parent()
+    && !parent.as_os_str().is_empty()
+  {
+    fs::create_dir_all(parent)?;
+  }
+  fs::write(path, content)
+}
+
+/// Render the plan recorded by a dry run: one entry per step, indented by
+/// nesting level, with its command, working directory and environment.
+/// Steps that fell through to the raw-command fallback are marked
+/// `unresolved`.
+pub fn format_plan(hook: &str, steps: &[PlanStep], color: bool) -> String {
+  let paint = |code: &str, text: &str| {
+    if color {
+      format!("\x1b[{code}m{text}\x1b[0m")
+    } else {
+      text.to_string()
+    }
+  };
+  let mut out = String::new();
+  out.push_str(&paint("1", &format!("Plan for '{hook}':")));
+  out.push('\n');
+  if steps.is_empty() {
+    out.push_str("  (nothing to run)\n");
+  }
+  for step in steps {
+    let indent = "  ".repeat(step.depth + 1);
+    let mut tags = vec![step.kind.to_string()];
+    if step.cached {
+      tags.push("cached".into());
+    }
+    let tags = paint("2", &format!("({})", tags.join(", ")));
+    let mut line = format!("{indent}{} {tags}", step.name.replace('\n', " "));
+    if step.fallback {
+      line.push(' ');
+      line.push_str(&paint("33", "unresolved"));
+    }
+    out.push_str(&line);
+    out.push('\n');
+    if let Some(command) = &step.command {
+      out.push_str(&format!("{indent}  $ {command}\n"));
+      out.push_str(&format!("{indent}  cwd: {}\n", step.cwd.display()));
+    }
+    if !step.env.is_empty() {
+      let env = step
+        .env
+        .iter()
+        .map(|(k, v)| format!("{k}={}", shell::quote(v)));
+      out.push_str(&format!(
+        "{indent}  env: {}\n",
+        env.collect::<Vec<_>>().join(" ")
+      ));
+    }
+  }
+  out
+}
+
+/// Render tasks as dependency trees for `huk tasks --tree`. Tasks that no
+/// other task depends on are the roots, followed by any task only reachable
+/// through a cycle; dependencies that are not defined, or that would close a
+/// cycle, are marked instead of being expanded.
+pub fn format_task_tree(tasks: &[TaskEntry], color: bool) -> String {
+  struct Tree<'a> {
+    tasks:   &'a [TaskEntry],
+    color:   bool,
+    stack:   Vec<&'a str>,
+    visited: Vec<&'a str>,
+    out:     String,
+  }
+
+  impl<'a> Tree<'a> {
+    fn paint(&self, code: &str, text: &str) -> String {
+      if self.color {
+        format!("\x1b[{code}m{text}\x1b[0m")
+      } else {
+        text.to_string()
+      }
+    }
+
+    fn root(&mut self, task: &'a TaskEntry) {
+      let mut line = self.paint("1;36", &task.name);
+      if let Some(description) = &task.description {
+        line.push(' ');
+        line.push_str(&self.paint("2", &format!("- {description}")));
+      }
+      self.out.push_str(&line);
+      self.out.push('\n');
+      self.branch(task, "");
+    }
+
+    fn branch(&mut self, task: &'a TaskEntry, prefix: &str) {
+      self.stack.push(&task.name);
+      self.visited.push(&task.name);
+      for (i, dep) in task.dependencies.iter().enumerate() {
+        let (connector, indent) = if i + 1 == task.dependencies.len() {
+          ("└── ", "    ")
+        } else {
+          ("├── ", "│   ")
+        };
+        let mut line = format!("{prefix}{}{dep}", self.paint("2", connector));
+        let found = self.tasks.iter().find(|t| t.name == *dep);
+        match found {
+          _ if self.stack.contains(&dep.as_str()) => {
+            line.push_str(&format!(" {}", self.paint("31", "(circular)")));
+          }
+          None => line.push_str(&format!(" {}", self.paint("31", "(missing)"))),
+          Some(_) => {}
+        }
+        self.out.push_str(&line);
+        self.out.push('\n');
+        if let Some(dep) =
+          found.filter(|t| !self.stack.contains(&t.name.as_str()))
+        {
+          self.branch(dep, &format!("{prefix}{indent}"));
+        }
+      }
+      self.stack.pop();
+    }
+  }
+
+  let mut tree = Tree {
+    tasks,
+    color,
+    stack: Vec::new(),
+    visited: Vec::new(),
+    out: String::new(),
+  };
+  for task in tasks {
+    if !tasks.iter().any(|t| t.dependencies.contains(&task.name)) {
+      tree.root(task);
+    }
+  }
+  for task in tasks {
+    if !tree.visited.contains(&task.name.as_str()) {
+      tree.root(task);
+    }
+  }
+  tree.out
+}
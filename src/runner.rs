@@ -5,19 +5,40 @@
 //! configuration loading to [`crate::config`] and executes commands via
 //! [`std::process::Command`].
 
+use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::io;
+use std::io::IsTerminal;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
 use std::process::Command;
 use std::process::ExitStatus;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
 
+use ::derive_more::Display;
 use ::derive_more::IsVariant;
 use ::serde_json::json;
-use moos::CowStr;
+use serde::Deserialize;
 use serde::Serialize;
 use serde_json::Value;
 use thiserror::Error;
 
 use crate::GIT_HOOKS;
+use crate::builtins;
+use crate::builtins::BuiltinError;
+use crate::cache;
+use crate::cache::CacheError;
+use crate::cache::CacheInputs;
+use crate::cache::CacheStore;
 use crate::cli::AddOpts;
 use crate::cli::ListOpts;
 use crate::cli::RemoveOpts;
@@ -25,6 +46,22 @@ use crate::cli::RunOpts;
 use crate::cli::TaskOpts;
 use crate::cli::UpdateOpts;
 use crate::config::*;
+use crate::git::git_output;
+use crate::history;
+use crate::install;
+use crate::package_manager;
+use crate::package_manager::PackageManager;
+use crate::package_manager::PackageManagerError;
+use crate::refs;
+use crate::refs::RefInput;
+use crate::report;
+use crate::report::RunSummary;
+use crate::server;
+use crate::server::ServerError;
+use crate::shell;
+use crate::shell::SplitError;
+use crate::task::TaskObject;
+use crate::task::TaskRef;
 use crate::task::TaskSpec;
 use crate::task::TaskSpecParseError;
 
@@ -60,6 +97,27 @@ pub enum RunnerError {
     "configuration file '{0}' is not a JSON object; unable to modify hooks"
   )]
   InvalidConfigShape(String),
+  /// `huk history show` was given an id that is not in the history.
+  #[error("no run with id {0} in the history")]
+  UnknownRun(u64),
+  /// A task's cache key could not be computed.
+  #[error(transparent)]
+  Cache(#[from] CacheError),
+  /// A command could not be split into words for direct execution.
+  #[error(transparent)]
+  ShellSplit(#[from] SplitError),
+  /// The package manager for Node scripts is missing or the wrong version.
+  #[error(transparent)]
+  PackageManager(#[from] PackageManagerError),
+  /// A built-in task could not run.
+  #[error(transparent)]
+  Builtin(#[from] BuiltinError),
+  /// A server-side hook could not be prepared.
+  #[error(transparent)]
+  Server(#[from] ServerError),
+  /// A built-in task ran and its check failed.
+  #[error("built-in task 'huk:{0}' failed")]
+  BuiltinFailure(String),
 }
 
 /// Handler for the `list` subcommand.
@@ -89,7 +147,12 @@ pub fn handle_list(opts: &ListOpts) -> Result<(), RunnerError> {
     #[serde(untagged)]
     enum HookEntry {
       Name(String),
-      Full { name: String, spec: Option<Value> },
+      Full {
+        name:       String,
+        spec:       Option<Value>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        resolution: Option<Value>,
+      },
     }
 
     let mut hooks: Vec<HookEntry>;
@@ -110,12 +173,17 @@ pub fn handle_list(opts: &ListOpts) -> Result<(), RunnerError> {
       hooks = hooks_sorted
         .iter()
         .map(|(hook, spec)| HookEntry::Full {
-          name: hook.to_string(),
-          spec: if opts.name_only {
+          name:       hook.to_string(),
+          spec:       if opts.name_only {
             None
           } else {
             Some(spec.to_json())
           },
+          resolution: if opts.name_only {
+            None
+          } else {
+            resolution_json(&cfg, spec)
+          },
         })
         .collect();
     }
@@ -178,12 +246,10 @@ pub fn handle_list(opts: &ListOpts) -> Result<(), RunnerError> {
     eprintln!("Discovered {n} hook{s} in '{path}':");
     eprintln!();
   }
-  let mut i = 0;
-  for (hook, spec) in hooks_sorted {
+  for (i, (hook, spec)) in hooks_sorted.into_iter().enumerate() {
     if i != 0 && !opts.all && !opts.compact && !opts.name_only {
       eprintln!();
     }
-    i += 1;
     if opts.name_only || opts.all {
       println!("- {hook}");
       continue;
@@ -199,90 +265,208 @@ pub fn handle_list(opts: &ListOpts) -> Result<(), RunnerError> {
         reset = "\x1b[0m"
       );
 
-      let spec_str = spec.to_string();
-      let info: String = spec_str.replace('\n', "\n  ");
-
-      // if each line of info starts with a number and colon, e.g. `1: ...\n2:
-      // ...\n`, we want to indent accordingly and colorize the numbers to
-      // be dimmer than the actual command text.
-      if info.lines().all(|line| {
-        line
-          .trim_start()
-          .chars()
-          .next()
-          .is_some_and(|c| c.is_ascii_digit() || c == '-')
-      }) {
-        let mut lines: Vec<&str> = info.lines().collect::<Vec<&str>>();
-        let tmp = lines.drain(..);
-        for line in tmp {
-          let trimmed: &str = line.trim_start();
-          let (num_part, rest): (&str, &str) =
-            trimmed.split_at(trimmed.find(' ').unwrap_or(trimmed.len()));
-          let num_part = num_part.trim_end_matches(':').trim_end_matches('.');
-          let num_part = format!("{num_part}.");
-
-          // in the rest part, attempt to resolve the task name to its
-          // configured command, printing them on the same line as the
-          // number, but dimming the number part and emboldening/
-          // underlining the task name. for tasks that appear to be a shell
-          // script/command themselves, just print them as is.
-          let rest = rest.trim().to_string();
-          let info = if cfg.node_scripts.contains_key(&rest)
-            || cfg.deno_tasks.contains_key(&rest)
-          {
-            let (_kind, cmd): (CowStr, CowStr) =
-              if let Some(script) = cfg.node_scripts.get(&rest) {
-                (CowStr::from("script"), CowStr::from(script.as_str()))
-              } else if let Some(script) = cfg.deno_tasks.get(&rest) {
-                (CowStr::from("task"), CowStr::from(script.as_str()))
-              } else {
-                (CowStr::from("unknown"), "<unknown>".into())
-              };
-            let named = rest.clone();
-            let cmd = cmd.replace('\n', " ");
-            format!(
-              r#"{bun}{magenta}{named}{reset}{indent}{cmd}{reset}"#,
-              bun = "\x1b[1;4m",
-              magenta = "\x1b[35m",
-              reset = "\x1b[0m",
-              indent = "\n     \x1b[2m↪︎\x1b[0m \x1b[3m"
-            )
-          } else {
-            rest
-          };
-
-          eprintln!(
-            r#"  {dim}{num_part}{reset} {info}"#,
-            dim = "\x1b[2m",
-            reset = "\x1b[0m"
-          );
+      match spec {
+        TaskSpec::Single(reference) => {
+          eprintln!("  {}", format_reference(&cfg, reference));
+        }
+        // Number each entry of a sequence, dimming the numbers so that the
+        // task names and commands stand out.
+        TaskSpec::Sequence(list) => {
+          for (i, item) in list.iter().enumerate() {
+            let info = match item {
+              TaskSpec::Single(reference) => format_reference(&cfg, reference),
+              other => other.to_string().replace('\n', "\n     "),
+            };
+            eprintln!(
+              r#"  {dim}{n}.{reset} {info}"#,
+              n = i + 1,
+              dim = "\x1b[2m",
+              reset = "\x1b[0m"
+            );
+          }
+        }
+        TaskSpec::Detailed { .. } => {
+          eprintln!("  {}", spec.to_string().replace('\n', "\n  "));
         }
-        continue;
       }
-      eprintln!("  {info}");
       continue;
     }
   }
   Ok(())
 }
 
+/// Describe how the bare entries of a hook specification resolve, for the
+/// machine-readable `huk list` output: an object for a single entry, an array
+/// (with `null` for task objects) for a sequence, and `None` for a task
+/// object.
+fn resolution_json(cfg: &HookConfig, spec: &TaskSpec) -> Option<Value> {
+  match spec {
+    TaskSpec::Single(reference) => Some(match resolve(cfg, reference) {
+      Ok((resolution, args)) => {
+        let mut value = json!(resolution);
+        if !args.is_empty() {
+          value["args"] = json!(args);
+        }
+        value
+      }
+      Err(err) => json!({"kind": "error", "error": err.to_string()}),
+    }),
+    TaskSpec::Detailed { .. } => None,
+    TaskSpec::Sequence(list) => Some(Value::Array(
+      list
+        .iter()
+        .map(|item| resolution_json(cfg, item).unwrap_or(Value::Null))
+        .collect(),
+    )),
+  }
+}
+
+/// Render a task reference for `huk list`: the reference itself, followed
+/// by what it resolves to. Shell commands are shown as written; references
+/// to tasks and scripts also show their command.
+fn format_reference(cfg: &HookConfig, reference: &str) -> String {
+  const RESET: &str = "\x1b[0m";
+  let arrow = "\n     \x1b[2m↪︎\x1b[0m";
+  match resolve(cfg, reference).map(|(resolution, _)| resolution) {
+    Ok(Resolution::Command(_)) => reference.to_string(),
+    Ok(resolution @ Resolution::Fallback(_)) => {
+      format!("{reference}{arrow} \x1b[33m{resolution}{RESET}")
+    }
+    Ok(resolution) => {
+      let detail = match &resolution {
+        Resolution::HukTask(name) => Some(cfg.huk_tasks[name].summary()),
+        Resolution::DenoTask(name) => Some(cfg.deno_tasks[name].summary()),
+        Resolution::NodeScript(name) => Some(cfg.node_scripts[name].clone()),
+        _ => None,
+      }
+      .map(|cmd| format!(" \x1b[3m{}", cmd.replace('\n', " ")))
+      .unwrap_or_default();
+      format!(
+        "\x1b[1;4;35m{reference}{RESET}{arrow} \x1b[2m{resolution}{RESET}\
+         {detail}{RESET}"
+      )
+    }
+    Err(err) => format!("{reference}{arrow} \x1b[31m{err}{RESET}"),
+  }
+}
+
 /// Handler for the `run` subcommand.
 pub fn handle_run(opts: &RunOpts) -> Result<(), RunnerError> {
-  let cfg = HookConfig::discover(&std::env::current_dir()?)?;
+  let cwd = std::env::current_dir()?;
+  // Check the hook name before consuming standard input or checking out
+  // anything for it.
   if opts.hook.is_empty() {
     eprintln!("Please specify a valid hook name.");
-    if opts.verbose && !cfg.hooks.is_empty() {
+    if opts.verbose
+      && let Ok(cfg) = HookConfig::discover(&cwd)
+      && !cfg.hooks.is_empty()
+    {
       crate::print_available_hooks!(&cfg);
     }
     return Err(ConfigError::UnknownHook(opts.hook.clone()).into());
   }
-  if opts.hook.is_empty() || !GIT_HOOKS.contains(&&*opts.hook) {
+  if !GIT_HOOKS.contains(&&*opts.hook) {
     return Err(ConfigError::UnknownHook(opts.hook.clone()).into());
   }
-  if let Some(spec) = cfg.hooks.get(&opts.hook) {
-    let mut runner = TaskRunner::new(&cfg);
-    runner.run_spec(spec, &opts.hook, &opts.args)?;
+  let ref_input = read_ref_input(&opts.hook, &opts.args, &cwd)?;
+  // Bare repositories run server-side hooks in a checkout of the pushed
+  // tree, with the configuration read from the repository itself.
+  let server = if server::is_bare(&cwd) {
+    Some(server::prepare(&cwd, &ref_input)?)
   } else {
+    None
+  };
+  let discovered;
+  let cfg = match &server {
+    Some(hook) => &hook.config,
+    None => {
+      discovered = HookConfig::discover(&cwd)?;
+      &discovered
+    }
+  };
+  // A checkout, merge or rebase may have changed which hooks are
+  // configured.
+  let sync_hook =
+    server.is_none() && install::SYNC_HOOKS.contains(&&*opts.hook);
+  let synced = sync_hook && !opts.dry_run && {
+    match install::sync(&cwd, cfg) {
+      Ok(synced) => synced,
+      Err(err) => {
+        eprintln!("warning: could not reinstall the hooks: {err}");
+        false
+      }
+    }
+  };
+  if synced {
+    eprintln!("huk: the configuration changed; hooks reinstalled");
+  }
+  validate_hook(cfg, &opts.hook)?;
+  if let Some(spec) = cfg.hooks.get(&opts.hook)
+    && opts.dry_run
+  {
+    let mut runner = TaskRunner::new_dry_run(cfg);
+    runner.refs = ref_input;
+    if let Some(hook) = &server {
+      hook.configure(&mut runner);
+    }
+    if opts.no_cache {
+      runner.cache = None;
+    }
+    runner.run_spec(spec, &opts.hook, &opts.args)?;
+    let plan = runner.take_plan();
+    let color = io::stdout().is_terminal();
+    print!("{}", report::format_plan(&opts.hook, &plan, color));
+    for step in plan.iter().filter(|step| step.fallback) {
+      let hint = closest_task_name(cfg, &step.name)
+        .map(|name| format!(" (did you mean '{name}'?)"))
+        .unwrap_or_default();
+      eprintln!(
+        "warning: '{}' is not a task, script or hook and will run as a shell \
+         command{hint}",
+        step.name
+      );
+    }
+  } else if let Some(spec) = cfg.hooks.get(&opts.hook) {
+    let mut runner = TaskRunner::new(cfg);
+    runner.refs = ref_input;
+    if let Some(hook) = &server {
+      hook.configure(&mut runner);
+    }
+    if opts.no_cache {
+      runner.cache = None;
+    }
+    // The checkout of a server-side hook is not a repository to keep
+    // history in.
+    let record = cfg.settings.history && server.is_none();
+    runner.tee_output = record && cfg.settings.history_output;
+    let started = SystemTime::now();
+    let timer = Instant::now();
+    let result = runner.run_spec(spec, &opts.hook, &opts.args);
+    let elapsed = timer.elapsed();
+    if cfg.settings.summary {
+      runner.print_summary(&opts.hook, elapsed);
+    }
+    let reported = opts.report.map(|format| {
+      let path = opts
+        .report_path
+        .clone()
+        .unwrap_or_else(|| format.default_path().into());
+      report::write_report(format, &path, &runner.summary(&opts.hook, elapsed))
+    });
+    if record {
+      history::record_run(
+        cfg,
+        &opts.hook,
+        &opts.args,
+        started,
+        elapsed,
+        std::mem::take(&mut runner.runs),
+        &result,
+      );
+    }
+    result?;
+    reported.transpose()?;
+  } else if !(sync_hook && (cfg.settings.sync || synced)) {
     let path = cfg.source.as_path_buf().display().to_string();
     eprintln!("Hook '{}' is not defined in {path}.", opts.hook);
     if opts.verbose && !cfg.hooks.is_empty() {
@@ -292,6 +476,28 @@ pub fn handle_run(opts: &RunOpts) -> Result<(), RunnerError> {
   Ok(())
 }
 
+/// Read the ref updates Git writes to the standard input of `hook`, if it is
+/// one of [`refs::STDIN_HOOKS`] and standard input is not a terminal, or
+/// passes as the arguments of `update`, and resolve them against the
+/// repository in `cwd`.
+fn read_ref_input(
+  hook: &str,
+  args: &[String],
+  cwd: &Path,
+) -> Result<Arc<RefInput>, RunnerError> {
+  let mut input = if hook == "update" {
+    RefInput::from_update_args(args)
+  } else if refs::STDIN_HOOKS.contains(&hook) && !io::stdin().is_terminal() {
+    let mut raw = Vec::new();
+    io::stdin().read_to_end(&mut raw)?;
+    RefInput::parse(hook, raw)
+  } else {
+    return Ok(Arc::default());
+  };
+  input.resolve(hook, cwd);
+  Ok(Arc::new(input))
+}
+
 /// Handler for the `tasks` subcommand.
 pub fn handle_task(opts: &TaskOpts) -> Result<(), RunnerError> {
   let cfg = HookConfig::discover(&std::env::current_dir()?)?;
@@ -299,13 +505,17 @@ pub fn handle_task(opts: &TaskOpts) -> Result<(), RunnerError> {
   let mut all_tasks: HashSet<String> = HashSet::new();
   all_tasks.extend(cfg.node_scripts.keys().cloned());
   all_tasks.extend(cfg.deno_tasks.keys().cloned());
+  all_tasks.extend(cfg.huk_tasks.keys().cloned());
 
   let path = cfg.source.as_path_buf();
 
   if let Some(ref run_task) = opts.run {
-    if all_tasks.contains(run_task) {
+    let builtin = run_task
+      .strip_prefix("huk:")
+      .is_some_and(builtins::is_builtin);
+    if builtin || all_tasks.contains(run_task) {
       let mut runner = TaskRunner::new(&cfg);
-      runner.run_named_task(run_task)?;
+      runner.run_single(run_task, &opts.args)?;
     } else {
       eprintln!(
         "There is no '{run_task}' task defined in '{path}'.",
@@ -314,46 +524,23 @@ pub fn handle_task(opts: &TaskOpts) -> Result<(), RunnerError> {
     }
   } else {
     #[derive(Serialize)]
-    struct TaskEntry {
-      name:    String,
-      command: String,
-      #[serde(rename = "type")]
-      kind:    String,
-    }
-
-    #[derive(Serialize)]
-    struct TaskList {
+    struct TaskList<'a> {
       source: String,
-      tasks:  Vec<TaskEntry>,
+      tasks:  &'a [TaskEntry],
     }
 
-    let mut tasks: Vec<TaskEntry> = cfg
-      .deno_tasks
-      .iter()
-      .map(|(name, cmd)| TaskEntry {
-        name:    name.clone(),
-        command: cmd.clone(),
-        kind:    "task".into(),
-      })
-      .collect();
-
-    tasks.extend(cfg.node_scripts.iter().map(|(name, cmd)| TaskEntry {
-      name:    name.clone(),
-      command: cmd.clone(),
-      kind:    "script".into(),
-    }));
-
-    tasks.sort_by(|a, b| {
-      a.name
-        .cmp(&b.name)
-        .then_with(|| a.kind.cmp(&b.kind))
-        .then_with(|| a.command.cmp(&b.command))
-    });
+    let tasks = task_entries(&cfg);
+
+    if opts.tree {
+      let color = io::stdout().is_terminal() && !opts.compact;
+      print!("{}", report::format_task_tree(&tasks, color));
+      return Ok(());
+    }
 
     if opts.json || opts.yaml || opts.toml {
       let payload = TaskList {
         source: path.display().to_string(),
-        tasks,
+        tasks:  &tasks,
       };
 
       if opts.json {
@@ -386,22 +573,194 @@ pub fn handle_task(opts: &TaskOpts) -> Result<(), RunnerError> {
       }
     }
 
-    crate::print_tasks!(&cfg, compact = opts.compact);
+    crate::print_tasks!(&cfg, &tasks, compact = opts.compact);
   }
   Ok(())
 }
 
+/// A Deno task or Node script as shown by `huk tasks`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TaskEntry {
+  pub name:         String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub command:      Option<String>,
+  #[serde(rename = "type")]
+  pub kind:         String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub description:  Option<String>,
+  /// Tasks run before this one.
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub dependencies: Vec<String>,
+  /// Hooks that run the task, directly or through the dependencies and
+  /// references of the tasks and hooks they run.
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub hooks:        Vec<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub cwd:          Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub location:     Option<TaskLocation>,
+}
+
+/// Collect every `huk.tasks` entry, Deno task and Node script of `cfg`,
+/// sorted by name.
+pub fn task_entries(cfg: &HookConfig) -> Vec<TaskEntry> {
+  let references = hook_references(cfg);
+  let hooks = |kind: &'static str, name: &str| {
+    references
+      .get(&(kind, name.to_string()))
+      .cloned()
+      .unwrap_or_default()
+  };
+  let mut tasks: Vec<TaskEntry> = cfg
+    .huk_tasks
+    .iter()
+    .map(|(name, task)| {
+      let (dependencies, cwd) = match &task.spec {
+        TaskSpec::Detailed(object) => {
+          (object.dependencies.clone(), object.cwd.clone())
+        }
+        _ => (Vec::new(), None),
+      };
+      TaskEntry {
+        name: name.clone(),
+        command: task.command(),
+        kind: "huk".into(),
+        description: task.description().map(str::to_string),
+        dependencies,
+        hooks: hooks("huk", name),
+        cwd,
+        location: task.location.clone(),
+      }
+    })
+    .collect();
+  tasks.extend(cfg.deno_tasks.iter().map(|(name, task)| TaskEntry {
+    name:         name.clone(),
+    command:      task.command.clone(),
+    kind:         "task".into(),
+    description:  task.description.clone(),
+    dependencies: task.dependencies.clone(),
+    hooks:        hooks("task", name),
+    cwd:          task.cwd.as_ref().map(|p| p.display().to_string()),
+    location:     task.location.clone(),
+  }));
+
+  tasks.extend(cfg.node_scripts.iter().map(|(name, cmd)| {
+    let meta = cfg.script_meta.get(name).cloned().unwrap_or_default();
+    TaskEntry {
+      name:         name.clone(),
+      command:      Some(cmd.clone()),
+      kind:         "script".into(),
+      description:  meta.description,
+      dependencies: Vec::new(),
+      hooks:        hooks("script", name),
+      cwd:          None,
+      location:     meta.location,
+    }
+  }));
+
+  tasks.sort_by(|a, b| {
+    a.name
+      .cmp(&b.name)
+      .then_with(|| a.kind.cmp(&b.kind))
+      .then_with(|| a.command.cmp(&b.command))
+  });
+  tasks
+}
+
+/// Map every `huk.tasks` entry, Deno task and Node script a hook runs, keyed
+/// by its [`TaskEntry::kind`] and name, to the sorted names of those hooks.
+/// Task references, dependencies and the hooks and tasks they run are
+/// followed transitively; references that do not resolve are ignored.
+fn hook_references(
+  cfg: &HookConfig,
+) -> HashMap<(&'static str, String), Vec<String>> {
+  fn walk(cfg: &HookConfig, spec: &TaskSpec, out: &mut Vec<Resolution>) {
+    let mut push = |reference: &str| {
+      if let Ok((resolution, _)) = resolve(cfg, reference) {
+        out.push(resolution);
+      }
+    };
+    match spec {
+      TaskSpec::Single(reference) => push(reference),
+      TaskSpec::Detailed(object) => object
+        .dependencies
+        .iter()
+        .chain(&object.task)
+        .for_each(|r| push(r)),
+      TaskSpec::Sequence(list) => {
+        list.iter().for_each(|item| walk(cfg, item, out))
+      }
+    }
+  }
+  let mut references: HashMap<(&'static str, String), Vec<String>> =
+    HashMap::new();
+  for (hook, spec) in &cfg.hooks {
+    let mut pending = Vec::new();
+    walk(cfg, spec, &mut pending);
+    let mut seen = HashSet::new();
+    while let Some(resolution) = pending.pop() {
+      if !seen.insert(resolution.clone()) {
+        continue;
+      }
+      let key = match resolution {
+        Resolution::HukTask(name) => {
+          walk(cfg, &cfg.huk_tasks[&name].spec, &mut pending);
+          ("huk", name)
+        }
+        Resolution::DenoTask(name) => {
+          let task = &cfg.deno_tasks[&name];
+          pending.extend(
+            task
+              .dependencies
+              .iter()
+              .map(|dep| task.key(dep))
+              .filter(|key| cfg.deno_tasks.contains_key(key))
+              .map(Resolution::DenoTask),
+          );
+          ("task", name)
+        }
+        Resolution::NodeScript(name) => ("script", name),
+        Resolution::Hook(name) => {
+          walk(cfg, &cfg.hooks[&name], &mut pending);
+          continue;
+        }
+        _ => continue,
+      };
+      references.entry(key).or_default().push(hook.clone());
+    }
+  }
+  references.values_mut().for_each(|hooks| hooks.sort());
+  references
+}
+
 pub(crate) fn mutate_hooks<F>(
   cfg: &HookConfig,
   mutator: F,
 ) -> Result<(), RunnerError>
 where
   F: FnOnce(&mut serde_json::Map<String, Value>) -> Result<(), RunnerError>,
+{
+  mutate_config(cfg, |value| with_hooks_map(value, &cfg.source, mutator))?;
+  Ok(())
+}
+
+/// Apply `mutator` to the whole configuration file, writing it back only if
+/// it changed. Returns whether it did.
+pub(crate) fn mutate_config<F>(
+  cfg: &HookConfig,
+  mutator: F,
+) -> Result<bool, RunnerError>
+where
+  F: FnOnce(&mut Value) -> Result<(), RunnerError>,
 {
   let mut value = load_config_value(&cfg.source)?;
-  with_hooks_map(&mut value, &cfg.source, mutator)?;
+  let original = value.clone();
+  mutator(&mut value)?;
+  if value == original {
+    return Ok(false);
+  }
   write_config_value(&cfg.source, &value)?;
-  Ok(())
+  Ok(true)
 }
 
 /// Handler for the `add` subcommand.
@@ -522,12 +881,85 @@ pub fn handle_update(opts: &UpdateOpts) -> Result<(), RunnerError> {
   Ok(())
 }
 
+/// Find the Deno task, Node script or hook whose name is closest to `name`,
+/// for suggesting a fix when `name` looks like a typo. Only names within a
+/// small edit distance are considered.
+pub(crate) fn closest_task_name<'a>(
+  cfg: &'a HookConfig,
+  name: &str,
+) -> Option<&'a str> {
+  // Commands with arguments are deliberate, not misspelled task names.
+  if name.contains(char::is_whitespace) {
+    return None;
+  }
+  let limit = (name.chars().count() / 3).max(1);
+  cfg
+    .huk_tasks
+    .keys()
+    .chain(cfg.deno_tasks.keys())
+    .chain(cfg.node_scripts.keys())
+    .chain(cfg.hooks.keys())
+    .map(|candidate| (edit_distance(name, candidate), candidate))
+    .filter(|(distance, _)| *distance <= limit)
+    .min()
+    .map(|(_, candidate)| candidate.as_str())
+}
+
+/// Levenshtein distance between two strings, counting a transposition of
+/// adjacent characters as a single edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+  let a: Vec<char> = a.chars().collect();
+  let b: Vec<char> = b.chars().collect();
+  let mut prev2 = vec![0; b.len() + 1];
+  let mut prev: Vec<usize> = (0..=b.len()).collect();
+  for i in 1..=a.len() {
+    let mut row = vec![i; b.len() + 1];
+    for j in 1..=b.len() {
+      let cost = usize::from(a[i - 1] != b[j - 1]);
+      row[j] = (prev[j] + 1).min(row[j - 1] + 1).min(prev[j - 1] + cost);
+      if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+        row[j] = row[j].min(prev2[j - 2] + 1);
+      }
+    }
+    prev2 = std::mem::replace(&mut prev, row);
+  }
+  prev[b.len()]
+}
+
 /// A stateful task runner responsible for executing task specifications.
 pub struct TaskRunner<'cfg> {
-  pub config: &'cfg HookConfig,
-  visiting:   HashSet<String>,
+  pub config:      &'cfg HookConfig,
+  visiting:        HashSet<String>,
   /// Optional buffer for capturing stdout/stderr when running via the TUI.
-  pub output: Option<Vec<OutputChunk>>,
+  pub output:      Option<Vec<OutputChunk>>,
+  /// Store of successful run results for tasks that declare `inputs`. This is
+  /// `None` outside a git repository or when caching is disabled.
+  pub cache:       Option<CacheStore>,
+  /// Environment overrides applied to every spawned command. Task objects
+  /// with an `env` field extend this for the duration of their execution.
+  pub env:         BTreeMap<String, String>,
+  /// Echo and capture command output so it can be stored in the run history.
+  pub tee_output:  bool,
+  /// Every command executed or skipped so far, in order.
+  pub runs:        Vec<TaskRun>,
+  /// Current nesting level of hooks referenced from other hooks.
+  depth:           usize,
+  /// Working directory of spawned commands.
+  pub cwd:         PathBuf,
+  /// Whether `cwd` was set by the `cwd` of a task object being run, rather
+  /// than inherited from the process.
+  task_cwd:        bool,
+  /// When set, commands are resolved and recorded here instead of being
+  /// executed. Used by `huk run --dry-run`.
+  pub plan:        Option<Vec<PlanStep>>,
+  /// Command prefix for the package manager, resolved on first use.
+  package_manager: Option<Vec<String>>,
+  /// Set while resolving a name that matched no task, script or hook, so the
+  /// resulting plan step can be flagged.
+  fallback:        bool,
+  /// Ref updates Git passed on standard input, narrowed to those matching
+  /// the `when` conditions of the task objects being run.
+  pub refs:        Arc<RefInput>,
 }
 
 impl<'cfg> TaskRunner<'cfg> {
@@ -536,17 +968,72 @@ impl<'cfg> TaskRunner<'cfg> {
       config,
       visiting: HashSet::new(),
       output: None,
+      cache: CacheStore::discover(config.source.base_dir())
+        .ok()
+        .map(|store| store.with_max_entries(config.settings.cache_max_entries)),
+      env: BTreeMap::new(),
+      tee_output: false,
+      runs: Vec::new(),
+      depth: 0,
+      cwd: std::env::current_dir()
+        .unwrap_or_else(|_| config.source.base_dir().to_path_buf()),
+      task_cwd: false,
+      plan: None,
+      package_manager: None,
+      fallback: false,
+      refs: Arc::default(),
     }
   }
 
+  /// Create a runner that records an execution plan instead of running
+  /// anything. Retrieve the plan with [`TaskRunner::take_plan`].
+  pub fn new_dry_run(config: &'cfg HookConfig) -> Self {
+    Self {
+      plan: Some(Vec::new()),
+      ..Self::new(config)
+    }
+  }
+
+  /// Retrieve the recorded plan if this is a dry run.
+  pub fn take_plan(&mut self) -> Vec<PlanStep> {
+    self.plan.take().unwrap_or_default()
+  }
+
   pub fn new_with_capture(config: &'cfg HookConfig) -> Self {
     Self {
-      config,
-      visiting: HashSet::new(),
       output: Some(Vec::new()),
+      ..Self::new(config)
+    }
+  }
+
+  /// Collect the timing data of every task run so far.
+  pub fn summary<'a>(
+    &'a self,
+    hook: &'a str,
+    total: Duration,
+  ) -> RunSummary<'a> {
+    RunSummary {
+      hook,
+      runs: &self.runs,
+      total,
+      warn_after: self.config.settings.warn_after,
     }
   }
 
+  /// Print a table of every task run so far, with its kind, wall time and
+  /// status, to stderr. Tasks slower than the configured `warn_after`
+  /// threshold are highlighted.
+  pub fn print_summary(&self, hook: &str, total: Duration) {
+    if self.runs.is_empty() {
+      return;
+    }
+    let color = io::stderr().is_terminal();
+    eprint!(
+      "\n{}",
+      report::format_summary(&self.summary(hook, total), color)
+    );
+  }
+
   /// Retrieve captured output if output capture is enabled.
   pub fn take_output(&mut self) -> Vec<OutputChunk> {
     self.output.take().unwrap_or_default()
@@ -563,21 +1050,69 @@ impl<'cfg> TaskRunner<'cfg> {
   ) -> Result<(), RunnerError> {
     match spec {
       TaskSpec::Single(name) => self.run_single(name, extra_args),
-      TaskSpec::Detailed {
-        command,
-        dependencies,
-        ..
-      } => {
-        // Execute dependencies first.
-        for dep in dependencies {
-          self.run_named_task(dep)?;
-        }
-        if let Some(cmd) = command {
-          self.exec_raw_command(cmd, extra_args)
+      TaskSpec::Detailed(object) => {
+        let TaskObject {
+          command,
+          task,
+          args,
+          forward_args,
+          dependencies,
+          parallel,
+          shell,
+          env,
+          cwd,
+          inputs,
+          when,
+          description,
+        } = object.as_ref();
+        let outer_refs = if when.is_empty() {
+          None
         } else {
-          // Only dependencies defined; nothing else to do.
-          Ok(())
+          let branch = if !self.refs.listed {
+            git_output(&self.cwd, &["symbolic-ref", "--short", "-q", "HEAD"])
+          } else {
+            None
+          };
+          let Some(matching) = self.refs.filter(when, branch.as_deref()) else {
+            let label = description
+              .as_deref()
+              .or(command.as_deref())
+              .or(task.as_deref())
+              .unwrap_or(hook);
+            self.note(format!("{label}: skipped ('when' conditions not met)"));
+            return Ok(());
+          };
+          Some(std::mem::replace(&mut self.refs, Arc::new(matching)))
+        };
+        let outer_env = self.env.clone();
+        self
+          .env
+          .extend(env.iter().map(|(k, v)| (k.clone(), v.clone())));
+        let outer_cwd = cwd.as_ref().map(|dir| {
+          let dir = self.config.source.base_dir().join(dir);
+          let task_cwd = std::mem::replace(&mut self.task_cwd, true);
+          (std::mem::replace(&mut self.cwd, dir), task_cwd)
+        });
+        let forwarded = if *forward_args { extra_args } else { &[] };
+        let args = [args.as_slice(), forwarded].concat();
+        let result = self.run_detailed(
+          command.as_deref(),
+          task.as_deref(),
+          dependencies,
+          *parallel,
+          *shell,
+          inputs,
+          &args,
+        );
+        self.env = outer_env;
+        if let Some((cwd, task_cwd)) = outer_cwd {
+          self.cwd = cwd;
+          self.task_cwd = task_cwd;
+        }
+        if let Some(refs) = outer_refs {
+          self.refs = refs;
         }
+        result
       }
       TaskSpec::Sequence(list) => {
         for item in list {
@@ -588,35 +1123,271 @@ impl<'cfg> TaskRunner<'cfg> {
     }
   }
 
-  /// Execute a single task by name or treat it as a raw command if unknown.
+  /// Execute the body of a task object: its dependencies (concurrently if
+  /// `parallel` is set), then its command or task reference. If the task
+  /// declares `inputs` and an identical successful run is already cached,
+  /// the command is skipped.
+  #[allow(clippy::too_many_arguments)]
+  fn run_detailed(
+    &mut self,
+    command: Option<&str>,
+    task: Option<&str>,
+    dependencies: &[String],
+    parallel: bool,
+    shell: bool,
+    inputs: &[String],
+    extra_args: &[String],
+  ) -> Result<(), RunnerError> {
+    // Execute dependencies first.
+    if parallel && dependencies.len() > 1 {
+      self.run_parallel(dependencies)?;
+    } else {
+      for dep in dependencies {
+        self.run_named_task(dep)?;
+      }
+    }
+    // Only dependencies defined; nothing else to do.
+    let Some(cmd) = command.or(task) else {
+      return Ok(());
+    };
+    // A task reference is cached by what it resolves to, so that editing the
+    // referenced script or task invalidates earlier results.
+    let resolved = match task {
+      Some(reference) if self.cache.is_some() && !inputs.is_empty() => {
+        Some(resolve(self.config, reference)?.0)
+      }
+      _ => None,
+    };
+    let kind = resolved
+      .as_ref()
+      .map_or(TaskKind::Command, Resolution::kind);
+    let key = match &self.cache {
+      Some(_) if !inputs.is_empty() => Some(cache::compute_key(
+        self.config.source.base_dir(),
+        &CacheInputs {
+          command: cmd,
+          kind,
+          body: &resolved
+            .as_ref()
+            .map_or_else(|| cmd.to_string(), |r| definition(self.config, r)),
+          cwd: &self.cwd,
+          shell,
+          env: &self.env,
+          args: extra_args,
+          patterns: inputs,
+        },
+      )?),
+      _ => None,
+    };
+    if let (Some(store), Some(key)) = (&self.cache, &key)
+      && store.contains(key)
+    {
+      if let Some(plan) = self.plan.as_mut() {
+        plan.push(PlanStep {
+          name: cmd.to_string(),
+          kind,
+          command: Some(cmd.to_string()),
+          cwd: self.cwd.clone(),
+          env: self.env.clone(),
+          depth: self.depth,
+          cached: true,
+          fallback: false,
+        });
+        return Ok(());
+      }
+      self.note(format!("{cmd}: cached"));
+      self.runs.push(TaskRun {
+        name: cmd.to_string(),
+        command: cmd.to_string(),
+        kind,
+        depth: self.depth,
+        duration_ms: 0,
+        exit_code: None,
+        success: true,
+        cached: true,
+        output: String::new(),
+      });
+      return Ok(());
+    }
+    if task.is_some() {
+      self.run_single(cmd, extra_args)?;
+    } else if shell {
+      self.exec_raw_command(cmd, extra_args)?;
+    } else {
+      self.exec_direct_command(cmd, extra_args)?;
+    }
+    if let (Some(store), Some(key), None) = (&self.cache, &key, &self.plan) {
+      // A cache that cannot be written only costs a re-run next time, so it
+      // should never fail the hook.
+      let _ = store.record(key, cmd);
+    }
+    Ok(())
+  }
+
+  /// Print an informational message to stderr, or capture it alongside the
+  /// command output when running via the TUI.
+  fn note(&mut self, message: String) {
+    if let Some(buf) = self.output.as_mut() {
+      buf.push(OutputChunk::Stderr(message));
+    } else {
+      eprintln!("{message}");
+    }
+  }
+
+  /// Execute a single task reference, resolved with [`resolve`]. Unknown
+  /// bare names are run as raw commands unless the `strict` setting is on.
+  /// Arguments written in the reference itself (`lint -- --fix`) come before
+  /// `extra_args`.
   pub(crate) fn run_single(
     &mut self,
-    name: &str,
+    reference: &str,
     extra_args: &[String],
   ) -> Result<(), RunnerError> {
+    let (resolution, args) = resolve(self.config, reference)?;
+    let extra_args = &[args.as_slice(), extra_args].concat();
+    let name = resolution.target();
     // To avoid cycles, track the task names we are resolving.
     if self.visiting.contains(name) {
       return Err(RunnerError::CircularDependency(name.to_string()));
     }
     self.visiting.insert(name.to_string());
-    let result = if self.config.deno_tasks.get(name).is_some() {
-      // It's a Deno task.
-      self.exec_deno_task(name, extra_args)
-    } else if let Some(script) = self.config.node_scripts.get(name) {
-      // It's a Node script.
-      self.exec_node_script(name, script, extra_args)
-    } else if let Some(spec) = self.config.hooks.get(name) {
-      // It's another hook; run its spec.
-      self.run_spec(spec, name, extra_args)
-    } else {
-      // Unknown: treat as raw command.
-      self.exec_raw_command(name, extra_args)
+    let result = match &resolution {
+      Resolution::HukTask(name) => {
+        let spec = &self.config.huk_tasks[name].spec;
+        self.run_group(name, TaskKind::HukTask, spec, extra_args)
+      }
+      Resolution::DenoTask(name) => self.exec_deno_task(name, extra_args),
+      Resolution::NodeScript(name) => {
+        let script = &self.config.node_scripts[name];
+        self.exec_node_script(name, script, extra_args)
+      }
+      Resolution::Hook(name) => {
+        let spec = &self.config.hooks[name];
+        self.run_group(name, TaskKind::Hook, spec, extra_args)
+      }
+      Resolution::Command(cmd) => self.exec_raw_command(cmd, extra_args),
+      Resolution::Builtin(name) => self.exec_builtin(name, extra_args),
+      Resolution::Fallback(cmd) => {
+        self.fallback = true;
+        self.exec_raw_command(cmd, extra_args)
+      }
     };
     self.visiting.remove(name);
     result
   }
 
-  /// Run a named task defined in either node_scripts or deno_tasks.
+  /// Run the specification of another hook or of a `huk.tasks` entry,
+  /// grouping its commands under a single entry in the run log.
+  fn run_group(
+    &mut self,
+    name: &str,
+    kind: TaskKind,
+    spec: &'cfg TaskSpec,
+    extra_args: &[String],
+  ) -> Result<(), RunnerError> {
+    if let Some(plan) = self.plan.as_mut() {
+      plan.push(PlanStep {
+        name: name.to_string(),
+        kind,
+        command: None,
+        cwd: self.cwd.clone(),
+        env: self.env.clone(),
+        depth: self.depth,
+        cached: false,
+        fallback: false,
+      });
+      self.depth += 1;
+      let result = self.run_spec(spec, name, extra_args);
+      self.depth -= 1;
+      return result;
+    }
+    let slot = self.runs.len();
+    self.runs.push(TaskRun {
+      name: name.to_string(),
+      command: match kind {
+        TaskKind::Hook => format!("huk run {name}"),
+        _ => format!("huk tasks {name}"),
+      },
+      kind,
+      depth: self.depth,
+      duration_ms: 0,
+      exit_code: None,
+      success: false,
+      cached: false,
+      output: String::new(),
+    });
+    let started = Instant::now();
+    self.depth += 1;
+    let result = self.run_spec(spec, name, extra_args);
+    self.depth -= 1;
+    let entry = &mut self.runs[slot];
+    entry.duration_ms = started.elapsed().as_millis() as u64;
+    entry.success = result.is_ok();
+    result
+  }
+
+  /// Run task references concurrently, each on a fork of this runner with
+  /// the same environment and working directory. Once all of them have
+  /// finished, their run logs, captured output and plan steps are merged in
+  /// the order the references were listed, and the first failure in that
+  /// order is returned. Output from commands that are not captured may be
+  /// interleaved.
+  fn run_parallel(&mut self, references: &[String]) -> Result<(), RunnerError> {
+    let mut forks: Vec<Self> = references.iter().map(|_| self.fork()).collect();
+    let results: Vec<Result<(), RunnerError>> = thread::scope(|scope| {
+      let handles: Vec<_> = forks
+        .iter_mut()
+        .zip(references)
+        .map(|(fork, reference)| {
+          scope.spawn(move || fork.run_named_task(reference))
+        })
+        .collect();
+      handles
+        .into_iter()
+        .map(|handle| {
+          handle
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+        })
+        .collect()
+    });
+    for fork in forks {
+      self.runs.extend(fork.runs);
+      if let (Some(output), Some(forked)) = (self.output.as_mut(), fork.output)
+      {
+        output.extend(forked);
+      }
+      if let (Some(plan), Some(forked)) = (self.plan.as_mut(), fork.plan) {
+        plan.extend(forked);
+      }
+    }
+    results.into_iter().collect()
+  }
+
+  /// A runner sharing this one's configuration, environment, working
+  /// directory and mode, with empty logs.
+  fn fork(&self) -> Self {
+    Self {
+      config:          self.config,
+      visiting:        self.visiting.clone(),
+      output:          self.output.as_ref().map(|_| Vec::new()),
+      cache:           self.cache.clone(),
+      env:             self.env.clone(),
+      tee_output:      self.tee_output,
+      runs:            Vec::new(),
+      depth:           self.depth,
+      cwd:             self.cwd.clone(),
+      task_cwd:        self.task_cwd,
+      plan:            self.plan.as_ref().map(|_| Vec::new()),
+      package_manager: self.package_manager.clone(),
+      fallback:        false,
+      refs:            self.refs.clone(),
+    }
+  }
+
+  /// Run a task reference from a `dependencies` list. Dependencies only
+  /// receive the arguments written in the reference itself, never the
+  /// arguments Git passed to the hook.
   pub(crate) fn run_named_task(
     &mut self,
     name: &str,
@@ -624,125 +1395,714 @@ impl<'cfg> TaskRunner<'cfg> {
     self.run_single(name, &[])
   }
 
+  /// Run a built-in task, printing its report (or capturing it when running
+  /// via the TUI). A failed check is reported as
+  /// [`RunnerError::BuiltinFailure`].
+  pub(crate) fn exec_builtin(
+    &mut self,
+    name: &str,
+    extra_args: &[String],
+  ) -> Result<(), RunnerError> {
+    let display = shell::join(
+      std::iter::once(format!("huk:{name}")).chain(extra_args.iter().cloned()),
+    );
+    if let Some(plan) = self.plan.as_mut() {
+      plan.push(PlanStep {
+        name:     format!("huk:{name}"),
+        kind:     TaskKind::Builtin,
+        command:  Some(display),
+        cwd:      self.cwd.clone(),
+        env:      self.env.clone(),
+        depth:    self.depth,
+        cached:   false,
+        fallback: false,
+      });
+      return Ok(());
+    }
+    let color = self.output.is_none() && io::stderr().is_terminal();
+    let started = Instant::now();
+    let outcome =
+      builtins::run(name, extra_args, self.config, &self.cwd, color)?;
+    if !outcome.report.is_empty() {
+      self.note(outcome.report.trim_end().to_string());
+    }
+    self.runs.push(TaskRun {
+      name:        format!("huk:{name}"),
+      command:     display,
+      kind:        TaskKind::Builtin,
+      depth:       self.depth,
+      duration_ms: started.elapsed().as_millis() as u64,
+      exit_code:   None,
+      success:     outcome.success,
+      cached:      false,
+      output:      truncate_output(outcome.report),
+    });
+    if outcome.success {
+      Ok(())
+    } else {
+      Err(RunnerError::BuiltinFailure(name.to_string()))
+    }
+  }
+
+  /// Expand the ref placeholders of `cmd` with [`RefInput::expand`], noting
+  /// that the command is skipped when there is nothing to substitute.
+  fn expand_placeholders(&mut self, cmd: &str) -> Option<String> {
+    let expanded = self.refs.expand(cmd);
+    if expanded.is_none() {
+      self.note(format!(
+        "{cmd}: skipped (no pushed commits for {{push_range}} or \
+         {{changed_files}})"
+      ));
+    }
+    expanded
+  }
+
   /// Execute a raw shell command. Extra arguments from the hook invocation are
-  /// appended.
+  /// quoted with [`shell::quote`] and appended, so the shell passes each one
+  /// through as a single literal word.
   pub(crate) fn exec_raw_command(
     &mut self,
     cmd: &str,
     extra_args: &[String],
   ) -> Result<(), RunnerError> {
-    // Compose the final command string. If there are extra args, append them.
-    let mut full_cmd = cmd.to_string();
+    let Some(mut full_cmd) = self.expand_placeholders(cmd) else {
+      return Ok(());
+    };
     if !extra_args.is_empty() {
-      // Append each argument quoting as necessary (naive quoting: wrap in
-      // single quotes if whitespace).
-      for arg in extra_args {
-        if arg.contains(' ') {
-          full_cmd.push(' ');
-          full_cmd.push_str(&format!("'{}'", arg.replace('"', "\\\"")));
-        } else {
-          full_cmd.push(' ');
-          full_cmd.push_str(arg);
-        }
-      }
+      full_cmd.push(' ');
+      full_cmd.push_str(&shell::join(extra_args));
     }
     // Execute via sh -c.
     let mut command = Command::new("sh");
     command.arg("-c").arg(&full_cmd);
-    self.spawn_command(command, full_cmd)
+    self.spawn_command(command, full_cmd, cmd, TaskKind::Command)
   }
 
-  /// Execute a Deno task using `deno task`.
+  /// Execute a command without a shell. The command string is split into
+  /// words with [`shell::split`] and extra arguments from the hook invocation
+  /// are appended as separate argv entries, untouched.
+  pub(crate) fn exec_direct_command(
+    &mut self,
+    cmd: &str,
+    extra_args: &[String],
+  ) -> Result<(), RunnerError> {
+    let Some(expanded) = self.expand_placeholders(cmd) else {
+      return Ok(());
+    };
+    let words = shell::split(&expanded)?;
+    let mut command = Command::new(&words[0]);
+    command.args(&words[1..]).args(extra_args);
+    let display = shell::join(words.iter().chain(extra_args));
+    self.spawn_command(command, display, cmd, TaskKind::Command)
+  }
+
+  /// Execute a Deno task using `deno task`, which also runs its
+  /// dependencies. Tasks of workspace members run in the member's directory,
+  /// under the name their own configuration file gives them.
   pub(crate) fn exec_deno_task(
     &mut self,
     name: &str,
     extra_args: &[String],
   ) -> Result<(), RunnerError> {
+    let task = &self.config.deno_tasks[name];
+    let task_name = task.name(name);
     let mut cmd = Command::new("deno");
-    cmd.arg("task").arg(name);
+    cmd.arg("task").arg(task_name);
     for arg in extra_args {
       cmd.arg(arg);
     }
-    self.spawn_command(cmd, format!("deno task {name}"))
+    let display = shell::join(
+      ["deno", "task", task_name]
+        .into_iter()
+        .chain(extra_args.iter().map(String::as_str)),
+    );
+    let member_cwd = task.cwd.clone();
+    let outer_cwd = member_cwd.map(|cwd| std::mem::replace(&mut self.cwd, cwd));
+    let result = self.spawn_command(cmd, display, name, TaskKind::DenoTask);
+    if let Some(cwd) = outer_cwd {
+      self.cwd = cwd;
+    }
+    result
   }
 
-  /// Execute a Node script using the configured package manager.
+  /// Execute a Node script using the project's package manager, detected
+  /// with [`PackageManager::detect`]. The manager's version is only checked
+  /// when a command is actually going to run, not for dry runs. With the
+  /// `direct_scripts` setting, scripts that allow it are run directly instead.
   pub(crate) fn exec_node_script(
     &mut self,
     name: &str,
-    _script: &str,
+    script: &str,
     extra_args: &[String],
   ) -> Result<(), RunnerError> {
-    // Determine the package manager. Parse something like "pnpm@7.1.2" into
-    // "pnpm".
-    let manager = self.config.package_manager.as_deref().unwrap_or("npm");
-    let exe_name = Self::extract_package_manager_command(manager);
-    // Build the command: <pm> run <script> [-- <extra args>]
-    let mut cmd = Command::new(&exe_name);
-    cmd.arg("run").arg(name);
-    // If there are extra arguments, insert -- to forward them to the script.
-    if !extra_args.is_empty() {
-      cmd.arg("--");
-      for arg in extra_args {
-        cmd.arg(arg);
+    let base = self.config.source.base_dir();
+    let manager =
+      PackageManager::detect(base, self.config.package_manager.as_deref())?;
+    if self.config.settings.direct_scripts {
+      let lifecycle = manager.runs_pre_post_scripts(base);
+      let scripts = &self.config.node_scripts;
+      let stage = |event: String| {
+        scripts
+          .get(&event)
+          .filter(|_| lifecycle)
+          .map(String::as_str)
+      };
+      let pre = stage(format!("pre{name}"));
+      let post = stage(format!("post{name}"));
+      let direct = [pre, Some(script), post]
+        .into_iter()
+        .flatten()
+        .all(|body| package_manager::can_run_directly(base, body));
+      if direct {
+        return self.exec_node_script_directly(name, extra_args, lifecycle);
       }
     }
-    self.spawn_command(cmd, format!("{exe_name} run {name}"))
+    let mut argv = match (&self.plan, &self.package_manager) {
+      (Some(_), _) => vec![manager.kind.binary().to_string()],
+      (None, Some(invocation)) => invocation.clone(),
+      (None, None) => {
+        let invocation = manager.invocation()?;
+        self.package_manager = Some(invocation.clone());
+        invocation
+      }
+    };
+    argv.extend(manager.run_args(name, extra_args));
+    let mut cmd = Command::new(&argv[0]);
+    cmd.args(&argv[1..]);
+    let display = shell::join(&argv);
+    self.spawn_command(cmd, display, name, TaskKind::NodeScript)
   }
 
-  /// Extract the binary name from a packageManager field value. For example,
-  /// `"pnpm@9.1.4"` becomes `"pnpm"`. If the value is simply a bare name
-  /// without a version it will be returned unchanged.
-  fn extract_package_manager_command(pm: &str) -> String {
-    // The packageManager string may look like "npm@10.8.1". Split at '@'.
-    let parts: Vec<&str> = pm.split('@').collect();
-    let pm = parts[0].to_lowercase();
-    let pm = pm.trim();
-    match pm {
-      "npm" | "yarn" | "pnpm" | "bun" => pm.to_string(),
-      _ => "npm".to_string(),
+  /// Run a Node script the way its package manager would, without starting
+  /// it: its `pre<name>` script when `lifecycle` is set, the script itself
+  /// with `extra_args` appended, then its `post<name>` script, each through
+  /// `sh -c` with the environment from [`package_manager::script_env`]. Like
+  /// under the package manager, the scripts run in the directory of
+  /// `package.json` unless the task object sets a `cwd`.
+  fn exec_node_script_directly(
+    &mut self,
+    name: &str,
+    extra_args: &[String],
+    lifecycle: bool,
+  ) -> Result<(), RunnerError> {
+    let package_json = self.config.source.as_path();
+    let stages = [
+      (format!("pre{name}"), &[][..]),
+      (name.to_string(), extra_args),
+      (format!("post{name}"), &[][..]),
+    ];
+    let cwd = if self.task_cwd {
+      self.cwd.clone()
+    } else {
+      self.config.source.base_dir().to_path_buf()
+    };
+    for (i, (event, args)) in stages.into_iter().enumerate() {
+      if i != 1 && !lifecycle {
+        continue;
+      }
+      let Some(body) = self.config.node_scripts.get(&event) else {
+        continue;
+      };
+      let mut full_cmd = body.clone();
+      if !args.is_empty() {
+        full_cmd.push(' ');
+        full_cmd.push_str(&shell::join(args));
+      }
+      let mut command = Command::new("sh");
+      command.arg("-c").arg(&full_cmd);
+      let outer_env = self.env.clone();
+      self
+        .env
+        .extend(package_manager::script_env(package_json, &event, body));
+      let outer_cwd = std::mem::replace(&mut self.cwd, cwd.clone());
+      let result =
+        self.spawn_command(command, full_cmd, &event, TaskKind::NodeScript);
+      self.env = outer_env;
+      self.cwd = outer_cwd;
+      result?;
     }
+    Ok(())
   }
 
   /// Spawn the command either streaming output directly or capturing
-  /// stdout/stderr when an output buffer is present.
+  /// stdout/stderr when an output buffer is present. Every execution is
+  /// recorded in [`TaskRunner::runs`].
   fn spawn_command(
     &mut self,
     mut cmd: Command,
     display: String,
+    name: &str,
+    kind: TaskKind,
   ) -> Result<(), RunnerError> {
-    if let Some(buf) = self.output.as_mut() {
+    if !self.refs.updates.is_empty() {
+      cmd.envs(self.refs.env());
+    }
+    cmd.envs(&self.env).current_dir(&self.cwd);
+    let fallback = std::mem::take(&mut self.fallback);
+    if let Some(plan) = self.plan.as_mut() {
+      let argv = std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|arg| arg.to_string_lossy().into_owned());
+      plan.push(PlanStep {
+        name: name.to_string(),
+        kind,
+        command: Some(shell::join(argv)),
+        cwd: self.cwd.clone(),
+        env: self.env.clone(),
+        depth: self.depth,
+        cached: false,
+        fallback,
+      });
+      return Ok(());
+    }
+    if !self.refs.raw.is_empty() {
+      // Hand Git's standard input on to the command, as it would have
+      // received it had it been the hook itself. The command may exit
+      // without reading it, so a failed write is not an error.
+      let (reader, mut writer) = io::pipe()?;
+      let input = self.refs.clone();
+      thread::spawn(move || writer.write_all(&input.raw));
+      cmd.stdin(reader);
+    }
+    let started = Instant::now();
+    let (status, output) = if let Some(buf) = self.output.as_mut() {
       let output = cmd.output()?;
+      let mut combined = String::new();
       if !output.stdout.is_empty() {
-        buf.push(OutputChunk::Stdout(
-          String::from_utf8_lossy(&output.stdout).to_string(),
-        ));
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        combined.push_str(&stdout);
+        buf.push(OutputChunk::Stdout(stdout));
       }
       if !output.stderr.is_empty() {
-        buf.push(OutputChunk::Stderr(
-          String::from_utf8_lossy(&output.stderr).to_string(),
-        ));
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        combined.push_str(&stderr);
+        buf.push(OutputChunk::Stderr(stderr));
       }
-      if output.status.success() {
-        Ok(())
-      } else {
-        Err(RunnerError::CommandFailure {
-          cmd:    display,
-          status: output.status,
-        })
+      (output.status, combined)
+    } else if self.tee_output {
+      tee_command(&mut cmd)?
+    } else {
+      (cmd.status()?, String::new())
+    };
+    self.runs.push(TaskRun {
+      name: name.to_string(),
+      command: display.clone(),
+      kind,
+      depth: self.depth,
+      duration_ms: started.elapsed().as_millis() as u64,
+      exit_code: status.code(),
+      success: status.success(),
+      cached: false,
+      output: truncate_output(output),
+    });
+    if status.success() {
+      Ok(())
+    } else {
+      Err(RunnerError::CommandFailure {
+        cmd: display,
+        status,
+      })
+    }
+  }
+}
+
+/// Maximum number of bytes of output retained per command for the run
+/// history. Only the tail is kept, since that is where failures are reported.
+pub const OUTPUT_LIMIT: usize = 16 * 1024;
+
+/// Keep at most the last [`OUTPUT_LIMIT`] bytes of `output`, respecting
+/// character boundaries.
+fn truncate_output(mut output: String) -> String {
+  if output.len() <= OUTPUT_LIMIT {
+    return output;
+  }
+  let mut cut = output.len() - OUTPUT_LIMIT;
+  while !output.is_char_boundary(cut) {
+    cut += 1;
+  }
+  output.drain(..cut);
+  output
+}
+
+/// Run `cmd` with piped stdout/stderr, echoing both streams to this process's
+/// own stdout/stderr as they arrive while collecting the tail of the combined
+/// output.
+fn tee_command(cmd: &mut Command) -> io::Result<(ExitStatus, String)> {
+  fn pump<R: Read, W: Write>(
+    mut from: R,
+    mut to: W,
+    tail: &Mutex<Vec<u8>>,
+  ) -> io::Result<()> {
+    let mut chunk = [0u8; 8192];
+    // Once our own stdout or stderr is closed, keep draining the pipe so the
+    // child never blocks on a full buffer.
+    let mut writable = true;
+    loop {
+      let n = match from.read(&mut chunk) {
+        Ok(0) => return Ok(()),
+        Ok(n) => n,
+        Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+        Err(e) => return Err(e),
+      };
+      if writable {
+        writable = to.write_all(&chunk[..n]).and_then(|()| to.flush()).is_ok();
+      }
+      let mut tail = tail.lock().unwrap_or_else(|e| e.into_inner());
+      tail.extend_from_slice(&chunk[..n]);
+      if tail.len() > OUTPUT_LIMIT * 2 {
+        let excess = tail.len() - OUTPUT_LIMIT;
+        tail.drain(..excess);
       }
+    }
+  }
+
+  cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+  let mut child = cmd.spawn()?;
+  let stdout = child.stdout.take();
+  let stderr = child.stderr.take();
+  let tail = Mutex::new(Vec::new());
+  thread::scope(|scope| {
+    if let Some(out) = stdout {
+      scope.spawn(|| pump(out, io::stdout(), &tail));
+    }
+    if let Some(err) = stderr {
+      scope.spawn(|| pump(err, io::stderr(), &tail));
+    }
+  });
+  let status = child.wait()?;
+  let tail = tail.into_inner().unwrap_or_else(|e| e.into_inner());
+  Ok((status, String::from_utf8_lossy(&tail).to_string()))
+}
+
+/// How a task in a hook specification was resolved and executed.
+#[derive(
+  Clone, Copy, Debug, Default, PartialEq, Eq, Display, Serialize, Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaskKind {
+  /// A task from the Deno `tasks` field, run with `deno task`.
+  #[display("deno task")]
+  DenoTask,
+  /// A script from the Node `scripts` field, run with the package manager.
+  #[display("node script")]
+  NodeScript,
+  /// A raw command, either written inline or as a task object's `command`.
+  #[default]
+  #[display("command")]
+  Command,
+  /// Another hook referenced by name.
+  #[display("hook")]
+  Hook,
+  /// A task from the `huk.tasks` map.
+  #[display("huk task")]
+  HukTask,
+  /// A task built into `huk`, referenced with a `huk:` prefix.
+  #[display("built-in")]
+  Builtin,
+}
+
+/// What a task reference in a hook specification resolves to.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Display, Serialize)]
+#[serde(tag = "kind", content = "target", rename_all = "kebab-case")]
+pub enum Resolution {
+  /// A task from the `huk.tasks` map.
+  #[display("huk task '{_0}'")]
+  HukTask(String),
+  /// A task from the Deno `tasks` field.
+  #[display("deno task '{_0}'")]
+  DenoTask(String),
+  /// A script from the Node `scripts` field.
+  #[display("node script '{_0}'")]
+  NodeScript(String),
+  /// Another hook definition.
+  #[display("hook '{_0}'")]
+  Hook(String),
+  /// A shell command written with an explicit `sh:` prefix.
+  #[display("shell command")]
+  Command(String),
+  /// A task built into `huk`, written with a `huk:` prefix.
+  #[display("built-in 'huk:{_0}'")]
+  Builtin(String),
+  /// A bare name that matched nothing and is run as a shell command.
+  #[display("unresolved, run as shell command")]
+  Fallback(String),
+}
+
+impl Resolution {
+  /// The task name, or the command for shell commands.
+  pub fn target(&self) -> &str {
+    match self {
+      Resolution::HukTask(s)
+      | Resolution::DenoTask(s)
+      | Resolution::NodeScript(s)
+      | Resolution::Hook(s)
+      | Resolution::Command(s)
+      | Resolution::Builtin(s)
+      | Resolution::Fallback(s) => s,
+    }
+  }
+
+  /// The kind of task run for this resolution.
+  pub fn kind(&self) -> TaskKind {
+    match self {
+      Resolution::HukTask(_) => TaskKind::HukTask,
+      Resolution::DenoTask(_) => TaskKind::DenoTask,
+      Resolution::NodeScript(_) => TaskKind::NodeScript,
+      Resolution::Hook(_) => TaskKind::Hook,
+      Resolution::Builtin(_) => TaskKind::Builtin,
+      Resolution::Command(_) | Resolution::Fallback(_) => TaskKind::Command,
+    }
+  }
+}
+
+/// The definition a resolved reference runs: the command of a script or Deno
+/// task, the specification of a hook or `huk.tasks` entry, or the command or
+/// built-in name itself.
+fn definition(cfg: &HookConfig, resolution: &Resolution) -> String {
+  match resolution {
+    Resolution::HukTask(name) => cfg.huk_tasks[name].spec.to_string(),
+    Resolution::Hook(name) => cfg.hooks[name].to_string(),
+    Resolution::DenoTask(name) => {
+      let task = &cfg.deno_tasks[name];
+      json!({"command": task.command, "dependencies": task.dependencies})
+        .to_string()
+    }
+    Resolution::NodeScript(name) => cfg.node_scripts[name].clone(),
+    Resolution::Command(s)
+    | Resolution::Builtin(s)
+    | Resolution::Fallback(s) => s.clone(),
+  }
+}
+
+/// Resolve a task reference against the configuration, returning what it
+/// names and the arguments written after ` -- ` in the reference itself.
+/// Explicitly prefixed references (`task:`, `script:`, `hook:`, `huk:`)
+/// must name something that exists; `task:` names a `huk.tasks` entry or a
+/// Deno task, and `huk:` a built-in task.
+/// Bare names are looked up as a `huk.tasks` entry, Deno task, Node script
+/// and hook in that order; if none match they fall back to being run as a
+/// shell command, or are rejected when the `strict` setting is enabled.
+/// Shell commands are never split at ` -- `, since it is meaningful to most
+/// commands.
+pub fn resolve(
+  cfg: &HookConfig,
+  reference: &str,
+) -> Result<(Resolution, Vec<String>), RunnerError> {
+  let not_found = || RunnerError::TaskNotFound(reference.trim().to_string());
+  let lookup_task = |name: &str| {
+    if cfg.huk_tasks.contains_key(name) {
+      Some(Resolution::HukTask(name.to_string()))
+    } else if cfg.deno_tasks.contains_key(name) {
+      Some(Resolution::DenoTask(name.to_string()))
     } else {
-      let status = cmd.status()?;
-      if status.success() {
-        Ok(())
-      } else {
-        Err(RunnerError::CommandFailure {
-          cmd: display,
-          status,
-        })
+      None
+    }
+  };
+  let lookup_bare = |name: &str| {
+    if let Some(resolution) = lookup_task(name) {
+      Some(resolution)
+    } else if cfg.node_scripts.contains_key(name) {
+      Some(Resolution::NodeScript(name.to_string()))
+    } else if cfg.hooks.contains_key(name) {
+      Some(Resolution::Hook(name.to_string()))
+    } else {
+      None
+    }
+  };
+  let (resolution, args) = match TaskRef::parse(reference) {
+    TaskRef::Shell(cmd) if !cmd.is_empty() => {
+      return Ok((Resolution::Command(cmd.to_string()), Vec::new()));
+    }
+    TaskRef::Shell(_) => None,
+    TaskRef::Task(target) => {
+      let (name, args) = split_task_args(target);
+      lookup_task(name).map(|resolution| (resolution, args))
+    }
+    TaskRef::Script(target) => {
+      let (name, args) = split_task_args(target);
+      cfg
+        .node_scripts
+        .contains_key(name)
+        .then(|| (Resolution::NodeScript(name.to_string()), args))
+    }
+    TaskRef::Builtin(target) => {
+      let (name, args) = split_task_args(target);
+      builtins::is_builtin(name)
+        .then(|| (Resolution::Builtin(name.to_string()), args))
+    }
+    TaskRef::Hook(target) => {
+      let (name, args) = split_task_args(target);
+      cfg
+        .hooks
+        .contains_key(name)
+        .then(|| (Resolution::Hook(name.to_string()), args))
+    }
+    TaskRef::Bare(target) => {
+      let (name, args) = split_task_args(target);
+      match lookup_bare(name) {
+        Some(resolution) => Some((resolution, args)),
+        None if cfg.settings.strict => None,
+        None => Some((Resolution::Fallback(target.to_string()), None)),
+      }
+    }
+  }
+  .ok_or_else(not_found)?;
+  let args = match args {
+    Some(args) => shell::split(args)?,
+    None => Vec::new(),
+  };
+  Ok((resolution, args))
+}
+
+/// Split `name -- args` into the name and the (unparsed) argument string.
+fn split_task_args(target: &str) -> (&str, Option<&str>) {
+  if let Some((name, args)) = target.split_once(" -- ") {
+    (name.trim_end(), Some(args).filter(|a| !a.trim().is_empty()))
+  } else {
+    (
+      target.strip_suffix(" --").unwrap_or(target).trim_end(),
+      None,
+    )
+  }
+}
+
+/// Check that every task reference in every hook resolves, as
+/// [`validate_hook`] does for a single hook. Used by `huk doctor`.
+pub fn validate(cfg: &HookConfig) -> Result<(), RunnerError> {
+  let mut hooks: Vec<&String> = cfg.hooks.keys().collect();
+  hooks.sort();
+  hooks
+    .into_iter()
+    .try_for_each(|hook| validate_hook(cfg, hook))
+}
+
+/// Check that every task reference the hook `hook` reaches (bare entries,
+/// `task` fields and `dependencies`, including those inside the hooks and
+/// `huk.tasks` entries they run) resolves, so that mistakes are reported
+/// before anything runs rather than part-way through the hook. Other hooks
+/// are not checked, so a mistake in one does not stop the rest from running.
+pub fn validate_hook(cfg: &HookConfig, hook: &str) -> Result<(), RunnerError> {
+  fn check(
+    cfg: &HookConfig,
+    reference: &str,
+    stack: &mut Vec<String>,
+  ) -> Result<(), RunnerError> {
+    // Hooks and tasks may share a name, so entries carry their kind.
+    let (spec, key, name) = match resolve(cfg, reference)? {
+      (Resolution::DenoTask(name), _) => {
+        return check_deno_task(cfg, &name, &mut Vec::new());
+      }
+      (Resolution::HukTask(name), _) => {
+        (&cfg.huk_tasks[&name].spec, format!("task:{name}"), name)
+      }
+      (Resolution::Hook(name), _) => {
+        (&cfg.hooks[&name], format!("hook:{name}"), name)
+      }
+      _ => return Ok(()),
+    };
+    if stack.contains(&key) {
+      return Err(RunnerError::CircularDependency(name));
+    }
+    stack.push(key);
+    walk(cfg, spec, stack)?;
+    stack.pop();
+    Ok(())
+  }
+  fn walk(
+    cfg: &HookConfig,
+    spec: &TaskSpec,
+    stack: &mut Vec<String>,
+  ) -> Result<(), RunnerError> {
+    match spec {
+      TaskSpec::Single(reference) => check(cfg, reference, stack),
+      TaskSpec::Detailed(object) => object
+        .dependencies
+        .iter()
+        .chain(&object.task)
+        .try_for_each(|reference| check(cfg, reference, stack)),
+      TaskSpec::Sequence(list) => {
+        list.iter().try_for_each(|item| walk(cfg, item, stack))
       }
     }
   }
+  match cfg.hooks.get(hook) {
+    Some(spec) => walk(cfg, spec, &mut vec![format!("hook:{hook}")]),
+    None => Ok(()),
+  }
+}
+
+/// Check the dependency graph of a Deno task the way `deno task` does:
+/// every dependency must name another Deno task, and there must be no
+/// cycles. `stack` holds the tasks currently being checked.
+fn check_deno_task(
+  cfg: &HookConfig,
+  name: &str,
+  stack: &mut Vec<String>,
+) -> Result<(), RunnerError> {
+  if stack.iter().any(|n| n == name) {
+    return Err(RunnerError::CircularDependency(name.to_string()));
+  }
+  let Some(task) = cfg.deno_tasks.get(name) else {
+    return Err(RunnerError::TaskNotFound(name.to_string()));
+  };
+  stack.push(name.to_string());
+  for dep in &task.dependencies {
+    check_deno_task(cfg, &task.key(dep), stack)?;
+  }
+  stack.pop();
+  Ok(())
+}
+
+/// Record of a single command executed (or skipped) by a [`TaskRunner`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskRun {
+  /// The task name, or the command itself for raw commands.
+  #[serde(default)]
+  pub name:        String,
+  /// The command line as displayed to the user.
+  pub command:     String,
+  /// How the task was resolved.
+  #[serde(default)]
+  pub kind:        TaskKind,
+  /// Nesting level: commands run by a nested hook are one level deeper than
+  /// the hook's own entry.
+  #[serde(default)]
+  pub depth:       usize,
+  /// Wall-clock time spent running the command, in milliseconds.
+  pub duration_ms: u64,
+  /// Exit code of the process, if it exited normally.
+  pub exit_code:   Option<i32>,
+  /// Whether the command succeeded (or was skipped as cached).
+  pub success:     bool,
+  /// Whether the command was skipped because of a cached result.
+  #[serde(default)]
+  pub cached:      bool,
+  /// The tail of the command's combined stdout/stderr, if it was captured.
+  #[serde(default)]
+  pub output:      String,
+}
+
+/// A command that would be executed by a hook, as recorded by a dry run.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlanStep {
+  /// The task name, or the command itself for raw commands.
+  pub name:     String,
+  /// How the task was resolved.
+  pub kind:     TaskKind,
+  /// The exact argv that would be spawned, quoted for a POSIX shell. `None`
+  /// for hooks, whose commands follow as separate steps one level deeper.
+  pub command:  Option<String>,
+  /// Working directory the command would run in.
+  pub cwd:      PathBuf,
+  /// Environment overrides the command would run with.
+  pub env:      BTreeMap<String, String>,
+  /// Nesting level, as for [`TaskRun::depth`].
+  pub depth:    usize,
+  /// Whether the command would be skipped because of a cached result.
+  pub cached:   bool,
+  /// Whether the name matched no Deno task, Node script or hook and fell
+  /// through to being run as a shell command.
+  pub fallback: bool,
 }
 
 /// Captured output from a task execution, used primarily by the TUI dashboard.
@@ -0,0 +1,281 @@
+//! Server-side hooks in bare repositories.
+//!
+//! A bare repository has no working tree to find a configuration file in or
+//! to run tasks from. Instead, `huk` reads its configuration from the
+//! location named by the `HUK_CONFIG` environment variable or the
+//! `huk.config` Git setting, which is either `<rev>:<path>` for a file
+//! committed to the repository (such as `HEAD:package.json`) or a path
+//! relative to the Git directory. Without either, a `deno.json`,
+//! `deno.jsonc` or `package.json` in the Git directory is used, and then one
+//! committed at `HEAD`.
+//!
+//! Tasks run in a temporary checkout of the pushed tree, which is written
+//! through a throwaway index so that neither the repository's refs nor its
+//! index are touched, and which works inside the quarantine environment of
+//! `pre-receive`. The configuration comes from the server side rather than
+//! from what is being pushed, and is written over the pushed file of the
+//! same name.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use thiserror::Error;
+
+use crate::config::ConfigError;
+use crate::config::HookConfig;
+use crate::git::git_output;
+use crate::refs::RefInput;
+use crate::runner::TaskRunner;
+
+/// Configuration files looked for when no location is configured, in order.
+const CONFIG_FILES: [&str; 3] = ["deno.json", "deno.jsonc", "package.json"];
+
+/// Errors that may occur while preparing a server-side hook.
+#[derive(Error, Debug)]
+pub enum ServerError {
+  /// Failed to load the configuration.
+  #[error(transparent)]
+  Config(#[from] ConfigError),
+  /// No configuration location is set and none of the default files exist.
+  #[error(
+    "no configuration found for the bare repository {0}; set `git config \
+     huk.config <rev>:<path>` or add a deno.json or package.json"
+  )]
+  NoConfig(PathBuf),
+  /// A Git command failed.
+  #[error("`git {command}` failed: {detail}")]
+  Git { command: String, detail: String },
+  /// An underlying I/O error occurred.
+  #[error(transparent)]
+  Io(#[from] io::Error),
+}
+
+/// Where the configuration of a bare repository is read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigLocation {
+  /// A file committed to the repository, as `<rev>:<path>`.
+  Blob { rev: String, path: String },
+  /// A file on disk, relative to the Git directory unless absolute.
+  File(PathBuf),
+}
+
+impl ConfigLocation {
+  /// Parse a `huk.config` value: `<rev>:<path>` names a committed file and
+  /// anything else a file on disk.
+  pub fn parse(value: &str) -> Self {
+    match value.split_once(':') {
+      Some((rev, path)) if !rev.is_empty() && !path.is_empty() => Self::Blob {
+        rev:  rev.to_string(),
+        path: path.to_string(),
+      },
+      _ => Self::File(PathBuf::from(value)),
+    }
+  }
+
+  /// The configured location for the repository at `git_dir`, or the first
+  /// default file that exists.
+  pub fn find(git_dir: &Path) -> Result<Self, ServerError> {
+    let configured = env::var("HUK_CONFIG")
+      .ok()
+      .or_else(|| git_output(git_dir, &["config", "--get", "huk.config"]))
+      .filter(|value| !value.trim().is_empty());
+    if let Some(value) = configured {
+      return Ok(Self::parse(value.trim()));
+    }
+    if let Some(name) = CONFIG_FILES.iter().find(|f| git_dir.join(f).is_file())
+    {
+      return Ok(Self::File(PathBuf::from(name)));
+    }
+    CONFIG_FILES
+      .iter()
+      .find(|name| {
+        git_output(git_dir, &["cat-file", "-e", &format!("HEAD:{name}")])
+          .is_some()
+      })
+      .map(|name| Self::Blob {
+        rev:  "HEAD".to_string(),
+        path: name.to_string(),
+      })
+      .ok_or_else(|| ServerError::NoConfig(git_dir.to_path_buf()))
+  }
+
+  /// Read the configuration and write it to `dir`, at its path within the
+  /// repository for committed files and under its own name otherwise.
+  /// Returns the path it was written to.
+  fn materialize(
+    &self,
+    git_dir: &Path,
+    dir: &Path,
+  ) -> Result<PathBuf, ServerError> {
+    let (content, target) = match self {
+      Self::Blob { rev, path } => {
+        let object = format!("{rev}:{path}");
+        (
+          git(git_dir, &["cat-file", "blob", &object])?,
+          dir.join(path),
+        )
+      }
+      Self::File(path) => {
+        let source = git_dir.join(path);
+        let content =
+          fs::read(&source).map_err(|e| ConfigError::Io(source.clone(), e))?;
+        let name = path.file_name().unwrap_or(path.as_os_str());
+        (content, dir.join(name))
+      }
+    };
+    if let Some(parent) = target.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    fs::write(&target, content)?;
+    Ok(target)
+  }
+}
+
+/// A temporary directory holding a checkout of a pushed commit, removed
+/// when dropped.
+#[derive(Debug)]
+pub struct Checkout {
+  /// The work tree the hook's tasks run in.
+  pub dir: PathBuf,
+  /// The private directory created for the checkout, holding `dir` and the
+  /// index used to fill it.
+  root:    PathBuf,
+}
+
+impl Checkout {
+  /// Check out `commit` from the repository at `git_dir`, or create an
+  /// empty directory when there is no commit (as when every update deletes
+  /// its ref).
+  pub fn create(
+    git_dir: &Path,
+    commit: Option<&str>,
+  ) -> Result<Self, ServerError> {
+    let root = create_private_dir()?;
+    let checkout = Self {
+      dir: root.join("tree"),
+      root,
+    };
+    fs::create_dir(&checkout.dir)?;
+    if let Some(commit) = commit {
+      let work_tree = format!("--work-tree={}", checkout.dir.display());
+      let out = Command::new("git")
+        .args([&work_tree, "read-tree", "--reset", "-u", commit])
+        .current_dir(git_dir)
+        .env("GIT_INDEX_FILE", checkout.root.join("index"))
+        .output()?;
+      if !out.status.success() {
+        return Err(ServerError::Git {
+          command: format!("read-tree {commit}"),
+          detail:  String::from_utf8_lossy(&out.stderr).trim().to_string(),
+        });
+      }
+    }
+    Ok(checkout)
+  }
+}
+
+impl Drop for Checkout {
+  fn drop(&mut self) {
+    let _ = fs::remove_dir_all(&self.root);
+  }
+}
+
+/// Create a new directory in the system temporary directory that only the
+/// current user can access. The name is never reused: creation fails rather
+/// than adopting a directory that already exists, and a fresh name is tried
+/// instead.
+fn create_private_dir() -> io::Result<PathBuf> {
+  let mut builder = fs::DirBuilder::new();
+  #[cfg(unix)]
+  std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+  let mut attempt = 0u32;
+  loop {
+    let nanos = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|d| d.subsec_nanos())
+      .unwrap_or_default();
+    let name = format!("huk-{}-{nanos:09}-{attempt}", std::process::id());
+    let dir = env::temp_dir().join(name);
+    match builder.create(&dir) {
+      Ok(()) => return Ok(dir),
+      Err(err)
+        if err.kind() == io::ErrorKind::AlreadyExists && attempt < 100 =>
+      {
+        attempt += 1;
+      }
+      Err(err) => return Err(err),
+    }
+  }
+}
+
+/// A server-side hook ready to run: the configuration and the checkout its
+/// tasks run in.
+#[derive(Debug)]
+pub struct ServerHook {
+  pub config:   HookConfig,
+  pub checkout: Checkout,
+  /// The absolute path of the bare repository.
+  pub git_dir:  PathBuf,
+}
+
+impl ServerHook {
+  /// Run the tasks of `runner` in the checkout, with `GIT_DIR` pointing at
+  /// the bare repository so that Git commands in them still work.
+  pub fn configure(&self, runner: &mut TaskRunner) {
+    runner.cwd = self.checkout.dir.clone();
+    runner
+      .env
+      .insert("GIT_DIR".to_string(), self.git_dir.display().to_string());
+  }
+}
+
+/// Whether the repository containing `cwd` is bare.
+pub fn is_bare(cwd: &Path) -> bool {
+  git_output(cwd, &["rev-parse", "--is-bare-repository"])
+    .is_some_and(|out| out == "true")
+}
+
+/// Prepare a hook in the bare repository at `cwd`: check out the tip of the
+/// first update in `refs` that does not delete its ref, and load the
+/// configuration into it.
+pub fn prepare(cwd: &Path, refs: &RefInput) -> Result<ServerHook, ServerError> {
+  let git_dir = crate::git::git_dir(cwd)?;
+  let git_dir = fs::canonicalize(&git_dir).unwrap_or(git_dir);
+  let commit = refs
+    .updates
+    .iter()
+    .find(|update| !update.is_deletion())
+    .map(|update| update.new.as_str());
+  let checkout = Checkout::create(&git_dir, commit)?;
+  let path =
+    ConfigLocation::find(&git_dir)?.materialize(&git_dir, &checkout.dir)?;
+  Ok(ServerHook {
+    config: HookConfig::load(&path)?,
+    checkout,
+    git_dir,
+  })
+}
+
+/// Load the configuration of the bare repository at `cwd`, as used by
+/// `huk install`.
+pub fn load_config(cwd: &Path) -> Result<HookConfig, ServerError> {
+  Ok(prepare(cwd, &RefInput::default())?.config)
+}
+
+/// Run `git` in `dir` and return its raw stdout.
+fn git(dir: &Path, args: &[&str]) -> Result<Vec<u8>, ServerError> {
+  let out = Command::new("git").args(args).current_dir(dir).output()?;
+  if !out.status.success() {
+    return Err(ServerError::Git {
+      command: args.join(" "),
+      detail:  String::from_utf8_lossy(&out.stderr).trim().to_string(),
+    });
+  }
+  Ok(out.stdout)
+}
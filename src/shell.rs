@@ -0,0 +1,153 @@
+//! POSIX shell quoting and word splitting.
+//!
+//! Every place in `huk` that composes a command line for `sh -c` (forwarding
+//! Git's hook arguments to a raw command, or baking the `huk` binary path into
+//! an installed wrapper script) goes through [`quote`] or [`join`], so that
+//! arguments containing quotes, `$`, backticks, `;`, newlines or glob
+//! characters reach the target program verbatim instead of being interpreted
+//! by the shell.
+//!
+//! [`split`] performs the inverse operation for tasks that opt out of the
+//! shell entirely (`"shell": false`): the command string is broken into argv
+//! entries using the same quoting rules, and Git's arguments are appended as
+//! separate entries without ever being re-parsed.
+
+use std::borrow::Cow;
+
+use thiserror::Error;
+
+/// Errors that may occur while splitting a command string into words.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SplitError {
+  /// A single or double quoted section was never closed.
+  #[error("unterminated {0} quote in command '{1}'")]
+  UnterminatedQuote(char, String),
+  /// The command ended with a dangling backslash.
+  #[error("trailing backslash in command '{0}'")]
+  TrailingBackslash(String),
+  /// The command did not contain any words.
+  #[error("command is empty")]
+  Empty,
+}
+
+/// Returns `true` if `c` never needs quoting in a POSIX shell word.
+const fn is_safe_char(c: char) -> bool {
+  c.is_ascii_alphanumeric()
+    || matches!(c, '_' | '-' | '.' | '/' | ':' | ',' | '+' | '=' | '@' | '%')
+}
+
+/// Quote a single argument so that a POSIX shell reads it back as exactly one
+/// word with the original contents.
+///
+/// Strings made up solely of characters that carry no special meaning are
+/// returned unchanged. Everything else is wrapped in single quotes, inside
+/// which the shell performs no expansion at all; embedded single quotes are
+/// written as `'\''` (close the quote, emit an escaped quote, reopen).
+pub fn quote(s: &str) -> Cow<'_, str> {
+  if !s.is_empty() && s.chars().all(is_safe_char) {
+    return Cow::Borrowed(s);
+  }
+  let mut out = String::with_capacity(s.len() + 2);
+  out.push('\'');
+  for c in s.chars() {
+    if c == '\'' {
+      out.push_str("'\\''");
+    } else {
+      out.push(c);
+    }
+  }
+  out.push('\'');
+  Cow::Owned(out)
+}
+
+/// Quote each argument with [`quote`] and join them with single spaces.
+pub fn join<I, S>(args: I) -> String
+where
+  I: IntoIterator<Item = S>,
+  S: AsRef<str>,
+{
+  args
+    .into_iter()
+    .map(|arg| quote(arg.as_ref()).into_owned())
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// Split a command string into words following POSIX quoting rules.
+///
+/// Single quotes preserve everything literally, double quotes preserve
+/// everything except `\` before `"`, `\`, `$`, `` ` `` or a newline, and an
+/// unquoted backslash escapes the following character. No expansion of
+/// variables, globs or command substitutions is performed; those require a
+/// shell and are therefore unavailable to tasks that opt out of one.
+pub fn split(s: &str) -> Result<Vec<String>, SplitError> {
+  let mut words = Vec::new();
+  let mut current = String::new();
+  let mut in_word = false;
+  let mut chars = s.chars();
+
+  while let Some(c) = chars.next() {
+    match c {
+      ' ' | '\t' | '\n' => {
+        if in_word {
+          words.push(std::mem::take(&mut current));
+          in_word = false;
+        }
+      }
+      '\'' => {
+        in_word = true;
+        loop {
+          match chars.next() {
+            Some('\'') => break,
+            Some(ch) => current.push(ch),
+            None => {
+              return Err(SplitError::UnterminatedQuote('\'', s.to_string()));
+            }
+          }
+        }
+      }
+      '"' => {
+        in_word = true;
+        loop {
+          match chars.next() {
+            Some('"') => break,
+            Some('\\') => match chars.next() {
+              Some(ch @ ('"' | '\\' | '$' | '`')) => current.push(ch),
+              Some('\n') => {}
+              Some(ch) => {
+                current.push('\\');
+                current.push(ch);
+              }
+              None => {
+                return Err(SplitError::UnterminatedQuote('"', s.to_string()));
+              }
+            },
+            Some(ch) => current.push(ch),
+            None => {
+              return Err(SplitError::UnterminatedQuote('"', s.to_string()));
+            }
+          }
+        }
+      }
+      '\\' => match chars.next() {
+        Some('\n') => {}
+        Some(ch) => {
+          in_word = true;
+          current.push(ch);
+        }
+        None => return Err(SplitError::TrailingBackslash(s.to_string())),
+      },
+      ch => {
+        in_word = true;
+        current.push(ch);
+      }
+    }
+  }
+  if in_word {
+    words.push(current);
+  }
+  if words.is_empty() {
+    return Err(SplitError::Empty);
+  }
+  Ok(words)
+}
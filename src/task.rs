@@ -1,14 +1,19 @@
 //! Task specification structures.
 //!
 //! This module defines the [`TaskSpec`] type which represents the parsed
-//! definition of a task or set of tasks as found in the `hooks` section of
-//! either `deno.json`/`deno.jsonc` or `package.json`. A task specification may
-//! be a single string referencing a task name or shell command, an object
-//! describing the command, description and dependencies, or an array of either
-//! of those two forms.
+//! definition of a task or set of tasks as found in the `hooks` section (or
+//! the `huk.tasks` map) of either `deno.json`/`deno.jsonc` or `package.json`. A
+//! task specification may be a single string referencing a task name or shell
+//! command, an object describing the command, description and dependencies, or
+//! an array of either of those two forms.
+//!
+//! Task names inside a specification (bare strings and `dependencies`
+//! entries) are parsed into a [`TaskRef`], which may carry an explicit
+//! `task:`, `script:`, `hook:`, `sh:` or `huk:` prefix.
 
 use core::any::type_name_of_val;
 use core::str::FromStr;
+use std::collections::BTreeMap;
 
 use derive_more::with_trait::Debug;
 use derive_more::with_trait::Display;
@@ -27,18 +32,10 @@ pub enum TaskSpec {
   /// A bare string representing a script or command to execute.
   #[display("{_0}")]
   Single(String),
-  /// A detailed object specification containing a command and/or dependencies.
-  #[display("{command}{info}{deps}", command = if let Some(cmd) = command { format!("{cmd}\n") } else { "".to_string() }, info = if let Some(desc) = description { format!("   // {desc}\n") } else { "".to_string() }, deps = if !dependencies.is_empty() { format!("   depends on: {}", dependencies.join(", ")) } else { "".to_string() })]
-  Detailed {
-    /// Optional shell command to run. If absent, at least one dependency must
-    /// be provided.
-    command:      Option<String>,
-    /// Optional description for display purposes.
-    description:  Option<String>,
-    /// Names of tasks that this task depends on. These will be executed prior
-    /// to this task.
-    dependencies: Vec<String>,
-  },
+  /// A detailed object specification containing a command and/or
+  /// dependencies, boxed since it is much larger than the other variants.
+  #[display("{_0}")]
+  Detailed(Box<TaskObject>),
 
   /// A sequence of tasks. Each element may itself be either a single string or
   /// a detailed object.
@@ -52,6 +49,47 @@ pub enum TaskSpec {
   Sequence(Vec<TaskSpec>),
 }
 
+/// The object form of a task specification.
+#[derive(Display, Clone, PartialEq, Eq)]
+#[display("{command}{info}{deps}", command = if let Some(cmd) = command.as_ref().or(task.as_ref()) { format!("{cmd}\n") } else { "".to_string() }, info = if let Some(desc) = description { format!("   // {desc}\n") } else { "".to_string() }, deps = if !dependencies.is_empty() { format!("   depends on: {}", dependencies.join(", ")) } else { "".to_string() })]
+pub struct TaskObject {
+  /// Optional shell command to run. If absent, a `task` or at least one
+  /// dependency must be provided.
+  pub command:      Option<String>,
+  /// Reference to a task, script or hook to run instead of a command,
+  /// resolved like a bare string entry.
+  pub task:         Option<String>,
+  /// Arguments passed to the command or task, ahead of any arguments
+  /// forwarded from Git.
+  pub args:         Vec<String>,
+  /// Whether the arguments Git passes to the hook are forwarded to the
+  /// command or task (the default).
+  pub forward_args: bool,
+  /// Optional description for display purposes.
+  pub description:  Option<String>,
+  /// Names of tasks that this task depends on. These will be executed prior
+  /// to this task.
+  pub dependencies: Vec<String>,
+  /// Run the `dependencies` concurrently rather than one after another.
+  pub parallel:     bool,
+  /// Whether the command is run through `sh -c` (the default). When false,
+  /// the command is split into words and executed directly, with any
+  /// arguments forwarded from Git appended as separate argv entries.
+  pub shell:        bool,
+  /// Environment variables set for the command and its dependencies.
+  pub env:          BTreeMap<String, String>,
+  /// Working directory for the command and its dependencies, relative to
+  /// the directory of the configuration file.
+  pub cwd:          Option<String>,
+  /// Glob patterns naming the files this task reads. When present, a
+  /// successful run is cached and skipped until one of the inputs (or the
+  /// command, environment or forwarded arguments) changes.
+  pub inputs:       Vec<String>,
+  /// Conditions on the refs being pushed or received under which the task
+  /// runs; it always runs when empty.
+  pub when:         Condition,
+}
+
 impl std::fmt::Debug for TaskSpec {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     write!(f, "{self}")
@@ -64,22 +102,65 @@ impl TaskSpec {
   pub fn to_json(&self) -> Value {
     match self {
       TaskSpec::Single(s) => Value::String(s.clone()),
-      TaskSpec::Detailed {
-        command,
-        description,
-        dependencies,
-      } => {
+      TaskSpec::Detailed(object) => {
+        let TaskObject {
+          command,
+          task,
+          args,
+          forward_args,
+          description,
+          dependencies,
+          parallel,
+          shell,
+          env,
+          cwd,
+          inputs,
+          when,
+        } = object.as_ref();
         let mut map = serde_json::Map::new();
         if let Some(cmd) = command {
           map.insert("command".into(), Value::String(cmd.clone()));
         }
+        if let Some(task) = task {
+          map.insert("task".into(), Value::String(task.clone()));
+        }
+        if !args.is_empty() {
+          let args = args.iter().cloned().map(Value::String).collect();
+          map.insert("args".into(), Value::Array(args));
+        }
+        if !forward_args {
+          map.insert("forward_args".into(), Value::Bool(false));
+        }
         if !dependencies.is_empty() {
           let deps = dependencies.iter().cloned().map(Value::String).collect();
           map.insert("dependencies".into(), Value::Array(deps));
         }
+        if *parallel {
+          map.insert("parallel".into(), Value::Bool(true));
+        }
         if let Some(desc) = description {
           map.insert("description".into(), Value::String(desc.clone()));
         }
+        if !shell {
+          map.insert("shell".into(), Value::Bool(false));
+        }
+        if !env.is_empty() {
+          let env = env
+            .iter()
+            .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+            .collect();
+          map.insert("env".into(), Value::Object(env));
+        }
+        if let Some(cwd) = cwd {
+          map.insert("cwd".into(), Value::String(cwd.clone()));
+        }
+        if !inputs.is_empty() {
+          let inputs = inputs.iter().cloned().map(Value::String).collect();
+          map.insert("inputs".into(), Value::Array(inputs));
+        }
+        if !when.is_empty() {
+          map.insert("when".into(), when.to_json());
+        }
         Value::Object(map)
       }
       TaskSpec::Sequence(list) => {
@@ -89,6 +170,7 @@ impl TaskSpec {
     }
   }
 
+  #[allow(clippy::inherent_to_string_shadow_display)]
   pub fn to_string(&self) -> String {
     serde_json::to_string(&self.to_json()).unwrap_or_default()
   }
@@ -104,14 +186,49 @@ pub enum TaskSpecParseError {
   /// The JSON value was of a type not supported for tasks.
   #[error("expected string, object or array but found {0}")]
   InvalidType(String),
-  /// A task object did not specify a `command` or any `dependencies`.
+  /// A task object did not specify a `command`, a `task` or any
+  /// `dependencies`.
   #[error(
-    "object must specify either a 'command' or at least one 'dependencies' entry"
+    "object must specify a 'command', a 'task' or at least one \
+     'dependencies' entry"
   )]
   MissingCommandAndDeps,
+  /// A task object specified both a `command` and a `task`.
+  #[error("object cannot specify both a 'command' and a 'task'")]
+  CommandAndTask,
+  /// The `task` field was present but not a string.
+  #[error("'task' must be a string")]
+  InvalidTaskType,
+  /// The `args` field was not an array of strings.
+  #[error("'args' must be an array of strings")]
+  InvalidArgsType,
+  /// The `forward_args` field was present but not a boolean.
+  #[error("'forward_args' must be a boolean")]
+  InvalidForwardArgsType,
   /// A dependency entry was not a string.
   #[error("dependencies must be strings")]
   InvalidDependencyType,
+  /// The `shell` field was present but not a boolean.
+  #[error("'shell' must be a boolean")]
+  InvalidShellType,
+  /// The `env` field was not an object of string values.
+  #[error("'env' must be an object mapping names to string values")]
+  InvalidEnvType,
+  /// The `inputs` field was not a string or an array of strings.
+  #[error("'inputs' must be a glob pattern or an array of glob patterns")]
+  InvalidInputsType,
+  /// The `parallel` field was present but not a boolean.
+  #[error("'parallel' must be a boolean")]
+  InvalidParallelType,
+  /// The `cwd` field was present but not a string.
+  #[error("'cwd' must be a string")]
+  InvalidCwdType,
+  /// The `when` field was not an object of known conditions.
+  #[error(
+    "'when' must be an object with 'branches' (a pattern or an array of \
+     patterns) and/or 'deleted' (a boolean)"
+  )]
+  InvalidWhenType,
 }
 
 impl TaskSpec {
@@ -140,14 +257,95 @@ impl TaskSpec {
         } else if let Some(Value::String(name)) = deps_value {
           dependencies.push(name.clone());
         }
-        if command.is_none() && dependencies.is_empty() {
+        let task = match map.get("task") {
+          None | Some(Value::Null) => None,
+          Some(Value::String(task)) => Some(task.clone()),
+          Some(_) => return Err(TaskSpecParseError::InvalidTaskType),
+        };
+        if command.is_some() && task.is_some() {
+          return Err(TaskSpecParseError::CommandAndTask);
+        }
+        if command.is_none() && task.is_none() && dependencies.is_empty() {
           return Err(TaskSpecParseError::MissingCommandAndDeps);
         }
-        Ok(TaskSpec::Detailed {
+        let mut args = Vec::new();
+        match map.get("args") {
+          None | Some(Value::Null) => {}
+          Some(Value::Array(values)) => {
+            for value in values {
+              let Value::String(arg) = value else {
+                return Err(TaskSpecParseError::InvalidArgsType);
+              };
+              args.push(arg.clone());
+            }
+          }
+          Some(_) => return Err(TaskSpecParseError::InvalidArgsType),
+        }
+        let forward_args = match map.get("forward_args") {
+          None | Some(Value::Null) => true,
+          Some(Value::Bool(b)) => *b,
+          Some(_) => return Err(TaskSpecParseError::InvalidForwardArgsType),
+        };
+        let parallel = match map.get("parallel") {
+          None | Some(Value::Null) => false,
+          Some(Value::Bool(b)) => *b,
+          Some(_) => return Err(TaskSpecParseError::InvalidParallelType),
+        };
+        let cwd = match map.get("cwd") {
+          None | Some(Value::Null) => None,
+          Some(Value::String(cwd)) => Some(cwd.clone()),
+          Some(_) => return Err(TaskSpecParseError::InvalidCwdType),
+        };
+        let shell = match map.get("shell") {
+          None | Some(Value::Null) => true,
+          Some(Value::Bool(b)) => *b,
+          Some(_) => return Err(TaskSpecParseError::InvalidShellType),
+        };
+        let mut env = BTreeMap::new();
+        match map.get("env") {
+          None | Some(Value::Null) => {}
+          Some(Value::Object(vars)) => {
+            for (key, value) in vars {
+              let Value::String(value) = value else {
+                return Err(TaskSpecParseError::InvalidEnvType);
+              };
+              env.insert(key.clone(), value.clone());
+            }
+          }
+          Some(_) => return Err(TaskSpecParseError::InvalidEnvType),
+        }
+        let mut inputs = Vec::new();
+        match map.get("inputs") {
+          None | Some(Value::Null) => {}
+          Some(Value::String(pattern)) => inputs.push(pattern.clone()),
+          Some(Value::Array(patterns)) => {
+            for pattern in patterns {
+              let Value::String(pattern) = pattern else {
+                return Err(TaskSpecParseError::InvalidInputsType);
+              };
+              inputs.push(pattern.clone());
+            }
+          }
+          Some(_) => return Err(TaskSpecParseError::InvalidInputsType),
+        }
+        let when = match map.get("when") {
+          None | Some(Value::Null) => Condition::default(),
+          Some(value) => Condition::from_json(value)?,
+        };
+        Ok(TaskSpec::Detailed(Box::new(TaskObject {
           command,
+          task,
+          args,
+          forward_args,
           description,
           dependencies,
-        })
+          parallel,
+          shell,
+          env,
+          cwd,
+          inputs,
+          when,
+        })))
       }
       Value::Array(arr) => {
         let mut seq = Vec::with_capacity(arr.len());
@@ -163,6 +361,75 @@ impl TaskSpec {
   }
 }
 
+/// Conditions restricting when a task object runs, checked against the refs
+/// a hook such as `pre-push` or `pre-receive` updates. For hooks that update
+/// no refs, `branches` is matched against the current branch instead.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Condition {
+  /// Glob patterns of branch names (such as `main` or `release/*`). The task
+  /// runs only if one of the updated branches matches.
+  pub branches: Vec<String>,
+  /// `false` skips updates that delete a ref and `true` considers only
+  /// those; deletions count like any other update when unset.
+  pub deleted:  Option<bool>,
+}
+
+impl Condition {
+  /// Whether no condition is set, so the task always runs.
+  pub fn is_empty(&self) -> bool {
+    self.branches.is_empty() && self.deleted.is_none()
+  }
+
+  /// Parse the `when` field of a task object.
+  fn from_json(value: &Value) -> Result<Self, TaskSpecParseError> {
+    let Value::Object(map) = value else {
+      return Err(TaskSpecParseError::InvalidWhenType);
+    };
+    let mut condition = Self::default();
+    for (key, value) in map {
+      match (key.as_str(), value) {
+        ("branches", Value::String(pattern)) => {
+          condition.branches.push(pattern.clone())
+        }
+        ("branches", Value::Array(patterns)) => {
+          for pattern in patterns {
+            let Value::String(pattern) = pattern else {
+              return Err(TaskSpecParseError::InvalidWhenType);
+            };
+            condition.branches.push(pattern.clone());
+          }
+        }
+        ("deleted", Value::Bool(deleted)) => condition.deleted = Some(*deleted),
+        (_, Value::Null) => {}
+        _ => return Err(TaskSpecParseError::InvalidWhenType),
+      }
+    }
+    Ok(condition)
+  }
+
+  fn to_json(&self) -> Value {
+    let mut map = serde_json::Map::new();
+    if !self.branches.is_empty() {
+      let branches = self.branches.iter().cloned().map(Value::String).collect();
+      map.insert("branches".into(), Value::Array(branches));
+    }
+    if let Some(deleted) = self.deleted {
+      map.insert("deleted".into(), Value::Bool(deleted));
+    }
+    Value::Object(map)
+  }
+
+  /// Whether `branch` matches one of the `branches` patterns, or any branch
+  /// when there are none.
+  pub fn matches_branch(&self, branch: &str) -> bool {
+    self.branches.is_empty()
+      || self.branches.iter().any(|pattern| {
+        glob::Pattern::new(pattern)
+          .map_or(pattern == branch, |glob| glob.matches(branch))
+      })
+  }
+}
+
 impl From<&TaskSpec> for Value {
   #[inline(always)]
   fn from(spec: &TaskSpec) -> Self {
@@ -212,3 +479,44 @@ impl TryFrom<&str> for TaskSpec {
     s.parse()
   }
 }
+
+/// A reference to something runnable, as written in a bare string entry or a
+/// `dependencies` list. A prefix selects the kind explicitly; without one the
+/// name is looked up as a `huk.tasks` entry, Deno task, Node script and hook,
+/// in that order, and run as a shell command if none match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, IsVariant)]
+pub enum TaskRef<'a> {
+  /// `task:<name>`: a task from the `huk.tasks` map or the Deno `tasks`
+  /// field.
+  Task(&'a str),
+  /// `script:<name>`: a script from the Node `scripts` field.
+  Script(&'a str),
+  /// `hook:<name>`: another hook definition.
+  Hook(&'a str),
+  /// `sh:<command>`: a shell command, never looked up as a name.
+  Shell(&'a str),
+  /// `huk:<name>`: a task built into `huk`, such as `huk:conventional`.
+  Builtin(&'a str),
+  /// A name without a prefix.
+  Bare(&'a str),
+}
+
+impl<'a> TaskRef<'a> {
+  /// Parse a reference, stripping its prefix if it has one. Whitespace after
+  /// the prefix is ignored.
+  pub fn parse(reference: &'a str) -> Self {
+    let reference = reference.trim();
+    let Some((prefix, rest)) = reference.split_once(':') else {
+      return TaskRef::Bare(reference);
+    };
+    let rest = rest.trim_start();
+    match prefix {
+      "task" => TaskRef::Task(rest),
+      "script" => TaskRef::Script(rest),
+      "hook" => TaskRef::Hook(rest),
+      "sh" => TaskRef::Shell(rest),
+      "huk" => TaskRef::Builtin(rest),
+      _ => TaskRef::Bare(reference),
+    }
+  }
+}
@@ -1 +1,16 @@
+mod builtins_test;
+mod cache_test;
 mod config_test;
+mod doctor_test;
+mod history_test;
+mod init_test;
+mod install_test;
+mod package_manager_test;
+mod plan_test;
+mod refs_test;
+mod report_test;
+mod resolve_test;
+mod server_test;
+mod shell_test;
+mod support;
+mod tasks_test;
@@ -0,0 +1,239 @@
+use crate::cache::CacheInputs;
+use crate::cache::CacheStats;
+use crate::cache::CacheStore;
+use crate::cache::compute_key;
+use crate::config::HookConfig;
+use crate::runner::TaskKind;
+use crate::runner::TaskRunner;
+use crate::task::TaskSpec;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs;
+use std::time::Duration;
+use std::time::UNIX_EPOCH;
+use tempfile::tempdir;
+
+fn key_for(base: &std::path::Path, command: &str, patterns: &[&str]) -> String {
+  let env = BTreeMap::new();
+  let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+  compute_key(
+    base,
+    &CacheInputs {
+      command,
+      kind: TaskKind::Command,
+      body: command,
+      cwd: base,
+      shell: true,
+      env: &env,
+      args: &[],
+      patterns: &patterns,
+    },
+  )
+  .unwrap()
+}
+
+#[test]
+fn key_is_stable_for_unchanged_inputs() {
+  let dir = tempdir().unwrap();
+  fs::create_dir(dir.path().join("src")).unwrap();
+  fs::write(dir.path().join("src/a.rs"), "fn a() {}").unwrap();
+  fs::write(dir.path().join("src/b.rs"), "fn b() {}").unwrap();
+  let first = key_for(dir.path(), "cargo test", &["src/**/*.rs"]);
+  let second = key_for(dir.path(), "cargo test", &["src/*.rs", "src/a.rs"]);
+  assert_eq!(first, second);
+  assert_eq!(first.len(), 64);
+}
+
+#[test]
+fn key_changes_with_file_contents() {
+  let dir = tempdir().unwrap();
+  fs::write(dir.path().join("input.txt"), "one").unwrap();
+  let before = key_for(dir.path(), "cat input.txt", &["*.txt"]);
+  fs::write(dir.path().join("input.txt"), "two").unwrap();
+  let after = key_for(dir.path(), "cat input.txt", &["*.txt"]);
+  assert_ne!(before, after);
+}
+
+#[test]
+fn key_changes_with_new_matching_file() {
+  let dir = tempdir().unwrap();
+  fs::write(dir.path().join("a.txt"), "a").unwrap();
+  let before = key_for(dir.path(), "true", &["*.txt"]);
+  fs::write(dir.path().join("b.txt"), "").unwrap();
+  assert_ne!(before, key_for(dir.path(), "true", &["*.txt"]));
+}
+
+#[test]
+fn key_changes_with_command_env_and_args() {
+  let dir = tempdir().unwrap();
+  let base = key_for(dir.path(), "tsc", &["*.ts"]);
+  assert_ne!(base, key_for(dir.path(), "tsc --noEmit", &["*.ts"]));
+
+  let mut env = BTreeMap::new();
+  env.insert("NODE_ENV".to_string(), "test".to_string());
+  let patterns = vec!["*.ts".to_string()];
+  let args = vec!["origin".to_string()];
+  let inputs = CacheInputs {
+    command:  "tsc",
+    kind:     TaskKind::Command,
+    body:     "tsc",
+    cwd:      dir.path(),
+    shell:    true,
+    env:      &BTreeMap::new(),
+    args:     &[],
+    patterns: &patterns,
+  };
+  let with_env = CacheInputs {
+    env: &env,
+    ..inputs
+  };
+  let with_args = CacheInputs {
+    args: &args,
+    ..inputs
+  };
+  let direct = CacheInputs {
+    shell: false,
+    ..inputs
+  };
+  let nested = dir.path().join("packages/app");
+  let elsewhere = CacheInputs {
+    cwd: &nested,
+    ..inputs
+  };
+  assert_eq!(base, compute_key(dir.path(), &inputs).unwrap());
+  assert_ne!(base, compute_key(dir.path(), &with_env).unwrap());
+  assert_ne!(base, compute_key(dir.path(), &with_args).unwrap());
+  assert_ne!(base, compute_key(dir.path(), &direct).unwrap());
+  assert_ne!(base, compute_key(dir.path(), &elsewhere).unwrap());
+}
+
+#[test]
+fn invalid_pattern_is_an_error() {
+  let dir = tempdir().unwrap();
+  let patterns = vec!["src/[".to_string()];
+  let result = compute_key(
+    dir.path(),
+    &CacheInputs {
+      command:  "true",
+      kind:     TaskKind::Command,
+      body:     "true",
+      cwd:      dir.path(),
+      shell:    true,
+      env:      &BTreeMap::new(),
+      args:     &[],
+      patterns: &patterns,
+    },
+  );
+  assert!(result.is_err());
+}
+
+#[test]
+fn store_records_and_clears_entries() {
+  let dir = tempdir().unwrap();
+  let store = CacheStore::new(dir.path().join("cache"));
+  assert_eq!(store.stats().unwrap(), CacheStats::default());
+  assert!(!store.contains("abc"));
+
+  store.record("abc", "cargo test").unwrap();
+  store.record("def", "tsc").unwrap();
+  assert!(store.contains("abc"));
+  let stats = store.stats().unwrap();
+  assert_eq!(stats.entries, 2);
+  assert!(stats.bytes > 0);
+
+  assert_eq!(store.clear().unwrap(), 2);
+  assert!(!store.contains("abc"));
+  assert_eq!(store.stats().unwrap().entries, 0);
+}
+
+#[test]
+fn parse_task_spec_env_and_inputs() {
+  let v = json!({
+    "command": "cargo test",
+    "env": {"RUST_BACKTRACE": "1"},
+    "inputs": ["src/**/*.rs", "Cargo.lock"]
+  });
+  let spec = TaskSpec::from_json(&v).unwrap();
+  let TaskSpec::Detailed(object) = &spec else {
+    panic!("expected detailed spec");
+  };
+  assert_eq!(
+    object.env.get("RUST_BACKTRACE").map(String::as_str),
+    Some("1")
+  );
+  assert_eq!(object.inputs, ["src/**/*.rs", "Cargo.lock"]);
+  assert_eq!(spec.to_json(), v);
+
+  let single = json!({"command": "tsc", "inputs": "src/*.ts"});
+  let TaskSpec::Detailed(object) = TaskSpec::from_json(&single).unwrap() else {
+    panic!("expected detailed spec");
+  };
+  assert_eq!(object.inputs, vec!["src/*.ts"]);
+
+  assert!(
+    TaskSpec::from_json(&json!({"command": "x", "env": {"A": 1}})).is_err()
+  );
+  assert!(
+    TaskSpec::from_json(&json!({"command": "x", "inputs": [1]})).is_err()
+  );
+}
+
+#[test]
+fn store_prunes_least_recently_used_entries() {
+  let dir = tempdir().unwrap();
+  let store = CacheStore::new(dir.path().join("cache")).with_max_entries(2);
+  let age = |key: &str, secs: u64| {
+    let path = dir.path().join("cache").join(format!("{key}.json"));
+    let file = fs::File::options().append(true).open(path).unwrap();
+    file
+      .set_modified(UNIX_EPOCH + Duration::from_secs(secs))
+      .unwrap();
+  };
+  store.record("old", "a").unwrap();
+  store.record("used", "b").unwrap();
+  age("old", 100);
+  age("used", 50);
+  // A hit makes the entry the most recently used one.
+  assert!(store.contains("used"));
+
+  store.record("new", "c").unwrap();
+  assert!(!store.contains("old"));
+  assert!(store.contains("used") && store.contains("new"));
+  assert_eq!(store.stats().unwrap().entries, 2);
+}
+
+#[test]
+fn task_references_are_cached_by_their_definition() {
+  let dir = tempdir().unwrap();
+  fs::write(dir.path().join("input.txt"), "data").unwrap();
+  let write_config = |command: &str| {
+    let config = json!({
+      "hooks": {"pre-commit": {"task": "build", "inputs": ["input.txt"]}},
+      "huk": {"tasks": {"build": command}}
+    });
+    fs::write(dir.path().join("package.json"), config.to_string()).unwrap();
+    HookConfig::discover(dir.path()).unwrap()
+  };
+  let run = |cfg: &HookConfig| {
+    let mut runner = TaskRunner::new(cfg);
+    runner.cwd = dir.path().to_path_buf();
+    runner.cache = Some(CacheStore::new(dir.path().join("cache")));
+    runner
+      .run_spec(&cfg.hooks["pre-commit"], "pre-commit", &[])
+      .unwrap();
+    runner
+      .runs
+      .last()
+      .map(|run| (run.kind, run.cached))
+      .unwrap()
+  };
+
+  let cfg = write_config("echo one >> log");
+  run(&cfg);
+  assert_eq!(run(&cfg), (TaskKind::HukTask, true));
+
+  let cfg = write_config("echo two >> log");
+  assert!(!run(&cfg).1);
+  let log = fs::read_to_string(dir.path().join("log")).unwrap();
+  assert_eq!(log, "one\ntwo\n");
+}
@@ -18,14 +18,10 @@ fn parse_task_spec_object_with_command() {
   let v = json!({"command": "deno fmt", "description": "Format code"});
   let spec = TaskSpec::from_json(&v).unwrap();
   match spec {
-    TaskSpec::Detailed {
-      command,
-      description,
-      dependencies,
-    } => {
-      assert_eq!(command, Some("deno fmt".into()));
-      assert_eq!(description, Some("Format code".into()));
-      assert!(dependencies.is_empty());
+    TaskSpec::Detailed(object) => {
+      assert_eq!(object.command, Some("deno fmt".into()));
+      assert_eq!(object.description, Some("Format code".into()));
+      assert!(object.dependencies.is_empty());
     }
     _ => panic!("unexpected variant"),
   }
@@ -130,3 +126,155 @@ fn removes_comments_and_preserves_newlines() {
   let parsed: serde_json::Value = serde_json::from_str(&cleaned).unwrap();
   assert_eq!(parsed, json!({ "a": 1, "b": 2 }));
 }
+
+#[test]
+fn parse_task_spec_object_with_task_reference() {
+  let v = json!({"task": "test", "args": ["--bail"], "forward_args": false});
+  let spec = TaskSpec::from_json(&v).unwrap();
+  let TaskSpec::Detailed(object) = &spec else {
+    panic!("expected detailed spec");
+  };
+  assert_eq!(object.task.as_deref(), Some("test"));
+  assert_eq!(object.args, ["--bail"]);
+  assert!(!object.forward_args);
+  assert_eq!(spec.to_json(), v);
+
+  let both = json!({"task": "test", "command": "vitest"});
+  assert_eq!(
+    TaskSpec::from_json(&both).unwrap_err(),
+    TaskSpecParseError::CommandAndTask
+  );
+  let bad_args = json!({"task": "test", "args": "--bail"});
+  assert_eq!(
+    TaskSpec::from_json(&bad_args).unwrap_err(),
+    TaskSpecParseError::InvalidArgsType
+  );
+}
+
+#[test]
+fn deno_tasks_keep_structure_and_workspace_members() {
+  let dir = tempdir().unwrap();
+  fs::write(
+    dir.path().join("deno.json"),
+    r#"{
+      "workspace": ["./api", "./web"],
+      "tasks": {
+        "fmt": "deno fmt",
+        "check": {
+          "description": "Lint and type-check",
+          "dependencies": ["fmt"]
+        }
+      }
+    }"#,
+  )
+  .unwrap();
+  fs::create_dir(dir.path().join("api")).unwrap();
+  fs::write(
+    dir.path().join("api/deno.jsonc"),
+    r#"{
+      // member tasks run in the member directory
+      "tasks": {
+        "serve": {"command": "deno serve main.ts", "dependencies": ["fmt"]},
+        "fmt": "true"
+      }
+    }"#,
+  )
+  .unwrap();
+  fs::create_dir(dir.path().join("web")).unwrap();
+  fs::write(
+    dir.path().join("web/deno.json"),
+    r#"{"tasks": {"serve": "deno run -A dev.ts"}}"#,
+  )
+  .unwrap();
+  let cfg = HookConfig::discover(dir.path()).unwrap();
+
+  let check = &cfg.deno_tasks["check"];
+  assert_eq!(check.command, None);
+  assert_eq!(check.description.as_deref(), Some("Lint and type-check"));
+  assert_eq!(check.dependencies, ["fmt"]);
+  assert_eq!(check.summary(), "(runs fmt)");
+
+  // Member tasks stay scoped to their configuration file.
+  assert!(!cfg.deno_tasks.contains_key("serve"));
+  let serve = &cfg.deno_tasks["api/serve"];
+  assert_eq!(serve.command.as_deref(), Some("deno serve main.ts"));
+  assert_eq!(serve.member.as_deref(), Some("api"));
+  assert_eq!(serve.cwd.as_deref(), Some(dir.path().join("api").as_path()));
+  assert_eq!(serve.name("api/serve"), "serve");
+  assert_eq!(serve.key("fmt"), "api/fmt");
+  assert_eq!(
+    cfg.deno_tasks["web/serve"].command.as_deref(),
+    Some("deno run -A dev.ts")
+  );
+  assert_eq!(cfg.deno_tasks["fmt"].command.as_deref(), Some("deno fmt"));
+  assert_eq!(cfg.deno_tasks["fmt"].cwd, None);
+  assert_eq!(cfg.deno_tasks["api/fmt"].command.as_deref(), Some("true"));
+}
+
+#[test]
+fn task_locations_only_match_direct_children() {
+  let dir = tempdir().unwrap();
+  fs::write(
+    dir.path().join("deno.json"),
+    r#"{
+      "imports": {"tasks": {"fmt": "x"}},
+      "tasks": {
+        "check": {
+          "description": "fmt",
+          "dependencies": ["fmt"]
+        },
+        "fmt": "deno fmt"
+      }
+    }"#,
+  )
+  .unwrap();
+  let cfg = HookConfig::discover(dir.path()).unwrap();
+  let line =
+    |name: &str| cfg.deno_tasks[name].location.as_ref().map(|l| l.line);
+  assert_eq!(line("check"), Some(4));
+  assert_eq!(line("fmt"), Some(8));
+}
+
+#[test]
+fn huk_tasks_define_tasks_and_annotate_scripts() {
+  let dir = tempdir().unwrap();
+  fs::write(
+    dir.path().join("package.json"),
+    r#"{
+      "scripts": {"lint": "eslint ."},
+      "huk": {
+        "tasks": {
+          "lint": {"description": "Lint sources"},
+          "build": {"command": "tsc", "description": "Compile", "cwd": "app"}
+        }
+      }
+    }"#,
+  )
+  .unwrap();
+  let cfg = HookConfig::discover(dir.path()).unwrap();
+  assert_eq!(
+    cfg.script_meta["lint"].description.as_deref(),
+    Some("Lint sources")
+  );
+  assert!(!cfg.huk_tasks.contains_key("lint"));
+  let build = &cfg.huk_tasks["build"];
+  assert_eq!(build.description(), Some("Compile"));
+  assert_eq!(build.command().as_deref(), Some("tsc"));
+  assert_eq!(build.location.as_ref().map(|l| l.line), Some(6));
+  assert_eq!(
+    build.spec.to_json(),
+    json!({"command": "tsc", "description": "Compile", "cwd": "app"})
+  );
+
+  fs::write(
+    dir.path().join("package.json"),
+    r#"{"huk": {"tasks": {"bad": {"parallel": "yes", "command": "true"}}}}"#,
+  )
+  .unwrap();
+  let err = HookConfig::discover(dir.path()).unwrap_err();
+  assert!(matches!(
+    err,
+    crate::config::ConfigError::InvalidTask(name, TaskSpecParseError::InvalidParallelType)
+      if name == "bad"
+  ));
+}
@@ -0,0 +1,91 @@
+use crate::doctor::Check;
+use crate::doctor::Fix;
+use crate::doctor::Status;
+use crate::doctor::diagnose;
+use crate::doctor::format_checks;
+use crate::install::wrapper_script;
+use crate::tests::support::git;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn find<'a>(checks: &'a [Check], prefix: &str) -> &'a Check {
+  checks
+    .iter()
+    .find(|check| check.message.starts_with(prefix))
+    .unwrap_or_else(|| panic!("no check starting with {prefix:?}"))
+}
+
+fn write_hook(path: &Path, script: &str, mode: u32) {
+  fs::write(path, script).unwrap();
+  fs::set_permissions(path, fs::Permissions::from_mode(mode)).unwrap();
+}
+
+#[test]
+fn diagnoses_hook_scripts_and_conflicts() {
+  let dir = tempdir().unwrap();
+  let root = dir.path();
+  git(root, &["init", "-q"]);
+  fs::write(
+    root.join("package.json"),
+    r#"{"hooks": {"pre-commit": "true", "pre-push": "true",
+      "commit-msg": "true", "post-merge": "true"}}"#,
+  )
+  .unwrap();
+  fs::create_dir(root.join(".husky")).unwrap();
+  let hooks = root.join(".git/hooks");
+  let bin = root.join("node_modules/.bin");
+  fs::create_dir_all(&bin).unwrap();
+  write_hook(&bin.join("huk"), "#!/bin/sh\n", 0o755);
+  write_hook(
+    &hooks.join("pre-commit"),
+    &wrapper_script("pre-commit", None, false),
+    0o755,
+  );
+  write_hook(
+    &hooks.join("commit-msg"),
+    &wrapper_script("commit-msg", None, false),
+    0o644,
+  );
+  write_hook(
+    &hooks.join("post-merge"),
+    "#!/bin/sh\n# Auto-generated by huk.\nexec '/nonexistent/huk' run \
+     'post-merge' -- \"$@\"\n",
+    0o755,
+  );
+
+  let checks = diagnose(root);
+  assert_eq!(checks[0].status, Status::Pass);
+  assert!(checks[0].message.starts_with("git "));
+  assert_eq!(find(&checks, "pre-commit").status, Status::Pass);
+  let missing = find(&checks, "pre-push");
+  assert_eq!(
+    (missing.status, &missing.fix),
+    (Status::Fail, &Some(Fix::Install))
+  );
+  let mode = find(&checks, "commit-msg");
+  assert!(mode.message.ends_with("is not executable"));
+  assert_eq!(
+    mode.fix,
+    Some(Fix::MakeExecutable(hooks.join("commit-msg")))
+  );
+  assert!(
+    find(&checks, "post-merge")
+      .message
+      .contains("/nonexistent/huk")
+  );
+  assert_eq!(find(&checks, "hooks run huk as").status, Status::Pass);
+  assert_eq!(find(&checks, "husky").status, Status::Warn);
+
+  let report = format_checks(&checks[..1], false);
+  assert!(report.starts_with("pass  git "));
+  assert!(report.ends_with("\n1 passed check, 0 warnings, 0 failures\n"));
+
+  let outside = tempdir().unwrap();
+  let checks = diagnose(outside.path());
+  assert_eq!(
+    checks.last().unwrap().message,
+    "not inside a Git repository"
+  );
+}
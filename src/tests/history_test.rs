@@ -0,0 +1,148 @@
+use crate::history::HistoryRecord;
+use crate::history::HistoryStore;
+use crate::history::format_duration;
+use crate::history::format_timestamp;
+use crate::runner::TaskKind;
+use crate::runner::TaskRun;
+use std::fs;
+use std::thread;
+use tempfile::tempdir;
+
+fn record(hook: &str, success: bool, output: &str) -> HistoryRecord {
+  HistoryRecord {
+    id: 0,
+    hook: hook.to_string(),
+    args: vec![],
+    started: 1_700_000_000,
+    duration_ms: 42,
+    success,
+    error: None,
+    commit: None,
+    branch: Some("main".into()),
+    tasks: vec![TaskRun {
+      name: "echo hi".into(),
+      command: "echo hi".into(),
+      kind: TaskKind::Command,
+      depth: 0,
+      duration_ms: 42,
+      exit_code: Some(if success { 0 } else { 1 }),
+      success,
+      cached: false,
+      output: output.to_string(),
+    }],
+  }
+}
+
+#[test]
+fn append_assigns_sequential_ids() {
+  let dir = tempdir().unwrap();
+  let store = HistoryStore::new(dir.path().join("history.jsonl"), 1 << 20);
+  assert!(store.load().unwrap().is_empty());
+  assert_eq!(store.append(record("pre-commit", true, "")).unwrap(), 1);
+  assert_eq!(store.append(record("pre-push", false, "boom")).unwrap(), 2);
+
+  let records = store.load().unwrap();
+  assert_eq!(records.len(), 2);
+  assert_eq!(records[1].id, 2);
+  assert_eq!(records[1].hook, "pre-push");
+  assert_eq!(records[1].tasks[0].output, "boom");
+}
+
+#[test]
+fn append_prunes_oldest_records_past_size_limit() {
+  let dir = tempdir().unwrap();
+  let path = dir.path().join("history.jsonl");
+  let store = HistoryStore::new(&path, 1_000);
+  let output = "x".repeat(300);
+  for _ in 0..10 {
+    store.append(record("pre-commit", true, &output)).unwrap();
+  }
+  assert!(fs::metadata(&path).unwrap().len() <= 1_000);
+  let records = store.load().unwrap();
+  assert!(!records.is_empty() && records.len() < 10);
+  // Identifiers keep increasing after pruning.
+  assert_eq!(records.last().unwrap().id, 10);
+}
+
+#[test]
+fn oversized_record_is_kept() {
+  let dir = tempdir().unwrap();
+  let store = HistoryStore::new(dir.path().join("history.jsonl"), 10);
+  store.append(record("pre-commit", true, "first")).unwrap();
+  store.append(record("pre-commit", true, "second")).unwrap();
+  let records = store.load().unwrap();
+  assert_eq!(records.len(), 1);
+  assert_eq!(records[0].tasks[0].output, "second");
+}
+
+#[test]
+fn load_skips_malformed_lines() {
+  let dir = tempdir().unwrap();
+  let path = dir.path().join("history.jsonl");
+  let store = HistoryStore::new(&path, 1 << 20);
+  store.append(record("pre-commit", true, "")).unwrap();
+  let mut content = fs::read_to_string(&path).unwrap();
+  content.push_str("{\"id\": 2, \"hook\": \"trunc");
+  fs::write(&path, content).unwrap();
+  assert_eq!(store.load().unwrap().len(), 1);
+}
+
+#[test]
+fn formats_timestamps_and_durations() {
+  assert_eq!(format_timestamp(0), "1970-01-01 00:00:00");
+  assert_eq!(format_timestamp(951_827_696), "2000-02-29 12:34:56");
+  assert_eq!(format_timestamp(1_700_000_000), "2023-11-14 22:13:20");
+  assert_eq!(format_timestamp(1_735_689_599), "2024-12-31 23:59:59");
+  assert_eq!(format_timestamp(1_735_689_600), "2025-01-01 00:00:00");
+  assert_eq!(format_timestamp(4_107_456_000), "2100-02-28 00:00:00");
+  assert_eq!(format_timestamp(4_107_542_400), "2100-03-01 00:00:00");
+  assert_eq!(format_duration(850), "850ms");
+  assert_eq!(format_duration(3_240), "3.2s");
+  assert_eq!(format_duration(125_000), "2m05s");
+}
+
+#[test]
+fn timestamps_cross_every_month_and_leap_year() {
+  let leap = |y: u64| {
+    y.is_multiple_of(4) && (!y.is_multiple_of(100) || y.is_multiple_of(400))
+  };
+  let mut secs = 0;
+  for year in 1970..2500 {
+    for month in 1..=12u64 {
+      let days = match month {
+        2 if leap(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+      };
+      for day in 1..=days {
+        assert_eq!(
+          format_timestamp(secs),
+          format!("{year:04}-{month:02}-{day:02} 00:00:00")
+        );
+        secs += 86_400;
+      }
+      assert_eq!(
+        format_timestamp(secs - 1),
+        format!("{year:04}-{month:02}-{days:02} 23:59:59")
+      );
+    }
+  }
+}
+
+#[test]
+fn concurrent_appends_get_distinct_ids() {
+  let dir = tempdir().unwrap();
+  let store = HistoryStore::new(dir.path().join("history.jsonl"), 1 << 20);
+  let mut ids: Vec<u64> = thread::scope(|scope| {
+    let handles: Vec<_> = (0..8)
+      .map(|_| {
+        scope.spawn(|| store.append(record("pre-commit", true, "")).unwrap())
+      })
+      .collect();
+    handles.into_iter().map(|h| h.join().unwrap()).collect()
+  });
+  ids.sort_unstable();
+  assert_eq!(ids, (1..=8).collect::<Vec<_>>());
+  assert_eq!(store.load().unwrap().len(), 8);
+}
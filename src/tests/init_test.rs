@@ -0,0 +1,47 @@
+use crate::config::ConfigSource;
+use crate::init::add_auto_install;
+use serde_json::Value;
+use serde_json::json;
+use std::path::PathBuf;
+
+fn object(value: Value) -> serde_json::Map<String, Value> {
+  value.as_object().unwrap().clone()
+}
+
+#[test]
+fn adds_the_prepare_script_once() {
+  let source = ConfigSource::PackageJson(PathBuf::from("package.json"));
+  let mut obj = object(json!({ "name": "app" }));
+  assert!(add_auto_install(&source, &mut obj).is_some());
+  assert_eq!(obj["scripts"]["prepare"], "huk install --auto --quiet");
+  assert_eq!(add_auto_install(&source, &mut obj), None);
+
+  // An existing prepare script keeps running first.
+  let mut obj = object(json!({ "scripts": { "prepare": "tsc" } }));
+  add_auto_install(&source, &mut obj);
+  assert_eq!(
+    obj["scripts"]["prepare"],
+    "tsc && huk install --auto --quiet"
+  );
+  assert_eq!(add_auto_install(&source, &mut obj), None);
+}
+
+#[test]
+fn adds_a_deno_task_using_the_imported_package() {
+  let source = ConfigSource::DenoJson(PathBuf::from("deno.json"));
+  let mut obj = object(json!({ "tasks": { "dev": "deno run main.ts" } }));
+  add_auto_install(&source, &mut obj);
+  assert_eq!(obj["tasks"]["prepare"], "huk install --auto --quiet");
+  assert_eq!(obj["tasks"]["dev"], "deno run main.ts");
+
+  let mut obj = object(json!({
+    "imports": { "huk": "npm:huk@^0.1" },
+    "tasks": { "prepare": { "command": "deno cache main.ts" } }
+  }));
+  add_auto_install(&source, &mut obj);
+  assert_eq!(
+    obj["tasks"]["prepare"]["command"],
+    "deno cache main.ts && deno run -A npm:huk install --auto --quiet"
+  );
+  assert_eq!(add_auto_install(&source, &mut obj), None);
+}
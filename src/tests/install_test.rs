@@ -0,0 +1,439 @@
+use crate::cli::InstallOpts;
+use crate::cli::UninstallOpts;
+use crate::config::HookConfig;
+use crate::install::ExistingHooks;
+use crate::install::backup_path;
+use crate::install::config_hash;
+use crate::install::find_git_hooks_dir;
+use crate::install::install;
+use crate::install::installed_hooks;
+use crate::install::is_ci_value;
+use crate::install::is_huk_script;
+use crate::install::locate_huk;
+use crate::install::restore_hooks_path;
+use crate::install::set_hooks_path;
+use crate::install::sync;
+use crate::install::uninstall;
+use crate::install::wrapper_script;
+use crate::manifest::HooksPath;
+use crate::manifest::InstalledHook;
+use crate::manifest::Manifest;
+use crate::manifest::ManifestStore;
+use crate::manifest::hash_script;
+use crate::tests::support::git;
+use std::fs;
+use std::io::Write;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process::Command;
+use std::process::Stdio;
+use tempfile::tempdir;
+
+fn write_script(path: &Path, script: &str) {
+  fs::write(path, script).unwrap();
+  fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+/// Run the hook at `path` with `input` on standard input and `env` set,
+/// from the directory containing it, returning its exit code and output.
+fn run_hook_with(
+  path: &Path,
+  input: &str,
+  env: &[(&str, &str)],
+) -> (Option<i32>, String) {
+  let mut child = Command::new(path)
+    .arg("origin")
+    .current_dir(path.parent().unwrap())
+    .envs(env.iter().copied())
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .spawn()
+    .unwrap();
+  child
+    .stdin
+    .take()
+    .unwrap()
+    .write_all(input.as_bytes())
+    .unwrap();
+  let out = child.wait_with_output().unwrap();
+  (
+    out.status.code(),
+    String::from_utf8_lossy(&out.stdout).into_owned(),
+  )
+}
+
+/// Run the hook at `path` with `input`, using a fake huk binary that echoes
+/// its arguments.
+fn run_hook(path: &Path, input: &str) -> (Option<i32>, String) {
+  let exe = path.with_file_name("fake huk");
+  write_script(&exe, "#!/bin/sh\necho \"huk $2 $4: $(cat)\"\n");
+  run_hook_with(path, input, &[("HUK_BIN", &exe.display().to_string())])
+}
+
+#[test]
+fn wrapper_replaces_itself_with_huk() {
+  let dir = tempdir().unwrap();
+  let exe = dir.path().join("huk");
+  write_script(&exe, "#!/bin/sh\necho $$\n");
+  let hook = dir.path().join("pre-commit");
+  write_script(&hook, &wrapper_script("pre-commit", None, false));
+  let child = Command::new(&hook)
+    .env("HUK_BIN", &exe)
+    .stdout(Stdio::piped())
+    .spawn()
+    .unwrap();
+  let pid = child.id().to_string();
+  let out = child.wait_with_output().unwrap();
+  assert_eq!(String::from_utf8_lossy(&out.stdout).trim(), pid);
+}
+
+#[test]
+fn chains_the_original_hook_with_its_input() {
+  let dir = tempdir().unwrap();
+  let hook = dir.path().join("pre-push");
+  write_script(
+    &backup_path(&hook),
+    "#!/bin/sh\necho \"original $1: $(cat)\"\nexit \"${FAIL:-0}\"\n",
+  );
+
+  write_script(&hook, &wrapper_script("pre-push", None, false));
+  assert!(is_huk_script(&hook) && !is_huk_script(&backup_path(&hook)));
+  assert_eq!(
+    run_hook(&hook, "refs"),
+    (Some(0), "huk pre-push origin: refs\n".into())
+  );
+
+  let before = wrapper_script("pre-push", Some(ExistingHooks::Before), false);
+  write_script(&hook, &before);
+  assert_eq!(
+    run_hook(&hook, "refs"),
+    (
+      Some(0),
+      "original origin: refs\nhuk pre-push origin: refs\n".into()
+    )
+  );
+  let after = wrapper_script("pre-push", Some(ExistingHooks::After), false);
+  write_script(&hook, &after);
+  assert_eq!(
+    run_hook(&hook, "refs"),
+    (
+      Some(0),
+      "huk pre-push origin: refs\noriginal origin: refs\n".into()
+    )
+  );
+
+  // A failing original stops the hook before huk runs.
+  write_script(
+    &hook,
+    &before.replace("#!/bin/sh\n", "#!/bin/sh\nFAIL=3\nexport FAIL\n"),
+  );
+  assert_eq!(
+    run_hook(&hook, "refs"),
+    (Some(3), "original origin: refs\n".into())
+  );
+
+  // A hook without input runs the original script with the same arguments.
+  let checkout = dir.path().join("post-checkout");
+  fs::copy(backup_path(&hook), backup_path(&checkout)).unwrap();
+  write_script(
+    &checkout,
+    &wrapper_script("post-checkout", Some(ExistingHooks::Before), false),
+  );
+  assert_eq!(
+    run_hook(&checkout, "").1,
+    "original origin: \nhuk post-checkout origin: \n"
+  );
+}
+
+#[test]
+fn records_installed_scripts_in_the_manifest() {
+  let dir = tempdir().unwrap();
+  let store = ManifestStore::new(dir.path().join("huk/install.json"));
+  assert_eq!(store.load().unwrap(), Manifest::default());
+
+  let script = dir.path().join("pre-commit");
+  let content = wrapper_script("pre-commit", None, false);
+  fs::write(&script, &content).unwrap();
+  let mut manifest = Manifest::default();
+  manifest.hooks.insert(
+    "pre-commit".into(),
+    InstalledHook {
+      path:    script.clone(),
+      hash:    hash_script(content.as_bytes()),
+      version: "1.0.0".into(),
+    },
+  );
+  store.save(&manifest).unwrap();
+  let loaded = store.load().unwrap();
+  assert_eq!(loaded, manifest);
+  assert!(loaded.matches("pre-commit", &script));
+  assert!(!loaded.matches("pre-push", &script));
+  fs::write(&script, "#!/bin/sh\necho edited\n").unwrap();
+  assert!(!loaded.matches("pre-commit", &script));
+
+  // Nothing left to record: the file goes away.
+  store.save(&Manifest::default()).unwrap();
+  assert!(!dir.path().join("huk/install.json").exists());
+  store.save(&Manifest::default()).unwrap();
+}
+
+#[test]
+fn finds_huk_at_run_time_or_explains_why_not() {
+  let dir = tempdir().unwrap();
+  let hook = dir.path().join("pre-commit");
+  // Neither HUK_BIN nor PATH leads to huk.
+  let path = [("PATH", "/nonexistent"), ("HUK_BIN", "")];
+  let run = |env: &[(&str, &str)]| {
+    let out = Command::new(&hook)
+      .current_dir(dir.path())
+      .env_clear()
+      .envs(path.iter().chain(env).copied())
+      .output()
+      .unwrap();
+    (
+      out.status.code(),
+      String::from_utf8_lossy(&out.stderr).into_owned(),
+    )
+  };
+  write_script(&hook, &wrapper_script("pre-commit", None, false));
+  let (code, stderr) = run(&[]);
+  assert_eq!(code, Some(1));
+  assert!(stderr.contains("cannot run the pre-commit hook: huk was not found"));
+  assert_eq!(run(&[("HUK_SKIP_MISSING", "1")]).0, Some(0));
+
+  write_script(&hook, &wrapper_script("pre-commit", None, true));
+  assert_eq!(run(&[]).0, Some(0));
+  assert_eq!(run(&[("HUK_SKIP_MISSING", "0")]).0, Some(1));
+
+  // A local install is used without PATH.
+  let bin = dir.path().join("node_modules/.bin");
+  fs::create_dir_all(&bin).unwrap();
+  write_script(&bin.join("huk"), "#!/bin/sh\nexit 7\n");
+  assert_eq!(run(&[("HUK_SKIP_MISSING", "0")]).0, Some(7));
+  assert_eq!(
+    locate_huk(dir.path()),
+    Some(bin.join("huk").display().to_string())
+  );
+}
+
+#[test]
+fn only_undoes_the_hooks_path_it_set() {
+  let dir = tempdir().unwrap();
+  let cwd = dir.path();
+  git(cwd, &["init", "-q"]);
+  git(cwd, &["config", "core.hooksPath", "custom"]);
+
+  let mut manifest = Manifest::default();
+  set_hooks_path(cwd, ".huk", &mut manifest, true).unwrap();
+  assert_eq!(git(cwd, &["config", "core.hooksPath"]), ".huk");
+  // Moving to another directory still remembers the original value.
+  set_hooks_path(cwd, "hooks", &mut manifest, true).unwrap();
+  assert_eq!(
+    manifest.hooks_path,
+    Some(HooksPath {
+      value:    "hooks".into(),
+      previous: Some("custom".into()),
+    })
+  );
+  restore_hooks_path(cwd, &mut manifest).unwrap();
+  assert_eq!(git(cwd, &["config", "core.hooksPath"]), "custom");
+  assert_eq!(manifest.hooks_path, None);
+
+  // A value that was already set is not huk's to undo.
+  set_hooks_path(cwd, "custom", &mut manifest, true).unwrap();
+  assert_eq!(manifest.hooks_path, None);
+
+  // Nor is one that someone changed after huk set it.
+  git(cwd, &["config", "--unset", "core.hooksPath"]);
+  set_hooks_path(cwd, ".huk", &mut manifest, true).unwrap();
+  git(cwd, &["config", "core.hooksPath", "elsewhere"]);
+  restore_hooks_path(cwd, &mut manifest).unwrap();
+  assert_eq!(git(cwd, &["config", "core.hooksPath"]), "elsewhere");
+}
+
+#[test]
+fn only_removes_scripts_huk_wrote() {
+  let dir = tempdir().unwrap();
+  let root = dir.path();
+  git(root, &["init", "-q"]);
+  let hooks = root.join(".git/hooks");
+  let configure = |hooks: &str| {
+    fs::write(
+      root.join("package.json"),
+      format!(r#"{{"hooks": {hooks}}}"#),
+    )
+    .unwrap()
+  };
+  let install_opts = InstallOpts {
+    hooks_dir: None,
+    force:     false,
+    managed:   false,
+    auto:      false,
+    quiet:     false,
+    recursive: false,
+  };
+  let uninstall_opts = |force: bool, names: &[&str]| UninstallOpts {
+    hooks_dir: None,
+    force,
+    hooks: names.iter().map(|name| name.to_string()).collect(),
+  };
+
+  configure(r#"{"pre-commit": "true", "pre-push": "true"}"#);
+  install(root, &install_opts).unwrap();
+  assert!(is_huk_script(&hooks.join("pre-commit")));
+  assert!(is_huk_script(&hooks.join("pre-push")));
+
+  // Reinstalling removes the scripts of hooks dropped from the
+  // configuration.
+  configure(r#"{"pre-commit": "true"}"#);
+  install(root, &install_opts).unwrap();
+  assert!(hooks.join("pre-commit").exists());
+  assert!(!hooks.join("pre-push").exists());
+
+  // Scripts huk did not write are skipped, unless --force is given.
+  let foreign = hooks.join("commit-msg");
+  write_script(&foreign, "#!/bin/sh\nexit 0\n");
+  uninstall(root, &uninstall_opts(false, &["commit-msg"])).unwrap();
+  assert!(foreign.exists());
+  uninstall(root, &uninstall_opts(true, &["commit-msg"])).unwrap();
+  assert!(!foreign.exists());
+
+  // Without names, everything huk installed goes.
+  write_script(&foreign, "#!/bin/sh\nexit 0\n");
+  uninstall(root, &uninstall_opts(false, &[])).unwrap();
+  assert!(!hooks.join("pre-commit").exists());
+  assert!(foreign.exists());
+}
+
+#[test]
+fn only_a_meaningful_ci_value_counts() {
+  for value in ["true", "1", "yes", "github-actions"] {
+    assert!(is_ci_value(value), "{value}");
+  }
+  for value in ["", " ", "false", "FALSE", "0"] {
+    assert!(!is_ci_value(value), "{value:?}");
+  }
+}
+
+#[test]
+fn only_auto_skips_installing_outside_a_repository() {
+  let dir = tempdir().unwrap();
+  fs::write(
+    dir.path().join("package.json"),
+    r#"{ "hooks": { "pre-commit": "true" } }"#,
+  )
+  .unwrap();
+  let opts = |auto: bool| InstallOpts {
+    hooks_dir: None,
+    force: false,
+    managed: false,
+    auto,
+    quiet: true,
+    recursive: false,
+  };
+  assert!(install(dir.path(), &opts(true)).is_ok());
+  assert!(install(dir.path(), &opts(false)).is_err());
+}
+
+#[test]
+fn sync_hooks_follow_the_configuration() {
+  let dir = tempdir().unwrap();
+  let path = dir.path().join("package.json");
+  let load = |json: &str| {
+    fs::write(&path, json).unwrap();
+    HookConfig::load(&path).unwrap()
+  };
+  let plain = load(r#"{ "hooks": { "pre-commit": "true" } }"#);
+  assert_eq!(
+    installed_hooks(&plain).into_iter().collect::<Vec<_>>(),
+    ["pre-commit"]
+  );
+  let synced =
+    load(r#"{ "huk": { "sync": true }, "hooks": { "pre-commit": "true" } }"#);
+  assert_eq!(
+    installed_hooks(&synced).into_iter().collect::<Vec<_>>(),
+    ["post-checkout", "post-merge", "post-rewrite", "pre-commit"]
+  );
+
+  // Only changes that affect the scripts change the hash.
+  let hash = config_hash(&synced);
+  let renamed = load(
+    r#"{ "huk": { "sync": true }, "hooks": { "pre-commit": "echo ok" } }"#,
+  );
+  assert_eq!(config_hash(&renamed), hash);
+  let added = load(
+    r#"{ "huk": { "sync": true },
+         "hooks": { "pre-commit": "true", "pre-push": "true" } }"#,
+  );
+  assert_ne!(config_hash(&added), hash);
+}
+
+#[test]
+fn sync_reinstalls_when_the_configuration_changes() {
+  let dir = tempdir().unwrap();
+  let root = dir.path();
+  git(root, &["init", "-q"]);
+  let hooks = root.join(".git/hooks");
+  let configure = |json: &str| {
+    fs::write(root.join("package.json"), json).unwrap();
+    HookConfig::discover(root).unwrap()
+  };
+  let cfg =
+    configure(r#"{"huk": {"sync": true}, "hooks": {"pre-commit": "true"}}"#);
+  let opts = InstallOpts {
+    hooks_dir: None,
+    force:     false,
+    managed:   false,
+    auto:      false,
+    quiet:     false,
+    recursive: false,
+  };
+  install(root, &opts).unwrap();
+  assert!(is_huk_script(&hooks.join("post-checkout")));
+  assert!(!sync(root, &cfg).unwrap());
+
+  let cfg = configure(
+    r#"{"huk": {"sync": true},
+        "hooks": {"pre-commit": "true", "pre-push": "true"}}"#,
+  );
+  assert!(sync(root, &cfg).unwrap());
+  assert!(is_huk_script(&hooks.join("pre-push")));
+  assert!(!sync(root, &cfg).unwrap());
+
+  // Turning sync off removes the sync hooks, which then stay quiet.
+  let cfg = configure(r#"{"hooks": {"pre-commit": "true"}}"#);
+  assert!(sync(root, &cfg).unwrap());
+  assert!(!hooks.join("post-checkout").exists());
+  assert!(!hooks.join("pre-push").exists());
+  assert!(!sync(root, &cfg).unwrap());
+}
+
+#[test]
+fn resolves_the_hooks_directory_git_uses() {
+  let dir = tempdir().unwrap();
+  let root = fs::canonicalize(dir.path()).unwrap();
+  let main = root.join("main");
+  fs::create_dir(&main).unwrap();
+  git(&main, &["init", "-q"]);
+  git(&main, &["commit", "-q", "--allow-empty", "-m", "initial"]);
+  git(&main, &["worktree", "add", "-q", "../linked"]);
+  let nested = root.join("linked/src");
+  fs::create_dir(&nested).unwrap();
+
+  // Linked worktrees share the hooks of the main repository.
+  let hooks = main.join(".git/hooks");
+  assert_eq!(find_git_hooks_dir(&nested, None).unwrap(), hooks);
+
+  // A relative core.hooksPath is relative to the top of the working tree.
+  git(&main, &["config", "core.hooksPath", ".huk"]);
+  let resolved = find_git_hooks_dir(&nested, None).unwrap();
+  fs::create_dir(root.join("linked/.huk")).unwrap();
+  assert_eq!(
+    fs::canonicalize(resolved).unwrap(),
+    root.join("linked/.huk")
+  );
+  assert_eq!(
+    find_git_hooks_dir(&nested, Some("hooks".into())).unwrap(),
+    nested.join("hooks")
+  );
+}
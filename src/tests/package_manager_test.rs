@@ -0,0 +1,134 @@
+use crate::package_manager::Detection;
+use crate::package_manager::PackageManager;
+use crate::package_manager::PackageManagerError;
+use crate::package_manager::PackageManagerKind;
+use crate::package_manager::can_run_directly;
+use crate::package_manager::script_env;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn field_takes_precedence_over_lockfiles() {
+  let dir = tempdir().unwrap();
+  fs::write(dir.path().join("yarn.lock"), "").unwrap();
+  let pm =
+    PackageManager::detect(dir.path(), Some("pnpm@9.1.4+sha512.abc")).unwrap();
+  assert_eq!(pm.kind, PackageManagerKind::Pnpm);
+  assert_eq!(pm.version.as_deref(), Some("9.1.4"));
+  assert_eq!(pm.detection, Detection::Field);
+
+  assert!(matches!(
+    PackageManager::detect(dir.path(), Some("volt@1.0.0")),
+    Err(PackageManagerError::Unsupported(name)) if name == "volt"
+  ));
+}
+
+#[test]
+fn detects_manager_from_lockfile() {
+  let cases = [
+    ("pnpm-lock.yaml", PackageManagerKind::Pnpm),
+    ("yarn.lock", PackageManagerKind::Yarn),
+    ("bun.lockb", PackageManagerKind::Bun),
+    ("bun.lock", PackageManagerKind::Bun),
+    ("package-lock.json", PackageManagerKind::Npm),
+    ("deno.lock", PackageManagerKind::Deno),
+  ];
+  for (lockfile, kind) in cases {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join(lockfile), "").unwrap();
+    let pm = PackageManager::detect(dir.path(), None).unwrap();
+    assert_eq!(pm.kind, kind, "{lockfile}");
+    assert_eq!(pm.detection, Detection::Lockfile(dir.path().join(lockfile)));
+  }
+
+  let empty = tempdir().unwrap();
+  let pm = PackageManager::detect(empty.path(), None).unwrap();
+  assert_eq!(pm.kind, PackageManagerKind::Npm);
+  assert_eq!(pm.detection, Detection::Default);
+}
+
+#[test]
+fn run_args_follow_manager_conventions() {
+  let args = vec!["--fix".to_string()];
+  let argv = |kind| {
+    PackageManager {
+      kind,
+      version: None,
+      detection: Detection::Default,
+    }
+    .run_args("lint", &args)
+    .join(" ")
+  };
+  assert_eq!(argv(PackageManagerKind::Npm), "run lint -- --fix");
+  assert_eq!(argv(PackageManagerKind::Pnpm), "run lint --fix");
+  assert_eq!(argv(PackageManagerKind::Yarn), "run lint --fix");
+  assert_eq!(argv(PackageManagerKind::Bun), "run lint --fix");
+  assert_eq!(argv(PackageManagerKind::Deno), "task lint --fix");
+  let npm = PackageManager {
+    kind:      PackageManagerKind::Npm,
+    version:   None,
+    detection: Detection::Default,
+  };
+  assert_eq!(npm.run_args("lint", &[]), vec!["run", "lint"]);
+}
+
+#[test]
+fn pre_post_scripts_follow_manager_behaviour() {
+  let runs = |dir: &std::path::Path, field: Option<&str>| {
+    PackageManager::detect(dir, field)
+      .unwrap()
+      .runs_pre_post_scripts(dir)
+  };
+  let dir = tempdir().unwrap();
+  assert!(runs(dir.path(), Some("npm@10.8.0")));
+  assert!(runs(dir.path(), Some("bun@1.1.0")));
+  assert!(runs(dir.path(), Some("yarn@1.22.22")));
+  assert!(!runs(dir.path(), Some("yarn@4.5.0")));
+  assert!(!runs(dir.path(), Some("pnpm@9.1.4")));
+  assert!(!runs(dir.path(), Some("deno")));
+
+  fs::write(
+    dir.path().join(".npmrc"),
+    "enable-pre-post-scripts = true\n",
+  )
+  .unwrap();
+  assert!(runs(dir.path(), Some("pnpm@9.1.4")));
+
+  fs::write(dir.path().join("yarn.lock"), "# yarn lockfile v1\n").unwrap();
+  assert!(runs(dir.path(), None));
+  fs::write(dir.path().join("yarn.lock"), "__metadata:\n  version: 8\n")
+    .unwrap();
+  assert!(!runs(dir.path(), None));
+}
+
+#[test]
+fn direct_execution_requires_provided_environment() {
+  let dir = tempdir().unwrap();
+  assert!(can_run_directly(dir.path(), "eslint ."));
+  assert!(can_run_directly(dir.path(), "echo $npm_package_name"));
+  assert!(can_run_directly(dir.path(), "echo ${npm_lifecycle_event}"));
+  assert!(!can_run_directly(dir.path(), "echo $npm_config_registry"));
+  assert!(!can_run_directly(dir.path(), "echo $npm_package_names"));
+
+  fs::write(dir.path().join(".pnp.cjs"), "").unwrap();
+  assert!(!can_run_directly(dir.path(), "eslint ."));
+}
+
+#[test]
+fn script_env_mirrors_npm() {
+  let dir = tempdir().unwrap();
+  let nested = dir.path().join("packages/app");
+  fs::create_dir_all(nested.join("node_modules/.bin")).unwrap();
+  fs::create_dir_all(dir.path().join("node_modules/.bin")).unwrap();
+  let package_json = nested.join("package.json");
+  fs::write(&package_json, r#"{"name": "app", "version": "0.1.0"}"#).unwrap();
+
+  let env = script_env(&package_json, "lint", "eslint .");
+  assert_eq!(env["npm_package_name"], "app");
+  assert_eq!(env["npm_package_version"], "0.1.0");
+  assert_eq!(env["npm_lifecycle_event"], "lint");
+  assert_eq!(env["npm_lifecycle_script"], "eslint .");
+  let path: Vec<_> = std::env::split_paths(&env["PATH"]).collect();
+  assert_eq!(path[0], nested.join("node_modules/.bin"));
+  assert_eq!(path[1], dir.path().join("node_modules/.bin"));
+}
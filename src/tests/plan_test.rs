@@ -0,0 +1,229 @@
+use crate::config::HookConfig;
+use crate::report::format_plan;
+use crate::runner::TaskKind;
+use crate::runner::TaskRunner;
+use crate::runner::closest_task_name;
+use std::fs;
+use tempfile::tempdir;
+
+fn config(package_json: &str) -> (tempfile::TempDir, HookConfig) {
+  let dir = tempdir().unwrap();
+  fs::write(dir.path().join("package.json"), package_json).unwrap();
+  let cfg = HookConfig::discover(dir.path()).unwrap();
+  (dir, cfg)
+}
+
+#[test]
+fn dry_run_resolves_without_executing() {
+  let (dir, cfg) = config(
+    r#"{
+      "hooks": {
+        "pre-commit": ["lint", "lnit", "pre-push"],
+        "pre-push": {"command": "touch ran", "env": {"CI": "1"}}
+      },
+      "scripts": {"lint": "eslint ."},
+      "packageManager": "pnpm@9.1.4"
+    }"#,
+  );
+  let mut runner = TaskRunner::new_dry_run(&cfg);
+  runner.cwd = dir.path().to_path_buf();
+  let args = vec!["--fix".to_string()];
+  runner
+    .run_spec(&cfg.hooks["pre-commit"], "pre-commit", &args)
+    .unwrap();
+  let plan = runner.take_plan();
+  assert!(!dir.path().join("ran").exists());
+  assert!(runner.runs.is_empty());
+
+  let kinds: Vec<(TaskKind, usize)> =
+    plan.iter().map(|s| (s.kind, s.depth)).collect();
+  assert_eq!(
+    kinds,
+    vec![
+      (TaskKind::NodeScript, 0),
+      (TaskKind::Command, 0),
+      (TaskKind::Hook, 0),
+      (TaskKind::Command, 1),
+    ]
+  );
+  assert_eq!(plan[0].command.as_deref(), Some("pnpm run lint --fix"));
+  assert_eq!(plan[1].command.as_deref(), Some("sh -c 'lnit --fix'"));
+  assert!(plan[1].fallback && !plan[0].fallback && !plan[3].fallback);
+  assert_eq!(plan[3].env.get("CI").map(String::as_str), Some("1"));
+  assert_eq!(plan[3].cwd, dir.path());
+
+  let text = format_plan("pre-commit", &plan, false);
+  assert!(text.starts_with("Plan for 'pre-commit':\n  lint (node script)\n"));
+  assert!(text.contains("  lnit (command) unresolved\n"));
+  assert!(text.contains("\n    touch ran (command)\n      $ sh -c"));
+  assert!(text.contains("      env: CI=1\n"));
+}
+
+#[test]
+fn suggests_close_task_names() {
+  let (_dir, cfg) = config(
+    r#"{
+      "hooks": {"pre-commit": "lint"},
+      "scripts": {"lint": "eslint .", "test": "vitest", "format": "prettier"}
+    }"#,
+  );
+  assert_eq!(closest_task_name(&cfg, "lnit"), Some("lint"));
+  assert_eq!(closest_task_name(&cfg, "fromat"), Some("format"));
+  assert_eq!(closest_task_name(&cfg, "pre-comit"), Some("pre-commit"));
+  assert_eq!(closest_task_name(&cfg, "cargo"), None);
+  assert_eq!(closest_task_name(&cfg, "lnit --fix"), None);
+}
+
+#[test]
+fn task_objects_control_argument_forwarding() {
+  let (dir, cfg) = config(
+    r#"{
+      "hooks": {
+        "pre-push": [
+          {"task": "test", "args": ["--bail"], "forward_args": false},
+          {"task": "lint -- --fix", "dependencies": ["test -- --run"]},
+          {"command": "echo", "args": ["hi"], "forward_args": false}
+        ]
+      },
+      "scripts": {"lint": "eslint .", "test": "vitest"}
+    }"#,
+  );
+  let mut runner = TaskRunner::new_dry_run(&cfg);
+  runner.cwd = dir.path().to_path_buf();
+  let args = vec!["origin".to_string()];
+  runner
+    .run_spec(&cfg.hooks["pre-push"], "pre-push", &args)
+    .unwrap();
+  let commands: Vec<String> = runner
+    .take_plan()
+    .into_iter()
+    .filter_map(|step| step.command)
+    .collect();
+  assert_eq!(
+    commands,
+    vec![
+      "npm run test -- --bail",
+      "npm run test -- --run",
+      "npm run lint -- --fix origin",
+      "sh -c 'echo hi'",
+    ]
+  );
+}
+
+#[test]
+fn direct_scripts_run_lifecycle_stages() {
+  let (dir, cfg) = config(
+    r#"{
+      "huk": {"direct_scripts": true},
+      "hooks": {"pre-commit": ["lint -- --fix", "registry"]},
+      "scripts": {
+        "prelint": "echo pre",
+        "lint": "eslint .",
+        "postlint": "echo post",
+        "registry": "echo $npm_config_registry"
+      }
+    }"#,
+  );
+  let mut runner = TaskRunner::new_dry_run(&cfg);
+  runner.cwd = dir.path().to_path_buf();
+  runner
+    .run_spec(&cfg.hooks["pre-commit"], "pre-commit", &[])
+    .unwrap();
+  let plan = runner.take_plan();
+  let steps: Vec<(&str, &str)> = plan
+    .iter()
+    .map(|s| (s.name.as_str(), s.command.as_deref().unwrap_or_default()))
+    .collect();
+  assert_eq!(
+    steps,
+    vec![
+      ("prelint", "sh -c 'echo pre'"),
+      ("lint", "sh -c 'eslint . --fix'"),
+      ("postlint", "sh -c 'echo post'"),
+      ("registry", "npm run registry"),
+    ]
+  );
+  assert_eq!(plan[1].env["npm_lifecycle_event"], "lint");
+}
+
+#[test]
+fn direct_scripts_follow_the_package_manager() {
+  let (dir, cfg) = config(
+    r#"{
+      "packageManager": "pnpm@9.1.4",
+      "huk": {
+        "direct_scripts": true,
+        "tasks": {"web": {"task": "lint", "cwd": "web"}}
+      },
+      "hooks": {"pre-commit": ["lint", "web"]},
+      "scripts": {"prelint": "echo pre", "lint": "eslint ."}
+    }"#,
+  );
+  let mut runner = TaskRunner::new_dry_run(&cfg);
+  runner.cwd = dir.path().join("src");
+  runner
+    .run_spec(&cfg.hooks["pre-commit"], "pre-commit", &[])
+    .unwrap();
+  let plan = runner.take_plan();
+  let steps: Vec<(&str, &std::path::Path)> = plan
+    .iter()
+    .filter(|s| s.command.is_some())
+    .map(|s| (s.name.as_str(), s.cwd.as_path()))
+    .collect();
+  assert_eq!(
+    steps,
+    vec![
+      ("lint", dir.path()),
+      ("lint", dir.path().join("web").as_path()),
+    ]
+  );
+}
+
+#[test]
+fn huk_tasks_run_with_cwd_and_parallel_dependencies() {
+  let (dir, cfg) = config(
+    r#"{
+      "hooks": {"pre-push": "ci -- --quick"},
+      "huk": {
+        "tasks": {
+          "ci": {
+            "dependencies": ["lint", "test"],
+            "parallel": true,
+            "task": "report"
+          },
+          "lint": {"command": "eslint .", "cwd": "web", "env": {"CI": "1"}},
+          "test": ["sh:cargo test", "sh:cargo doc"],
+          "report": "echo done"
+        }
+      }
+    }"#,
+  );
+  let mut runner = TaskRunner::new_dry_run(&cfg);
+  runner.cwd = dir.path().to_path_buf();
+  runner
+    .run_spec(&cfg.hooks["pre-push"], "pre-push", &[])
+    .unwrap();
+  let plan = runner.take_plan();
+  let steps: Vec<(TaskKind, usize, Option<&str>)> = plan
+    .iter()
+    .map(|s| (s.kind, s.depth, s.command.as_deref()))
+    .collect();
+  // Parallel dependencies are merged back in the order they are listed.
+  assert_eq!(
+    steps,
+    vec![
+      (TaskKind::HukTask, 0, None),
+      (TaskKind::HukTask, 1, None),
+      (TaskKind::Command, 2, Some("sh -c 'eslint .'")),
+      (TaskKind::HukTask, 1, None),
+      (TaskKind::Command, 2, Some("sh -c 'cargo test'")),
+      (TaskKind::Command, 2, Some("sh -c 'cargo doc'")),
+      (TaskKind::HukTask, 1, None),
+      (TaskKind::Command, 2, Some("sh -c 'echo done --quick'")),
+    ]
+  );
+  assert_eq!(plan[2].cwd, dir.path().join("web"));
+  assert_eq!(plan[2].env.get("CI").map(String::as_str), Some("1"));
+  assert_eq!(plan[4].cwd, dir.path());
+  assert!(plan[4].env.is_empty());
+}
@@ -0,0 +1,215 @@
+use crate::config::HookConfig;
+use crate::constants::GIT_HOOKS;
+use crate::refs::RefInput;
+use crate::refs::STDIN_HOOKS;
+use crate::runner::TaskRunner;
+use crate::task::Condition;
+use crate::task::TaskSpec;
+use crate::task::TaskSpecParseError;
+use crate::tests::support::commit;
+use crate::tests::support::git;
+use serde_json::json;
+use std::fs;
+use std::sync::Arc;
+use tempfile::tempdir;
+
+const ZERO: &str = "0000000000000000000000000000000000000000";
+
+#[test]
+fn parses_push_and_receive_lines() {
+  let push = format!(
+    "refs/heads/main {new} refs/heads/main {old}\n\
+     (delete) {ZERO} refs/heads/gone {old}\n\
+     garbage\n",
+    new = "a".repeat(40),
+    old = "b".repeat(40),
+  );
+  let input = RefInput::parse("pre-push", push.clone().into_bytes());
+  assert!(input.listed);
+  assert_eq!(input.raw, push.as_bytes());
+  assert_eq!(input.updates.len(), 2);
+  assert_eq!(
+    input.updates[0].local_ref.as_deref(),
+    Some("refs/heads/main")
+  );
+  assert_eq!(input.updates[0].branch(), Some("main"));
+  assert!(!input.updates[0].is_deletion());
+  assert!(input.updates[1].is_deletion());
+
+  let receive = format!("{ZERO} {} refs/tags/v1\n", "c".repeat(40));
+  let input = RefInput::parse("post-receive", receive.into_bytes());
+  assert!(input.updates[0].is_creation());
+  assert_eq!(input.updates[0].branch(), None);
+  assert!(!RefInput::parse("pre-commit", Vec::new()).listed);
+
+  // post-rewrite input lists rewritten commits, not ref updates.
+  let rewrite = format!("{} {} extra\n", "a".repeat(40), "b".repeat(40));
+  let input = RefInput::parse("post-rewrite", rewrite.clone().into_bytes());
+  assert!(!input.listed && input.updates.is_empty());
+  assert_eq!(input.raw, rewrite.as_bytes());
+}
+
+#[test]
+fn parses_and_checks_when_conditions() {
+  let spec = TaskSpec::from_json(&json!({
+    "command": "deploy",
+    "when": {"branches": ["main", "release/*"], "deleted": false}
+  }))
+  .unwrap();
+  let TaskSpec::Detailed(object) = &spec else {
+    panic!("expected a task object");
+  };
+  let when = &object.when;
+  assert!(when.matches_branch("release/1.2") && !when.matches_branch("dev"));
+  assert_eq!(when.deleted, Some(false));
+  assert_eq!(TaskSpec::from_json(&spec.to_json()).unwrap(), spec);
+  assert_eq!(
+    TaskSpec::from_json(&json!({"command": "x", "when": {"branches": 1}})),
+    Err(TaskSpecParseError::InvalidWhenType)
+  );
+  // Only the documented keys are accepted.
+  assert_eq!(
+    TaskSpec::from_json(&json!({"command": "x", "when": {"branch": "main"}})),
+    Err(TaskSpecParseError::InvalidWhenType)
+  );
+
+  let push = format!(
+    "refs/heads/main {sha} refs/heads/main {ZERO}\n\
+     (delete) {ZERO} refs/heads/old {sha}\n",
+    sha = "a".repeat(40),
+  );
+  let input = RefInput::parse("pre-push", push.into_bytes());
+  let names = |condition: Condition| {
+    input.filter(&condition, None).map(|refs| {
+      refs
+        .updates
+        .iter()
+        .map(|u| u.name.clone())
+        .collect::<Vec<_>>()
+    })
+  };
+  let only_main = Condition {
+    branches: vec!["main".into()],
+    ..Condition::default()
+  };
+  assert_eq!(
+    names(only_main.clone()),
+    Some(vec!["refs/heads/main".into()])
+  );
+  let deletions = Condition {
+    deleted: Some(true),
+    ..Condition::default()
+  };
+  assert_eq!(names(deletions), Some(vec!["refs/heads/old".into()]));
+  let deleted_main = Condition {
+    deleted: Some(true),
+    ..only_main.clone()
+  };
+  assert_eq!(names(deleted_main), None);
+
+  // Hooks without ref updates check the current branch instead.
+  let local = RefInput::default();
+  assert!(local.filter(&only_main, Some("main")).is_some());
+  assert!(local.filter(&only_main, Some("dev")).is_none());
+  assert!(local.filter(&only_main, None).is_none());
+}
+
+#[test]
+fn resolves_ranges_and_changed_files() {
+  let dir = tempdir().unwrap();
+  let root = dir.path();
+  git(root, &["init", "-q", "-b", "main"]);
+  let first = commit(root, "one.txt", "one.txt");
+  git(root, &["update-ref", "refs/remotes/origin/main", &first]);
+  let second = commit(root, "two words.txt", "two words.txt");
+  let third = commit(root, "three.txt", "three.txt");
+
+  let push = format!(
+    "refs/heads/main {third} refs/heads/main {first}\n\
+     refs/heads/main {third} refs/heads/topic {ZERO}\n"
+  );
+  let mut input = RefInput::parse("pre-push", push.into_bytes());
+  input.resolve("pre-push", root);
+  let update = format!("{first}..{third}");
+  assert_eq!(input.updates[0].range.as_deref(), Some(update.as_str()));
+  // A new branch starts after the newest commit the remote already has.
+  assert_eq!(input.updates[1].range.as_deref(), Some(update.as_str()));
+  assert_eq!(input.changed_files(), ["three.txt", "two words.txt"]);
+  assert_eq!(
+    input.expand("lint {changed_files}").as_deref(),
+    Some("lint three.txt 'two words.txt'")
+  );
+  let env = input.env();
+  assert_eq!(env["HUK_PUSH_RANGE"], format!("{update} {update}"));
+  assert_eq!(env["HUK_CHANGED_FILES"], "three.txt\ntwo words.txt");
+  assert!(env["HUK_REFS"].starts_with(&format!("refs/heads/main {first}")));
+
+  // A receiving hook compares against every other ref, and `main` already
+  // has every commit of the new branch.
+  let receive = format!("{ZERO} {third} refs/heads/feature\n");
+  let mut input = RefInput::parse("pre-receive", receive.into_bytes());
+  input.resolve("pre-receive", root);
+  assert_eq!(input.updates[0].range, None);
+  assert!(input.changed_files().is_empty());
+  // Placeholders with nothing to substitute skip the command.
+  assert_eq!(input.expand("lint {changed_files}"), None);
+  assert_eq!(input.expand("git log {push_range}"), None);
+  assert_eq!(
+    RefInput::default().expand("lint .").as_deref(),
+    Some("lint .")
+  );
+  let receive = format!("{ZERO} {third} refs/heads/main\n");
+  let mut input = RefInput::parse("pre-receive", receive.into_bytes());
+  let range = format!("{second}..{third}");
+  git(root, &["update-ref", "refs/heads/main", &second]);
+  input.resolve("pre-receive", root);
+  assert_eq!(input.updates[0].range.as_deref(), Some(range.as_str()));
+}
+
+#[test]
+fn runner_skips_tasks_whose_conditions_fail() {
+  let dir = tempdir().unwrap();
+  fs::write(
+    dir.path().join("package.json"),
+    r#"{"hooks": {"pre-push": [
+      {"command": "echo {push_range}"},
+      {"command": "eslint {changed_files}"},
+      {"command": "deploy", "when": {"branches": "main"}},
+      {"command": "cleanup", "when": {"deleted": true}}
+    ]}}"#,
+  )
+  .unwrap();
+  let cfg = HookConfig::discover(dir.path()).unwrap();
+  let mut input = RefInput::parse(
+    "pre-push",
+    format!("(delete) {ZERO} refs/heads/topic {}\n", "a".repeat(40))
+      .into_bytes(),
+  );
+  input.updates[0].range = Some("x..y".into());
+  let mut runner = TaskRunner::new_dry_run(&cfg);
+  runner.refs = Arc::new(input);
+  runner
+    .run_spec(&cfg.hooks["pre-push"], "pre-push", &[])
+    .unwrap();
+  let commands: Vec<String> = runner
+    .take_plan()
+    .into_iter()
+    .filter_map(|step| step.command)
+    .collect();
+  assert_eq!(commands, ["sh -c 'echo x..y'", "sh -c cleanup"]);
+}
+
+#[test]
+fn every_stdin_hook_can_be_configured() {
+  for hook in STDIN_HOOKS {
+    assert!(GIT_HOOKS.contains(&hook), "{hook} is not a known hook");
+  }
+  let dir = tempdir().unwrap();
+  fs::write(
+    dir.path().join("package.json"),
+    json!({"hooks": {"reference-transaction": "true"}}).to_string(),
+  )
+  .unwrap();
+  let cfg = HookConfig::discover(dir.path()).unwrap();
+  assert!(cfg.hooks.contains_key("reference-transaction"));
+}
@@ -0,0 +1,136 @@
+use crate::config::Settings;
+use crate::config::parse_duration;
+use crate::report::RunSummary;
+use crate::report::format_summary;
+use crate::report::to_json;
+use crate::report::to_junit;
+use crate::runner::TaskKind;
+use crate::runner::TaskRun;
+use serde_json::json;
+use std::time::Duration;
+
+fn run(name: &str, kind: TaskKind, ms: u64, success: bool) -> TaskRun {
+  TaskRun {
+    name: name.into(),
+    command: name.into(),
+    kind,
+    depth: 0,
+    duration_ms: ms,
+    exit_code: Some(if success { 0 } else { 1 }),
+    success,
+    cached: false,
+    output: String::new(),
+  }
+}
+
+fn sample() -> Vec<TaskRun> {
+  let mut nested = run("lint", TaskKind::NodeScript, 1_200, true);
+  nested.depth = 1;
+  let mut cached = run("tsc", TaskKind::Command, 0, true);
+  cached.cached = true;
+  let mut failed = run("cargo test", TaskKind::Command, 7_000, false);
+  failed.output = "assertion <failed> & more".into();
+  vec![
+    run("check", TaskKind::Hook, 1_300, true),
+    nested,
+    cached,
+    failed,
+  ]
+}
+
+#[test]
+fn summary_lists_every_task_and_flags_slow_ones() {
+  let runs = sample();
+  let summary = RunSummary {
+    hook:       "pre-commit",
+    runs:       &runs,
+    total:      Duration::from_millis(8_300),
+    warn_after: Some(Duration::from_secs(5)),
+  };
+  let table = format_summary(&summary, false);
+  let lines: Vec<&str> = table.lines().collect();
+  assert_eq!(lines[0], "Summary for 'pre-commit':");
+  assert!(lines[2].contains("check") && lines[2].contains("hook"));
+  assert!(lines[3].contains("  lint") && lines[3].contains("node script"));
+  assert!(lines[4].contains("cached"));
+  assert!(lines[5].contains("failed") && lines[5].ends_with("(slow)"));
+  assert!(lines[6].contains("total") && lines[6].contains("8.3s"));
+  assert_eq!(table.matches("(slow)").count(), 1);
+  assert!(!table.contains('\x1b'));
+  assert!(format_summary(&summary, true).contains("\x1b[1;33m"));
+}
+
+#[test]
+fn json_report_describes_each_task() {
+  let runs = sample();
+  let summary = RunSummary {
+    hook:       "pre-push",
+    runs:       &runs,
+    total:      Duration::from_millis(8_300),
+    warn_after: None,
+  };
+  let report = to_json(&summary);
+  assert_eq!(report["hook"], "pre-push");
+  assert_eq!(report["success"], false);
+  assert_eq!(report["tasks"].as_array().unwrap().len(), 4);
+  assert_eq!(report["tasks"][1]["kind"], "node-script");
+  assert_eq!(report["tasks"][2]["status"], "cached");
+  assert_eq!(report["tasks"][3]["status"], "failed");
+  assert_eq!(report["tasks"][3]["slow"], false);
+}
+
+#[test]
+fn junit_report_escapes_and_marks_failures() {
+  let runs = sample();
+  let summary = RunSummary {
+    hook:       "pre-push",
+    runs:       &runs,
+    total:      Duration::from_millis(8_300),
+    warn_after: None,
+  };
+  let xml = to_junit(&summary);
+  assert!(xml.starts_with("<?xml"));
+  assert!(xml.contains("tests=\"3\" failures=\"1\" time=\"8.300\""));
+  assert!(!xml.contains("name=\"check\""));
+  assert!(xml.contains("classname=\"pre-push.node-script\""));
+  assert!(xml.contains("<failure message=\"exited with status 1\">"));
+  assert!(xml.contains("assertion &lt;failed&gt; &amp; more"));
+  assert!(xml.contains("<system-out>cached</system-out>"));
+}
+
+#[test]
+fn junit_report_counts_a_failing_nested_hook_once() {
+  let mut inner = run("cargo test", TaskKind::Command, 10, false);
+  inner.depth = 1;
+  let runs = vec![run("check", TaskKind::Hook, 10, false), inner];
+  let summary = RunSummary {
+    hook:       "pre-push",
+    runs:       &runs,
+    total:      Duration::from_millis(10),
+    warn_after: None,
+  };
+  let xml = to_junit(&summary);
+  assert!(xml.contains("tests=\"1\" failures=\"1\""));
+  assert_eq!(xml.matches("<failure ").count(), 1);
+}
+
+#[test]
+fn warn_after_accepts_numbers_and_suffixed_strings() {
+  assert_eq!(parse_duration("500ms"), Some(Duration::from_millis(500)));
+  assert_eq!(parse_duration("2.5s"), Some(Duration::from_millis(2_500)));
+  assert_eq!(parse_duration("1m"), Some(Duration::from_secs(60)));
+  assert_eq!(parse_duration("3"), Some(Duration::from_secs(3)));
+  assert_eq!(parse_duration("soon"), None);
+
+  let parse = |v| serde_json::from_value::<Settings>(v);
+  let s = parse(json!({"warn_after": 1.5})).unwrap();
+  assert_eq!(s.warn_after, Some(Duration::from_millis(1_500)));
+  let s = parse(json!({"warn_after": "250ms"})).unwrap();
+  assert_eq!(s.warn_after, Some(Duration::from_millis(250)));
+  assert_eq!(parse(json!({"warn_after": null})).unwrap().warn_after, None);
+  assert_eq!(
+    parse(json!({})).unwrap().warn_after,
+    Settings::default().warn_after
+  );
+  assert!(parse(json!({"warn_after": "later"})).is_err());
+}
@@ -0,0 +1,231 @@
+use crate::config::HookConfig;
+use crate::runner::Resolution;
+use crate::runner::RunnerError;
+use crate::runner::resolve;
+use crate::runner::validate;
+use crate::runner::validate_hook;
+use crate::task::TaskRef;
+use std::fs;
+use tempfile::tempdir;
+
+fn config(package_json: &str) -> HookConfig {
+  let dir = tempdir().unwrap();
+  fs::write(dir.path().join("package.json"), package_json).unwrap();
+  HookConfig::discover(dir.path()).unwrap()
+}
+
+#[test]
+fn parses_reference_prefixes() {
+  assert_eq!(TaskRef::parse("task:lint"), TaskRef::Task("lint"));
+  assert_eq!(TaskRef::parse("script: lint"), TaskRef::Script("lint"));
+  assert_eq!(TaskRef::parse("hook:pre-push"), TaskRef::Hook("pre-push"));
+  assert_eq!(TaskRef::parse("sh:echo a:b"), TaskRef::Shell("echo a:b"));
+  assert_eq!(TaskRef::parse(" lint "), TaskRef::Bare("lint"));
+  assert_eq!(TaskRef::parse("echo task:x"), TaskRef::Bare("echo task:x"));
+}
+
+#[test]
+fn resolves_explicit_and_bare_references() {
+  let cfg = config(
+    r#"{
+      "hooks": {"pre-commit": "lint", "pre-push": "test"},
+      "scripts": {"lint": "eslint .", "pre-push": "echo script"}
+    }"#,
+  );
+  let ok = |r: &str| resolve(&cfg, r).unwrap().0;
+  assert_eq!(ok("lint"), Resolution::NodeScript("lint".into()));
+  assert_eq!(ok("script:lint"), Resolution::NodeScript("lint".into()));
+  // Scripts take precedence over hooks for bare names; `hook:` overrides.
+  assert_eq!(ok("pre-push"), Resolution::NodeScript("pre-push".into()));
+  assert_eq!(ok("hook:pre-push"), Resolution::Hook("pre-push".into()));
+  assert_eq!(ok("sh:lint"), Resolution::Command("lint".into()));
+  assert_eq!(ok("lnit"), Resolution::Fallback("lnit".into()));
+
+  for missing in ["task:lint", "script:lnit", "hook:post-merge", "sh:"] {
+    assert!(matches!(
+      resolve(&cfg, missing),
+      Err(RunnerError::TaskNotFound(name)) if name == missing
+    ));
+  }
+}
+
+#[test]
+fn strict_mode_rejects_unresolved_names() {
+  let lenient = config(
+    r#"{
+      "hooks": {"pre-commit": [{"command": "true", "dependencies": ["lnit"]}]},
+      "scripts": {"lint": "eslint ."}
+    }"#,
+  );
+  assert!(validate(&lenient).is_ok());
+
+  let strict = config(
+    r#"{
+      "huk": {"strict": true},
+      "hooks": {"pre-commit": [{"command": "true", "dependencies": ["lnit"]}]},
+      "scripts": {"lint": "eslint ."}
+    }"#,
+  );
+  assert!(matches!(
+    validate(&strict),
+    Err(RunnerError::TaskNotFound(name)) if name == "lnit"
+  ));
+  assert_eq!(
+    resolve(&strict, "sh:lnit").unwrap().0,
+    Resolution::Command("lnit".into())
+  );
+
+  let explicit = config(r#"{"hooks": {"pre-commit": "script:missing"}}"#);
+  assert!(validate(&explicit).is_err());
+}
+
+#[test]
+fn a_broken_hook_does_not_block_the_others() {
+  let cfg = config(
+    r#"{
+      "huk": {"strict": true},
+      "hooks": {
+        "pre-commit": "lint",
+        "pre-push": "lnit",
+        "post-merge": "hook:pre-push",
+        "commit-msg": "hook:pre-commit"
+      },
+      "scripts": {"lint": "eslint ."}
+    }"#,
+  );
+  assert!(validate_hook(&cfg, "pre-commit").is_ok());
+  assert!(validate_hook(&cfg, "commit-msg").is_ok());
+  assert!(validate_hook(&cfg, "pre-push").is_err());
+  // Hooks run by the hook are checked with it.
+  assert!(validate_hook(&cfg, "post-merge").is_err());
+  assert!(validate(&cfg).is_err());
+}
+
+#[test]
+fn references_carry_their_own_arguments() {
+  let cfg = config(
+    r#"{
+      "hooks": {"pre-commit": "lint -- --fix"},
+      "scripts": {"lint": "eslint .", "test": "vitest"}
+    }"#,
+  );
+  let args = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+  assert_eq!(
+    resolve(&cfg, "lint -- --fix 'a b'").unwrap(),
+    (
+      Resolution::NodeScript("lint".into()),
+      args(&["--fix", "a b"])
+    )
+  );
+  assert_eq!(
+    resolve(&cfg, "script:test -- --bail").unwrap(),
+    (Resolution::NodeScript("test".into()), args(&["--bail"]))
+  );
+  assert_eq!(
+    resolve(&cfg, "lint --").unwrap(),
+    (Resolution::NodeScript("lint".into()), vec![])
+  );
+  // Unresolved names and shell commands keep their `--` untouched.
+  assert_eq!(
+    resolve(&cfg, "git diff -- src").unwrap(),
+    (Resolution::Fallback("git diff -- src".into()), vec![])
+  );
+  assert_eq!(
+    resolve(&cfg, "sh:lint -- --fix").unwrap(),
+    (Resolution::Command("lint -- --fix".into()), vec![])
+  );
+}
+
+#[test]
+fn validates_deno_task_dependencies() {
+  let deno = |tasks: &str| {
+    let dir = tempdir().unwrap();
+    fs::write(
+      dir.path().join("deno.json"),
+      format!(r#"{{"hooks": {{"pre-commit": "check"}}, "tasks": {tasks}}}"#),
+    )
+    .unwrap();
+    HookConfig::discover(dir.path()).unwrap()
+  };
+  let ok = deno(r#"{"check": {"dependencies": ["fmt"]}, "fmt": "deno fmt"}"#);
+  assert!(validate(&ok).is_ok());
+
+  let missing = deno(r#"{"check": {"dependencies": ["lnit"]}}"#);
+  assert!(matches!(
+    validate(&missing),
+    Err(RunnerError::TaskNotFound(name)) if name == "lnit"
+  ));
+
+  let dir = tempdir().unwrap();
+  fs::write(
+    dir.path().join("deno.json"),
+    r#"{
+      "workspace": ["./api"],
+      "hooks": {"pre-commit": "check", "pre-push": "api/serve"},
+      "tasks": {"check": {"dependencies": ["serve"]}}
+    }"#,
+  )
+  .unwrap();
+  fs::create_dir(dir.path().join("api")).unwrap();
+  fs::write(
+    dir.path().join("api/deno.json"),
+    r#"{"tasks": {"serve": {"dependencies": ["build"]}, "build": "true"}}"#,
+  )
+  .unwrap();
+  let workspace = HookConfig::discover(dir.path()).unwrap();
+  // A root task cannot depend on a task only a member defines.
+  assert!(matches!(
+    validate_hook(&workspace, "pre-commit"),
+    Err(RunnerError::TaskNotFound(name)) if name == "serve"
+  ));
+  // Member dependencies resolve within the member.
+  assert!(validate_hook(&workspace, "pre-push").is_ok());
+
+  let cyclic = deno(
+    r#"{
+      "check": {"dependencies": ["a"]},
+      "a": {"command": "true", "dependencies": ["b"]},
+      "b": {"dependencies": ["a"]}
+    }"#,
+  );
+  assert!(matches!(
+    validate(&cyclic),
+    Err(RunnerError::CircularDependency(name)) if name == "a"
+  ));
+}
+
+#[test]
+fn resolves_and_validates_huk_tasks() {
+  let cfg = config(
+    r#"{
+      "hooks": {"pre-commit": "check"},
+      "scripts": {"lint": "eslint .", "check": "tsc"},
+      "huk": {
+        "tasks": {
+          "lint": {"description": "Lint sources"},
+          "check": {"dependencies": ["lint", "task:fmt"]},
+          "fmt": "prettier --write ."
+        }
+      }
+    }"#,
+  );
+  let ok = |r: &str| resolve(&cfg, r).unwrap().0;
+  // huk tasks take precedence over scripts; description-only entries only
+  // annotate the script of the same name.
+  assert_eq!(ok("check"), Resolution::HukTask("check".into()));
+  assert_eq!(ok("script:check"), Resolution::NodeScript("check".into()));
+  assert_eq!(ok("task:fmt"), Resolution::HukTask("fmt".into()));
+  assert_eq!(ok("lint"), Resolution::NodeScript("lint".into()));
+  assert!(validate(&cfg).is_ok());
+
+  let cyclic = config(
+    r#"{
+      "hooks": {"pre-commit": "a"},
+      "huk": {"tasks": {"a": {"dependencies": ["b"]}, "b": ["true", "a"]}}
+    }"#,
+  );
+  assert!(matches!(
+    validate(&cyclic),
+    Err(RunnerError::CircularDependency(name)) if name == "a"
+  ));
+}
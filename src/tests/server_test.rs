@@ -0,0 +1,105 @@
+use crate::refs::RefInput;
+use crate::runner::TaskRunner;
+use crate::server;
+use crate::server::ConfigLocation;
+use crate::server::ServerError;
+use crate::tests::support::commit;
+use crate::tests::support::git;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use tempfile::tempdir;
+
+const ZERO: &str = "0000000000000000000000000000000000000000";
+
+#[test]
+fn parses_config_locations() {
+  assert_eq!(
+    ConfigLocation::parse("HEAD:ci/package.json"),
+    ConfigLocation::Blob {
+      rev:  "HEAD".into(),
+      path: "ci/package.json".into(),
+    }
+  );
+  assert_eq!(
+    ConfigLocation::parse("hooks/deno.json"),
+    ConfigLocation::File("hooks/deno.json".into())
+  );
+}
+
+#[test]
+fn reads_update_hook_arguments() {
+  let args = ["refs/heads/main".to_string(), ZERO.into(), "a".repeat(40)];
+  let input = RefInput::from_update_args(&args);
+  assert!(input.listed && input.raw.is_empty());
+  assert_eq!(input.updates[0].name, "refs/heads/main");
+  assert!(input.updates[0].is_creation());
+  assert!(RefInput::from_update_args(&[]).updates.is_empty());
+}
+
+#[test]
+fn checks_out_the_pushed_tree_with_the_server_config() {
+  let dir = tempdir().unwrap();
+  let work = dir.path().join("work");
+  let bare = dir.path().join("bare.git");
+  fs::create_dir(&work).unwrap();
+  git(&work, &["init", "-q", "-b", "main"]);
+  commit(
+    &work,
+    "package.json",
+    r#"{"hooks": {"pre-receive": "trusted"}}"#,
+  );
+  git(dir.path(), &["clone", "-q", "--bare", "work", "bare.git"]);
+  assert!(server::is_bare(&bare) && !server::is_bare(&work));
+
+  // The pushed commit changes the hooks, but the configuration comes from
+  // what the server already has.
+  commit(
+    &work,
+    "package.json",
+    r#"{"hooks": {"pre-receive": "pushed"}}"#,
+  );
+  let pushed = commit(&work, "src.txt", "pushed");
+  git(&work, &["push", "-q", "../bare.git", "main:topic"]);
+  let input = RefInput::parse(
+    "pre-receive",
+    format!("{ZERO} {pushed} refs/heads/topic\n").into_bytes(),
+  );
+  let hook = server::prepare(&bare, &input).unwrap();
+  let dir_path = hook.checkout.dir.clone();
+  assert_eq!(
+    fs::read_to_string(dir_path.join("src.txt")).unwrap(),
+    "pushed"
+  );
+  // The checkout lives in a directory only the current user can enter, and
+  // holds nothing but the pushed tree.
+  let root = dir_path.parent().unwrap();
+  let mode = fs::metadata(root).unwrap().permissions().mode();
+  assert_eq!(mode & 0o777, 0o700);
+  assert_eq!(fs::read_dir(&dir_path).unwrap().count(), 2);
+  assert_eq!(hook.config.hooks["pre-receive"].to_string(), r#""trusted""#);
+  let mut runner = TaskRunner::new(&hook.config);
+  hook.configure(&mut runner);
+  assert_eq!(runner.cwd, dir_path);
+  assert_eq!(runner.env["GIT_DIR"], hook.git_dir.display().to_string());
+  drop(runner);
+  drop(hook);
+  assert!(!root.exists());
+
+  // `huk.config` may name any committed file, and a file in the Git
+  // directory is used before one committed at HEAD.
+  git(&bare, &["config", "huk.config", "topic:package.json"]);
+  let config = server::load_config(&bare).unwrap();
+  assert_eq!(config.hooks["pre-receive"].to_string(), r#""pushed""#);
+  git(&bare, &["config", "--unset", "huk.config"]);
+  fs::write(bare.join("deno.json"), r#"{"hooks": {"update": "local"}}"#)
+    .unwrap();
+  let config = server::load_config(&bare).unwrap();
+  assert!(config.hooks.contains_key("update"));
+
+  fs::remove_file(bare.join("deno.json")).unwrap();
+  git(&bare, &["config", "huk.config", "HEAD:missing.json"]);
+  assert!(matches!(
+    server::load_config(&bare),
+    Err(ServerError::Git { .. })
+  ));
+}
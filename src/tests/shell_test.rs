@@ -0,0 +1,146 @@
+use crate::shell::SplitError;
+use crate::shell::join;
+use crate::shell::quote;
+use crate::shell::split;
+use crate::task::TaskSpec;
+use serde_json::json;
+use std::process::Command;
+
+const NASTY: &[&str] = &[
+  "",
+  " ",
+  "plain",
+  "with space",
+  "it's",
+  "''",
+  "\"double\"",
+  "$HOME",
+  "${PATH}",
+  "`whoami`",
+  "$(whoami)",
+  "a; rm -rf /",
+  "a && b || c",
+  "line\nbreak",
+  "tab\there",
+  "*.rs",
+  "[abc]?",
+  "~user",
+  "back\\slash",
+  "#not-a-comment",
+  "!bang",
+  "semi;colon|pipe&amp",
+  "<in >out",
+  "ünïcödé ✓",
+  "-n",
+];
+
+#[test]
+fn quote_leaves_safe_words_unchanged() {
+  for word in ["main", "origin", "./a/b.txt", "--flag=value", "a@b:c,d+e%"] {
+    assert_eq!(quote(word), word);
+  }
+}
+
+#[test]
+fn quote_wraps_empty_string() {
+  assert_eq!(quote(""), "''");
+}
+
+#[test]
+fn quote_escapes_single_quotes() {
+  assert_eq!(quote("it's"), r#"'it'\''s'"#);
+  assert_eq!(quote("'"), r#"''\'''"#);
+}
+
+#[test]
+fn quote_does_not_escape_double_quotes() {
+  assert_eq!(quote(r#"say "hi""#), r#"'say "hi"'"#);
+}
+
+#[test]
+fn quote_wraps_shell_metacharacters() {
+  for word in ["$HOME", "`id`", "a;b", "a\nb", "*.rs", "a b", "x|y", "~"] {
+    let quoted = quote(word);
+    assert!(
+      quoted.starts_with('\'') && quoted.ends_with('\''),
+      "{quoted}"
+    );
+  }
+}
+
+#[test]
+fn join_quotes_each_argument() {
+  assert_eq!(join(["a", "b c", "it's"]), r#"a 'b c' 'it'\''s'"#);
+  assert_eq!(join(Vec::<String>::new()), "");
+}
+
+#[test]
+fn quoted_arguments_round_trip_through_sh() {
+  for word in NASTY {
+    let script = format!("printf '%s' {}", quote(word));
+    let out = Command::new("sh").arg("-c").arg(&script).output().unwrap();
+    assert!(out.status.success(), "sh failed for {word:?}");
+    assert_eq!(String::from_utf8_lossy(&out.stdout), *word, "{script}");
+  }
+}
+
+#[test]
+fn joined_arguments_round_trip_through_sh() {
+  let script = format!("printf '%s\\0' {}", join(NASTY));
+  let out = Command::new("sh").arg("-c").arg(&script).output().unwrap();
+  assert!(out.status.success());
+  let stdout = String::from_utf8_lossy(&out.stdout);
+  let words: Vec<&str> = stdout.trim_end_matches('\0').split('\0').collect();
+  assert_eq!(words, NASTY);
+}
+
+#[test]
+fn split_inverts_join() {
+  let words: Vec<&str> =
+    NASTY.iter().copied().filter(|w| !w.is_empty()).collect();
+  assert_eq!(split(&join(&words)).unwrap(), words);
+}
+
+#[test]
+fn split_handles_quotes_and_escapes() {
+  assert_eq!(
+    split(r#"echo 'a b' "c \"d\" \$e" f\ g"#).unwrap(),
+    vec!["echo", "a b", r#"c "d" $e"#, "f g"]
+  );
+  assert_eq!(split(r#"a"b"'c'd"#).unwrap(), vec!["abcd"]);
+  assert_eq!(split(r#"say "\n""#).unwrap(), vec!["say", "\\n"]);
+  assert_eq!(split("  spaced \t out \n").unwrap(), vec!["spaced", "out"]);
+  assert_eq!(split("empty ''").unwrap(), vec!["empty", ""]);
+}
+
+#[test]
+fn split_reports_errors() {
+  assert_eq!(
+    split("echo 'oops"),
+    Err(SplitError::UnterminatedQuote('\'', "echo 'oops".into()))
+  );
+  assert_eq!(
+    split("echo \"oops"),
+    Err(SplitError::UnterminatedQuote('"', "echo \"oops".into()))
+  );
+  assert_eq!(
+    split("echo \\"),
+    Err(SplitError::TrailingBackslash("echo \\".into()))
+  );
+  assert_eq!(split("   "), Err(SplitError::Empty));
+}
+
+#[test]
+fn parse_task_spec_shell_flag() {
+  let spec = TaskSpec::from_json(&json!({"command": "cargo fmt"})).unwrap();
+  assert!(matches!(spec, TaskSpec::Detailed(object) if object.shell));
+
+  let v = json!({"command": "cargo fmt", "shell": false});
+  let spec = TaskSpec::from_json(&v).unwrap();
+  assert!(matches!(spec, TaskSpec::Detailed(ref object) if !object.shell));
+  assert_eq!(spec.to_json(), v);
+
+  assert!(
+    TaskSpec::from_json(&json!({"command": "x", "shell": "no"})).is_err()
+  );
+}
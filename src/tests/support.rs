@@ -0,0 +1,31 @@
+//! Helpers shared by tests that need a real Git repository.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Run `git` with `args` in `dir`, with a fixed identity so that commits
+/// work without any user configuration, and return its trimmed standard
+/// output. Panics if the command fails.
+pub fn git(dir: &Path, args: &[&str]) -> String {
+  let out = Command::new("git")
+    .args(args)
+    .current_dir(dir)
+    .env("GIT_AUTHOR_NAME", "huk")
+    .env("GIT_AUTHOR_EMAIL", "huk@example.com")
+    .env("GIT_COMMITTER_NAME", "huk")
+    .env("GIT_COMMITTER_EMAIL", "huk@example.com")
+    .output()
+    .unwrap();
+  assert!(out.status.success(), "git {args:?}");
+  String::from_utf8_lossy(&out.stdout).trim().to_string()
+}
+
+/// Write `content` to `file` in the repository at `dir` and commit it,
+/// returning the id of the new commit.
+pub fn commit(dir: &Path, file: &str, content: &str) -> String {
+  fs::write(dir.join(file), content).unwrap();
+  git(dir, &["add", file]);
+  git(dir, &["commit", "-q", "-m", file]);
+  git(dir, &["rev-parse", "HEAD"])
+}
@@ -0,0 +1,121 @@
+use crate::config::HookConfig;
+use crate::report::format_task_tree;
+use crate::runner::task_entries;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn task_entries_carry_metadata() {
+  let dir = tempdir().unwrap();
+  let package_json = dir.path().join("package.json");
+  fs::write(
+    &package_json,
+    r#"{
+  "hooks": {
+    "pre-commit": ["lint", "sh:lint"],
+    "pre-push": {"task": "test", "dependencies": ["lint"]}
+  },
+  "scripts": {
+    "lint": "eslint .",
+    "test": "vitest",
+    "build": "tsc"
+  },
+  "scriptsMeta": {"lint": "Lint sources", "test": {"description": "Unit tests"}},
+  "huk": {"tasks": {"lint": {"description": "ignored"}, "build": {"description": "Compile"}}}
+}"#,
+  )
+  .unwrap();
+  let cfg = HookConfig::discover(dir.path()).unwrap();
+  let tasks = task_entries(&cfg);
+  let names: Vec<&str> = tasks.iter().map(|t| t.name.as_str()).collect();
+  assert_eq!(names, ["build", "lint", "test"]);
+
+  let [build, lint, test] = &tasks[..] else {
+    unreachable!()
+  };
+  assert_eq!(lint.description.as_deref(), Some("Lint sources"));
+  assert_eq!(test.description.as_deref(), Some("Unit tests"));
+  assert_eq!(build.description.as_deref(), Some("Compile"));
+
+  assert_eq!(lint.hooks, ["pre-commit", "pre-push"]);
+  assert_eq!(test.hooks, ["pre-push"]);
+  assert!(build.hooks.is_empty());
+
+  let location = lint.location.as_ref().unwrap();
+  assert_eq!(location.path, package_json);
+  assert_eq!(location.line, 7);
+  assert_eq!(build.location.as_ref().unwrap().line, 9);
+
+  let json = serde_json::to_value(lint).unwrap();
+  assert_eq!(json["location"]["line"], 7);
+  assert_eq!(json["hooks"][1], "pre-push");
+}
+
+#[test]
+fn tree_shows_dependencies() {
+  let dir = tempdir().unwrap();
+  fs::write(
+    dir.path().join("deno.json"),
+    r#"{
+      "tasks": {
+        "ci": {"description": "Everything", "dependencies": ["check", "test"]},
+        "check": {"dependencies": ["fmt", "lnit"]},
+        "fmt": "deno fmt",
+        "test": "deno test",
+        "loop": {"dependencies": ["again"]},
+        "again": {"dependencies": ["loop"]}
+      }
+    }"#,
+  )
+  .unwrap();
+  let cfg = HookConfig::discover(dir.path()).unwrap();
+  let tree = format_task_tree(&task_entries(&cfg), false);
+  assert_eq!(
+    tree,
+    "ci - Everything\n\
+     ├── check\n\
+     │   ├── fmt\n\
+     │   └── lnit (missing)\n\
+     └── test\n\
+     again\n\
+     └── loop\n    \
+     └── again (circular)\n"
+  );
+}
+
+#[test]
+fn hooks_are_attached_to_the_entry_a_reference_resolves_to() {
+  let dir = tempdir().unwrap();
+  fs::write(
+    dir.path().join("package.json"),
+    r#"{
+  "hooks": {
+    "pre-commit": "lint",
+    "pre-push": "script:lint",
+    "pre-merge-commit": {"task": "check"}
+  },
+  "scripts": {"lint": "eslint .", "fmt": "prettier --check ."},
+  "huk": {
+    "tasks": {
+      "lint": "cargo clippy",
+      "check": {"command": "true", "dependencies": ["script:fmt", "lint"]}
+    }
+  }
+}"#,
+  )
+  .unwrap();
+  let cfg = HookConfig::discover(dir.path()).unwrap();
+  let tasks = task_entries(&cfg);
+  let hooks = |name: &str, kind: &str| {
+    tasks
+      .iter()
+      .find(|t| t.name == name && t.kind == kind)
+      .unwrap()
+      .hooks
+      .clone()
+  };
+  assert_eq!(hooks("lint", "huk"), ["pre-commit", "pre-merge-commit"]);
+  assert_eq!(hooks("lint", "script"), ["pre-push"]);
+  assert_eq!(hooks("check", "huk"), ["pre-merge-commit"]);
+  assert_eq!(hooks("fmt", "script"), ["pre-merge-commit"]);
+}
@@ -1,8 +1,8 @@
 //! Terminal user interface for `huk`.
 //!
-//! Provides an interactive dashboard for browsing hooks, running them with
-//! confirmation and captured output, and editing hook definitions without
-//! leaving the terminal.
+//! Provides an interactive dashboard for browsing hooks and tasks, running
+//! them with confirmation and captured output, and editing hook definitions
+//! without leaving the terminal.
 
 #![allow(dead_code)]
 
@@ -50,8 +50,10 @@ use crate::config::*;
 use crate::constants::VERSION;
 use crate::runner::OutputChunk;
 use crate::runner::RunnerError;
+use crate::runner::TaskEntry;
 use crate::runner::TaskRunner;
 use crate::runner::mutate_hooks;
+use crate::runner::task_entries;
 use crate::task::TaskSpec;
 
 const LOG_LIMIT: usize = 2000;
@@ -160,6 +162,28 @@ fn format_spec(spec: &TaskSpec) -> String {
   serde_json::to_string(&spec.to_json()).unwrap_or_else(|_| spec.to_string())
 }
 
+/// Describe a task for the detail pane.
+fn format_task(task: &TaskEntry) -> String {
+  let mut text = format!("Task: {} ({})\n", task.name, task.kind);
+  if let Some(description) = &task.description {
+    text.push_str(&format!("{description}\n"));
+  }
+  text.push('\n');
+  if let Some(command) = &task.command {
+    text.push_str(&format!("$ {command}\n"));
+  }
+  if !task.dependencies.is_empty() {
+    text.push_str(&format!("depends on: {}\n", task.dependencies.join(", ")));
+  }
+  if !task.hooks.is_empty() {
+    text.push_str(&format!("used by: {}\n", task.hooks.join(", ")));
+  }
+  if let Some(location) = &task.location {
+    text.push_str(&format!("defined at: {location}\n"));
+  }
+  text
+}
+
 fn editable_spec(spec: &TaskSpec) -> String {
   match spec {
     TaskSpec::Single(s) => s.clone(),
@@ -172,29 +196,38 @@ fn editable_spec(spec: &TaskSpec) -> String {
 pub enum Focus {
   #[default]
   Hooks,
+  Tasks,
   Output,
 }
 
 impl Focus {
   fn next(self) -> Self {
     match self {
-      Focus::Hooks => Focus::Output,
+      Focus::Hooks => Focus::Tasks,
+      Focus::Tasks => Focus::Output,
       Focus::Output => Focus::Hooks,
     }
   }
 
   fn prev(self) -> Self {
-    self.next()
+    match self {
+      Focus::Hooks => Focus::Output,
+      Focus::Tasks => Focus::Hooks,
+      Focus::Output => Focus::Tasks,
+    }
   }
 }
 
 /// Internal state for the dashboard.
 #[derive(Clone, Constructor)]
+#[allow(clippy::too_many_arguments)]
 pub struct DashboardState<'a> {
   pub cwd:        &'a Path,
   pub running:    bool,
   pub hooks:      Vec<(String, TaskSpec)>,
   pub index:      usize,
+  pub tasks:      Vec<TaskEntry>,
+  pub task_index: usize,
   pub logs:       Vec<LogEntry>,
   pub prompt:     Option<Prompt>,
   pub focus:      Focus,
@@ -209,6 +242,8 @@ impl<'a> Default for DashboardState<'a> {
       running:    false,
       hooks:      Vec::new(),
       index:      0,
+      tasks:      Vec::new(),
+      task_index: 0,
       logs:       Vec::new(),
       prompt:     None,
       focus:      Focus::Hooks,
@@ -235,6 +270,7 @@ where
   fn refresh_config(&mut self) -> Result<(), RunnerError>;
   fn remove_hook(&mut self, name: &str) -> Result<(), RunnerError>;
   fn run_hook(&mut self, name: &str) -> Result<(), RunnerError>;
+  fn run_task(&mut self, name: &str) -> Result<(), RunnerError>;
   fn update_hook<T: TryInto<TaskSpec>>(
     &mut self,
     name: &str,
@@ -250,12 +286,10 @@ impl<'a> HookManager<'a> for DashboardState<'a> {
   }
 
   fn selected_hook(&'a self) -> Option<(CowStr<'a>, &'a TaskSpec)> {
-    self.hooks.get(self.index).map(|(name, spec)| {
-      (
-        CowStr::try_from(name.as_str()).unwrap_or_else(|_| CowStr::from("")),
-        spec,
-      )
-    })
+    self
+      .hooks
+      .get(self.index)
+      .map(|(name, spec)| (CowStr::from(name.as_str()), spec))
   }
 
   fn add_hook<T: TryInto<TaskSpec>>(
@@ -347,6 +381,28 @@ impl<'a> HookManager<'a> for DashboardState<'a> {
     }
     Ok(())
   }
+
+  fn run_task(&mut self, name: &str) -> Result<(), RunnerError> {
+    let cfg = HookConfig::discover(self.cwd)?;
+    self.apply_config(&cfg);
+    if !self.tasks.iter().any(|task| task.name == name) {
+      self.push_log(LogLevel::Error, format!("Task '{name}' not found."));
+      return Ok(());
+    }
+    let mut runner = TaskRunner::new_with_capture(&cfg);
+    self.running = true;
+    self.push_log(LogLevel::Info, format!("Running task '{name}'..."));
+    let result = runner.run_single(name, &[]);
+    self.running = false;
+    let output = runner.take_output();
+    self.append_output(output);
+    if let Err(err) = result {
+      self.push_log(LogLevel::Error, format!("{err}"));
+    } else {
+      self.push_log(LogLevel::Success, format!("Task '{name}' finished."));
+    }
+    Ok(())
+  }
 }
 
 impl<'a> Runnable<'a> for DashboardState<'a> {
@@ -396,23 +452,27 @@ impl<'a> Runnable<'a> for DashboardState<'a> {
               Tab => self.focus = self.focus.next(),
               BackTab => self.focus = self.focus.prev(),
               Up => match self.focus {
-                Focus::Hooks => self.move_selection_up(),
+                Focus::Hooks | Focus::Tasks => self.move_selection_up(),
                 Focus::Output => self.scroll_logs(1),
               },
               Down => match self.focus {
-                Focus::Hooks => self.move_selection_down(),
+                Focus::Hooks | Focus::Tasks => self.move_selection_down(),
                 Focus::Output => self.scroll_logs(-1),
               },
               Home => match self.focus {
                 Focus::Hooks => self.index = 0,
+                Focus::Tasks => self.task_index = 0,
                 Focus::Output => self.scroll_to_log_start(),
               },
               End => match self.focus {
                 Focus::Hooks => self.index = self.hooks.len().saturating_sub(1),
+                Focus::Tasks => {
+                  self.task_index = self.tasks.len().saturating_sub(1)
+                }
                 Focus::Output => self.scroll_to_log_end(),
               },
               PageUp => match self.focus {
-                Focus::Hooks => {
+                Focus::Hooks | Focus::Tasks => {
                   for _ in 0..3 {
                     self.move_selection_up();
                   }
@@ -420,7 +480,7 @@ impl<'a> Runnable<'a> for DashboardState<'a> {
                 Focus::Output => self.scroll_logs(5),
               },
               PageDown => match self.focus {
-                Focus::Hooks => {
+                Focus::Hooks | Focus::Tasks => {
                   for _ in 0..3 {
                     self.move_selection_down();
                   }
@@ -430,6 +490,12 @@ impl<'a> Runnable<'a> for DashboardState<'a> {
               Char('r') | Char('R') | F(5) => {
                 self.refresh_config()?;
               }
+              Enter if self.focus == Focus::Tasks => {
+                if let Some(task) = self.current_task() {
+                  let prompt = Prompt::confirm_run_task(task.name.clone());
+                  self.set_prompt(prompt)?;
+                }
+              }
               Enter => {
                 if let Some((name, _)) = self.current_hook() {
                   let prompt = Prompt::confirm_run(name.to_string());
@@ -481,9 +547,10 @@ impl Drawable for DashboardState<'_> {
       .split(f.area());
 
     let title = format!(
-      " huk dashboard — {} — {} hooks",
+      " huk dashboard — {} — {} hooks, {} tasks",
       self.source,
-      self.hooks.len()
+      self.hooks.len(),
+      self.tasks.len()
     );
     let header = Paragraph::new(Text::from(title))
       .style(Style::default().add_modifier(Modifier::BOLD))
@@ -500,6 +567,10 @@ impl Drawable for DashboardState<'_> {
       .direction(Direction::Horizontal)
       .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
       .split(layout[1]);
+    let lists = Layout::default()
+      .direction(Direction::Vertical)
+      .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+      .split(main[0]);
 
     let hook_items: Vec<ListItem> = self
       .hooks
@@ -530,14 +601,57 @@ impl Drawable for DashboardState<'_> {
         .padding(Padding::uniform(1))
         .title("Hooks (↑/↓ to move, Enter to run, a/e/d to add/edit/delete, r to reload, q to quit)"),
     );
-    f.render_widget(list, main[0]);
+    f.render_widget(list, lists[0]);
 
-    let spec_text = if let Some((name, spec)) = self.current_hook() {
+    let task_items: Vec<ListItem> = self
+      .tasks
+      .iter()
+      .enumerate()
+      .map(|(i, task)| {
+        let selected = i == self.task_index && self.focus == Focus::Tasks;
+        let marker = if selected { "›" } else { " " };
+        let style = if selected {
+          Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD)
+        } else {
+          Style::default()
+        };
+        ListItem::new(Line::from(vec![
+          Span::styled(format!("{marker} {}", task.name), style),
+          Span::styled(
+            format!(" ({})", task.kind),
+            Style::default().fg(Color::DarkGray),
+          ),
+        ]))
+      })
+      .collect();
+    let task_list = List::new(task_items).block(
+      Block::default()
+        .borders(Borders::ALL)
+        .border_style(if self.focus == Focus::Tasks {
+          Style::default().fg(Color::Yellow)
+        } else {
+          Style::default()
+        })
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(1))
+        .title("Tasks (Enter to run)"),
+    );
+    f.render_widget(task_list, lists[1]);
+
+    let (detail_title, spec_text) = if self.focus == Focus::Tasks {
+      let text = match self.current_task() {
+        Some(task) => format_task(task),
+        None => "No tasks defined.".into(),
+      };
+      ("Task", text)
+    } else if let Some((name, spec)) = self.current_hook() {
       let mut text = format!("Hook: {name}\n\n");
       text.push_str(&format_spec(spec));
-      text
+      ("Task Specification", text)
     } else {
-      "No hooks configured.".into()
+      ("Task Specification", "No hooks configured.".into())
     };
     let detail = Paragraph::new(spec_text)
       .block(
@@ -545,7 +659,7 @@ impl Drawable for DashboardState<'_> {
           .borders(Borders::ALL)
           .border_type(BorderType::Rounded)
           .padding(Padding::uniform(1))
-          .title("Task Specification"),
+          .title(detail_title),
       )
       .wrap(ratatui::widgets::Wrap { trim: true });
     f.render_widget(detail, main[1]);
@@ -652,6 +766,8 @@ impl<'a> DashboardState<'a> {
       cwd: cfg.source.as_path().parent().unwrap_or(Path::new(".")),
       hooks,
       index: 0,
+      tasks: task_entries(cfg),
+      task_index: 0,
       running: false,
       logs: Vec::new(),
       prompt: None,
@@ -674,6 +790,10 @@ impl<'a> DashboardState<'a> {
     if self.index >= self.hooks.len() && !self.hooks.is_empty() {
       self.index = self.hooks.len() - 1;
     }
+    self.tasks = task_entries(cfg);
+    if self.task_index >= self.tasks.len() && !self.tasks.is_empty() {
+      self.task_index = self.tasks.len() - 1;
+    }
     self.source = cfg.source.as_str().to_string();
   }
 
@@ -681,13 +801,32 @@ impl<'a> DashboardState<'a> {
     self.hooks.get(self.index).map(|(name, spec)| (name, spec))
   }
 
+  pub fn current_task(&self) -> Option<&TaskEntry> {
+    self.tasks.get(self.task_index)
+  }
+
+  /// Move the selection of the focused list (hooks unless the task list is
+  /// focused) up by one.
   pub fn move_selection_up(&mut self) {
-    self.index = self.index.saturating_sub(1);
+    match self.focus {
+      Focus::Tasks => self.task_index = self.task_index.saturating_sub(1),
+      _ => self.index = self.index.saturating_sub(1),
+    }
   }
 
+  /// Move the selection of the focused list down by one.
   pub fn move_selection_down(&mut self) {
-    if self.index + 1 < self.hooks.len() {
-      self.index += 1;
+    match self.focus {
+      Focus::Tasks => {
+        if self.task_index + 1 < self.tasks.len() {
+          self.task_index += 1;
+        }
+      }
+      _ => {
+        if self.index + 1 < self.hooks.len() {
+          self.index += 1;
+        }
+      }
     }
   }
 
@@ -782,7 +921,7 @@ impl<'a> DashboardState<'a> {
     if let Some(prompt) = &self.prompt {
       let inner_width = width.saturating_sub(2).max(1);
       let height = prompt.visual_height(inner_width);
-      height.max(3).min(10)
+      height.clamp(3, 10)
     } else {
       3
     }
@@ -832,6 +971,18 @@ impl<'a> DashboardState<'a> {
           return Ok(true);
         }
       },
+      PromptKind::ConfirmRunTask(name) => match code {
+        Char('y') | Enter => {
+          if let Err(err) = self.run_task(&name) {
+            self.push_log(LogLevel::Error, format!("{err}"));
+          }
+        }
+        Char('n') | Char('\x04') | Char('\x03') | Esc => {}
+        _ => {
+          self.set_prompt(prompt)?;
+          return Ok(true);
+        }
+      },
       PromptKind::ConfirmRemove(name) => match code {
         Char('y') | Enter => {
           if let Err(err) = self.remove_hook(&name) {
@@ -975,6 +1126,14 @@ impl Prompt {
     }
   }
 
+  pub fn confirm_run_task(name: String) -> Self {
+    Self {
+      kind: PromptKind::ConfirmRunTask(name.clone()),
+      label: format!("Run task '{name}'? (y/n)"),
+      ..Default::default()
+    }
+  }
+
   pub fn confirm_remove(name: String) -> Self {
     Self {
       kind: PromptKind::ConfirmRemove(name.clone()),
@@ -1001,11 +1160,10 @@ impl Prompt {
 
   pub fn update_hook(hook: String, preset: String) -> Self {
     Self {
-      kind: PromptKind::Update { hook: hook.clone() },
-      label: format!("New spec for '{hook}'"),
-      buffer: preset.clone(),
+      kind:         PromptKind::Update { hook: hook.clone() },
+      label:        format!("New spec for '{hook}'"),
+      buffer:       preset.clone(),
       cursor_index: preset.len(),
-      ..Default::default()
     }
   }
 
@@ -1022,6 +1180,7 @@ impl Prompt {
 #[derive(Clone)]
 pub enum PromptKind {
   ConfirmRun(String),
+  ConfirmRunTask(String),
   ConfirmRemove(String),
   AddName,
   AddSpec { hook: String },
@@ -1069,9 +1228,10 @@ impl Prompt {
 
   fn index_for_column(&self, line_start: usize, target_col: usize) -> usize {
     let line_start = line_start.min(self.buffer.len());
-    let mut col = 0;
     let mut idx = line_start;
-    for (offset, ch) in self.buffer[line_start..].char_indices() {
+    for (col, (offset, ch)) in
+      self.buffer[line_start..].char_indices().enumerate()
+    {
       if ch == '\n' {
         break;
       }
@@ -1079,7 +1239,6 @@ impl Prompt {
         idx = line_start + offset;
         return idx;
       }
-      col += 1;
       idx = line_start + offset + ch.len_utf8();
     }
     idx